@@ -0,0 +1,74 @@
+//! Throughput of OpenPGP CFB encryption on a multi-megabyte message
+//!
+//! Compares holding one expanded AES key for the whole message against
+//! expanding the key again for every block, which is what the CFB layer
+//! used to do.
+//!
+//! Run with `cargo bench --bench openpgp_cfb`.
+use std::time::{Duration, Instant};
+
+use aes::aes128::Aes128;
+
+use rpg::crypto::openpgp_cfb::OpenPgpCfbAes128;
+
+const MESSAGE_SIZE: usize = 8 * 1024 * 1024;
+const RUNS: usize = 3;
+
+fn main() {
+    let key = [0x42; 16];
+    let plaintext = vec![0x17; MESSAGE_SIZE];
+
+    println!("Encrypting {} MiB, best of {} runs:", MESSAGE_SIZE / 1024 / 1024, RUNS);
+
+    let reused = measure("One key schedule per message", || {
+        OpenPgpCfbAes128::with_key(&key)
+            .and_then(|cfb| cfb.encrypt_without_resync(&plaintext))
+            .expect("Failed to encrypt.")
+    });
+
+    let expanded = measure("One key schedule per block", || {
+        encrypt_expanding_key_per_block(&plaintext, key)
+    });
+
+    println!("Speedup: {:.1}x", expanded.as_secs_f64() / reused.as_secs_f64());
+}
+
+fn measure<F: Fn() -> Vec<u8>>(name: &str, encrypt: F) -> Duration {
+    let best = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            let ciphertext = encrypt();
+            let elapsed = start.elapsed();
+
+            assert_eq!(ciphertext.len(), MESSAGE_SIZE);
+
+            elapsed
+        })
+        .min()
+        .unwrap()
+    ;
+
+    let throughput = MESSAGE_SIZE as f64 / 1024.0 / 1024.0 / best.as_secs_f64();
+
+    println!("{:>30}: {:>8.1} ms, {:>7.2} MiB/s", name, best.as_secs_f64() * 1000.0, throughput);
+
+    best
+}
+
+/// Plain CFB, creating a new cipher for every block
+fn encrypt_expanding_key_per_block(plaintext: &[u8], key: [u8; 16]) -> Vec<u8> {
+    let mut ciphertext = Vec::with_capacity(plaintext.len());
+    let mut feedback = [0; 16];
+
+    for block in plaintext.chunks(16) {
+        let keystream = Aes128::with_key(key).encrypt_block(feedback);
+
+        for (i, octet) in block.iter().enumerate() {
+            feedback[i] = octet ^ keystream[i];
+        }
+
+        ciphertext.extend(&feedback[..block.len()]);
+    }
+
+    ciphertext
+}
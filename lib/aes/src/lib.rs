@@ -99,7 +99,7 @@ macro_rules! define_aes_cipher {
                 }
 
                 /// Encrypt a single block
-                pub fn encrypt_block(self, plaintext: [u8; Self::BS]) -> [u8; Self::BS] {
+                pub fn encrypt_block(&self, plaintext: [u8; Self::BS]) -> [u8; Self::BS] {
                     let mut state: State = plaintext.into();
 
                     state.add_round_key(self.round_key(0));
@@ -131,7 +131,7 @@ macro_rules! define_aes_cipher {
                 }
 
                 /// Decrypt a single block
-                pub fn decrypt_block(self, ciphertext: [u8; Self::BS]) -> [u8; Self::BS] {
+                pub fn decrypt_block(&self, ciphertext: [u8; Self::BS]) -> [u8; Self::BS] {
                     let mut state: State = ciphertext.into();
 
                     state.add_round_key(self.round_key(Self::Nr));
@@ -199,6 +199,15 @@ mod tests {
         assert_eq!(actual_plaintext, expected_plaintext);
     }
 
+    #[test]
+    fn aes_128_reuse_cipher_for_multiple_blocks() {
+        let aes = Aes128::with_key(hex!("2B7E1516 28AED2A6 ABF71588 09CF4F3C"));
+
+        assert_eq!(aes.encrypt_block(hex!("3243F6A8 885A308D 313198A2 E0370734")), hex!("3925841D 02DC09FB DC118597 196A0B32"));
+        assert_eq!(aes.decrypt_block(hex!("3925841D 02DC09FB DC118597 196A0B32")), hex!("3243F6A8 885A308D 313198A2 E0370734"));
+        assert_eq!(aes.encrypt_block(hex!("3243F6A8 885A308D 313198A2 E0370734")), hex!("3925841D 02DC09FB DC118597 196A0B32"));
+    }
+
     #[test]
     fn aes_128_expand_keys() {
         assert_eq!(Aes128::EXPANDED_KEY_WORD_COUNT, 44);
@@ -0,0 +1,354 @@
+use crate::ArmorChecksum;
+use crate::ArmorDataHeader;
+use crate::ArmorDataHeaderMap;
+use crate::ArmorDataType;
+use crate::ArmorError;
+use crate::format::MAX_LINE_LENGTH;
+
+/// How strictly `Armor::parse` follows RFC 4880
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArmorParseMode {
+    /// Reject anything RFC 4880 does not allow: header lines that are not
+    /// "Key: Value", invalid header keys, a missing blank line after the
+    /// headers, a footer line not matching the header line, Radix-64 lines
+    /// longer than 76 characters and whitespace within them
+    Strict,
+
+    /// Accept what GnuPG accepts: invalid headers are skipped, data may
+    /// start without a blank line, and lines may be of any length
+    Lenient,
+}
+
+/// Parsed ASCII Armor
+///
+/// # Examples
+/// ```rust
+/// use ascii_armor::{Armor, ArmorDataType, ArmorError, ArmorParseMode};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let input = "\
+///     -----BEGIN PGP MESSAGE-----\n\
+///     Version: Test\n\
+///     \n\
+///     SGVsbG8=\n\
+///     =EHJM\n\
+///     -----END PGP MESSAGE-----\n\
+/// ";
+///
+/// let armor = Armor::parse(input, ArmorParseMode::Strict)?;
+///
+/// assert_eq!(armor.data_type, ArmorDataType::PgpMessage);
+/// assert_eq!(armor.data, b"Hello");
+///
+/// let error = Armor::parse(&input.replace("SGVs", "SG*s"), ArmorParseMode::Lenient).unwrap_err();
+///
+/// assert_eq!(error.to_string(), "Line 4, column 3: Invalid character `*` in Radix-64 data.");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Links
+/// - [RFC 4880, Section 6.2: Forming ASCII Armor](https://tools.ietf.org/html/rfc4880#section-6.2)
+#[derive(Debug, PartialEq)]
+pub struct Armor {
+    pub data_type: ArmorDataType,
+    pub data_headers: ArmorDataHeaderMap,
+    pub data: Vec<u8>,
+
+    /// The checksum, verified against the data, if the armor has one
+    pub checksum: Option<ArmorChecksum>,
+}
+
+impl Armor {
+    /// Parse the first ASCII Armor in the input
+    ///
+    /// Text before the header line and after the footer line is ignored.
+    /// Errors for malformed input are `ArmorError::Syntax`, with the line
+    /// and column they were found at.
+    pub fn parse(input: &str, mode: ArmorParseMode) -> Result<Self, ArmorError> {
+        let strict = mode == ArmorParseMode::Strict;
+
+        let mut lines = input
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .zip(1..)
+        ;
+
+        let data_type = loop {
+            let (line, number) = lines.next().ok_or(ArmorError::ReaderMissingHeaderLine)?;
+
+            if let Some(data_type) = boundary_line(line, "BEGIN", strict) {
+                break ArmorDataType::from_str(data_type)
+                    .map_err(|error| syntax_error(number, "-----BEGIN ".len() + 1, error))?
+            }
+        };
+
+        let mut data_headers = ArmorDataHeaderMap::new();
+        let mut first_data_line = None;
+
+        for (line, number) in lines.by_ref() {
+            if line.trim().is_empty() {
+                break
+            }
+
+            let (key, value) = match line.split_once(':') {
+                Some(header) => header,
+                None if strict => return Err(syntax_error(number, 1, ArmorError::ReaderMissingBlankLine)),
+                None => {
+                    first_data_line = Some((line, number));
+                    break
+                },
+            };
+
+            if strict && (key.is_empty() || key.trim() != key || !(value.is_empty() || value.starts_with(' '))) {
+                return Err(syntax_error(number, 1, ArmorError::ReaderInvalidHeaderLine(line.into())))
+            }
+
+            match ArmorDataHeader::from_str(key.trim()) {
+                Ok(key) => data_headers.add(key, value.trim()),
+                Err(error) if strict => return Err(syntax_error(number, 1, error)),
+                Err(_) => {},
+            }
+        }
+
+        let mut encoded = String::new();
+        let mut checksum = None;
+
+        for (line, number) in first_data_line.into_iter().chain(lines.by_ref()) {
+            if let Some(footer) = boundary_line(line, "END", strict) {
+                if strict && footer != data_type.to_string() {
+                    let error = ArmorError::ReaderMismatchedFooterLine(footer.into());
+                    return Err(syntax_error(number, "-----END ".len() + 1, error))
+                }
+
+                return Self::decode(data_type, data_headers, &encoded, checksum, number)
+            }
+
+            let line = line.trim_end();
+
+            if line.is_empty() {
+                continue
+            }
+
+            if checksum.is_some() {
+                return Err(syntax_error(number, 1, ArmorError::ReaderMissingFooterLine))
+            }
+
+            if strict && line.chars().count() > MAX_LINE_LENGTH {
+                return Err(syntax_error(number, MAX_LINE_LENGTH + 1, ArmorError::ReaderLineTooLong(MAX_LINE_LENGTH)))
+            }
+
+            if line.starts_with('=') {
+                let parsed = ArmorChecksum::new(line).map_err(|error| syntax_error(number, 1, error))?;
+                checksum = Some((parsed, number));
+                continue
+            }
+
+            let padded = encoded.ends_with('=');
+
+            for (c, column) in line.chars().zip(1..) {
+                if c.is_whitespace() && !strict {
+                    continue
+                }
+
+                if !is_radix64(c) || (padded || encoded.ends_with('=')) && c != '=' {
+                    return Err(syntax_error(number, column, ArmorError::ReaderInvalidCharacter(c)))
+                }
+
+                encoded.push(c);
+            }
+        }
+
+        let last_line = input.split('\n').count();
+
+        Err(syntax_error(last_line, 1, ArmorError::ReaderMissingFooterLine))
+    }
+
+    fn decode(
+        data_type: ArmorDataType,
+        data_headers: ArmorDataHeaderMap,
+        encoded: &str,
+        checksum: Option<(ArmorChecksum, usize)>,
+        footer_line: usize,
+    ) -> Result<Self, ArmorError> {
+        if encoded.len() % 4 == 1 {
+            return Err(syntax_error(footer_line, 1, ArmorError::ReaderTruncatedData))
+        }
+
+        let data = base64::decode(encoded.as_bytes())
+            .map_err(|_| syntax_error(footer_line, 1, ArmorError::ReaderTruncatedData))?
+        ;
+
+        if let Some((checksum, number)) = checksum {
+            if !checksum.verify(&data) {
+                return Err(syntax_error(number, 1, ArmorError::ChecksumMismatch(checksum.get())))
+            }
+        }
+
+        Ok(Self {
+            data_type,
+            data_headers,
+            data,
+            checksum: checksum.map(|(checksum, _)| checksum),
+        })
+    }
+}
+
+/// Get the data type of a "-----BEGIN X-----" or "-----END X-----" line
+///
+/// Unless strict, any number of dashes may end the line.
+pub(crate) fn boundary_line<'a>(line: &'a str, kind: &str, strict: bool) -> Option<&'a str> {
+    let data_type = line.trim_end()
+        .strip_prefix("-----")?
+        .strip_prefix(kind)?
+        .strip_prefix(' ')?
+    ;
+
+    match strict {
+        true => data_type.strip_suffix("-----"),
+        false => Some(data_type.trim_end_matches('-')),
+    }
+}
+
+fn is_radix64(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '+' || c == '/' || c == '='
+}
+
+fn syntax_error(line: usize, column: usize, error: ArmorError) -> ArmorError {
+    ArmorError::Syntax {
+        line,
+        column,
+        error: Box::new(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    const ARMOR: &str = "\
+        -----BEGIN PGP MESSAGE-----\r\n\
+        Version: OpenPrivacy 0.99\r\n\
+        Comment: Hello\r\n\
+        \r\n\
+        SGVsbG8sIGJl\r\n\
+        YXV0aWZ1bCB3b3JsZCE=\r\n\
+        =4oUH\r\n\
+        -----END PGP MESSAGE-----\r\n\
+    ";
+
+    fn error_at(input: &str, mode: ArmorParseMode) -> (usize, usize, ArmorError) {
+        match Armor::parse(input, mode) {
+            Err(ArmorError::Syntax { line, column, error }) => (line, column, *error),
+            x => panic!("Expected syntax error, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn parse_in_both_modes() {
+        for mode in [ArmorParseMode::Strict, ArmorParseMode::Lenient].iter() {
+            let armor = Armor::parse(ARMOR, *mode).unwrap();
+
+            assert_eq!(armor.data_type, ArmorDataType::PgpMessage);
+            assert_eq!(armor.data, b"Hello, beautiful world!");
+            assert_eq!(armor.checksum, Some(ArmorChecksum::new("=4oUH").unwrap()));
+            assert_eq!(
+                armor.data_headers.get(&ArmorDataHeader::Version),
+                Some(vec!["OpenPrivacy 0.99"])
+            );
+        }
+    }
+
+    #[test]
+    fn custom_data_headers_survive_parse_and_write() {
+        let input = ARMOR.replace("Comment: Hello\r\n", "X-Zebra: One\r\nComment: Hello\r\nX-Alpha: Two\r\n");
+        let armor = Armor::parse(&input, ArmorParseMode::Strict).unwrap();
+
+        let mut builder = crate::ArmorWriterBuilder::new()
+            .data_type(armor.data_type.clone())
+            .data(&armor.data)
+        ;
+
+        for (key, value) in &armor.data_headers {
+            builder = builder.add_data_header(key.clone(), value);
+        }
+
+        let mut buffer: Vec<u8> = vec![];
+        builder.build().write_unchecked(&mut buffer).unwrap();
+
+        // The writer wraps at 64 columns, the input at 12
+        assert_eq!(String::from_utf8(buffer).unwrap(), input.replace("SGVsbG8sIGJl\r\n", "SGVsbG8sIGJl"));
+    }
+
+    #[test]
+    fn interleaved_data_headers_survive_parse_and_write() {
+        let input = ARMOR.replace("Version: OpenPrivacy 0.99\r\n", "Comment: a\r\nVersion: v\r\n");
+        let input = input.replace("Comment: Hello\r\n", "Comment: b\r\n");
+        let armor = Armor::parse(&input, ArmorParseMode::Strict).unwrap();
+
+        assert_eq!(armor.data_headers.get(&ArmorDataHeader::Comment), Some(vec!["a", "b"]));
+
+        let mut encoder = crate::ArmorEncoder::new(vec![], armor.data_type.clone());
+
+        for (key, value) in &armor.data_headers {
+            encoder = encoder.add_data_header(key.clone(), value);
+        }
+
+        encoder.write_all(&armor.data).unwrap();
+        let output = String::from_utf8(encoder.finish().unwrap()).unwrap();
+
+        assert!(output.contains("-----\r\nComment: a\r\nVersion: v\r\nComment: b\r\n\r\n"), "{}", output);
+        assert_eq!(Armor::parse(&output, ArmorParseMode::Strict).unwrap(), armor);
+    }
+
+    #[test]
+    fn parse_file_with_short_footer_line() {
+        let input = std::fs::read_to_string("tests/resources/gnupg-icon.png.asc").unwrap();
+        let armor = Armor::parse(&input, ArmorParseMode::Lenient).unwrap();
+
+        assert_eq!(armor.data, std::fs::read("tests/resources/gnupg-icon.png").unwrap());
+
+        // "-----END PGP MESSAGE----" is not a footer line
+        assert_eq!(error_at(&input, ArmorParseMode::Strict), (44, 1, ArmorError::ReaderMissingFooterLine));
+    }
+
+    #[test]
+    fn lenient_mode_tolerates_what_strict_mode_rejects() {
+        let cases = [
+            (ARMOR.replace("Comment: Hello", "Bad Key: Hello"), (3, 1, ArmorError::UnknownDataHeader("Bad Key".into()))),
+            (ARMOR.replace("Comment: Hello", "Comment:Hello"), (3, 1, ArmorError::ReaderInvalidHeaderLine("Comment:Hello".into()))),
+            (ARMOR.replace("Hello\r\n\r\n", "Hello\r\n"), (4, 1, ArmorError::ReaderMissingBlankLine)),
+            (ARMOR.replace("END PGP MESSAGE", "END PGP SIGNATURE"), (8, 10, ArmorError::ReaderMismatchedFooterLine("PGP SIGNATURE".into()))),
+            (ARMOR.replace("SGVsbG8sIGJl", &"SGVsbG8sIGJl".repeat(7)).replace("=4oUH\r\n", ""), (5, 77, ArmorError::ReaderLineTooLong(76))),
+            (ARMOR.replace("SGVsbG8sIGJl", "SGVsbG8s IGJl"), (5, 9, ArmorError::ReaderInvalidCharacter(' '))),
+        ];
+
+        for (input, expected) in cases.iter() {
+            assert_eq!(error_at(input, ArmorParseMode::Strict), *expected);
+            assert!(Armor::parse(input, ArmorParseMode::Lenient).is_ok(), "{:?}", expected);
+        }
+    }
+
+    #[test]
+    fn errors_in_both_modes() {
+        let cases = [
+            (ARMOR.replace("SGVs", "SG*s"), (5, 3, ArmorError::ReaderInvalidCharacter('*'))),
+            (ARMOR.replace("YXV0aWZ1bCB3b3JsZCE=", "YXV0aWZ1bCB3b3JsZCE=\r\nSGVs"), (7, 1, ArmorError::ReaderInvalidCharacter('S'))),
+            (ARMOR.replace("=4oUH", "=uizE"), (7, 1, ArmorError::ChecksumMismatch("=uizE".into()))),
+            (ARMOR.replace("=4oUH", "=4o"), (7, 1, ArmorError::InvalidChecksum("=4o".into()))),
+            (ARMOR.replace("=4oUH", "=4oUH\r\nSGVs"), (8, 1, ArmorError::ReaderMissingFooterLine)),
+            (ARMOR.replace("-----END PGP MESSAGE-----\r\n", ""), (8, 1, ArmorError::ReaderMissingFooterLine)),
+            (ARMOR.replace("BEGIN PGP MESSAGE", "BEGIN PGP NONSENSE"), (1, 12, ArmorError::UnknownDataType("PGP NONSENSE".into()))),
+            (ARMOR.replace("YXV0aWZ1bCB3b3JsZCE=", "Y"), (8, 1, ArmorError::ReaderTruncatedData)),
+        ];
+
+        for (input, expected) in cases.iter() {
+            assert_eq!(error_at(input, ArmorParseMode::Lenient), *expected);
+            assert_eq!(error_at(input, ArmorParseMode::Strict), *expected);
+        }
+
+        assert_eq!(Armor::parse("Hello", ArmorParseMode::Lenient), Err(ArmorError::ReaderMissingHeaderLine));
+    }
+}
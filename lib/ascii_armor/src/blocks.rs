@@ -0,0 +1,280 @@
+use std::io;
+use std::io::Read;
+use std::ops::Range;
+
+use crate::{ArmorChecksum, ArmorChecksumStatus};
+use crate::ArmorDataHeaderMap;
+use crate::ArmorDataType;
+use crate::ArmorDecoder;
+use crate::ArmorError;
+
+/// An armored block found in text
+#[derive(Debug)]
+pub struct ArmorBlock {
+    /// Byte range of the block in the text, from the header line up to the
+    /// end of the footer line, without its line ending
+    pub span: Range<usize>,
+    pub data_type: ArmorDataType,
+    pub data_headers: ArmorDataHeaderMap,
+
+    /// The decoded data
+    pub data: Vec<u8>,
+
+    /// The checksum of the block, if it has one
+    pub checksum: Option<ArmorChecksum>,
+
+    /// The outcome of verifying the checksum against the data
+    ///
+    /// A block whose checksum does not match is still yielded, so that the
+    /// caller can decide whether to use it.
+    pub checksum_status: ArmorChecksumStatus,
+}
+
+/// An armored block found in text that cannot be decoded
+#[derive(Debug, PartialEq)]
+pub struct ArmorBlockError {
+    /// Byte range of the header line of the block in the text, without its
+    /// line ending
+    pub span: Range<usize>,
+    pub error: ArmorError,
+}
+
+impl std::fmt::Display for ArmorBlockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Armored block at offset {}: {}", self.span.start, self.error)
+    }
+}
+
+impl std::error::Error for ArmorBlockError {}
+
+/// ArmorBlocks for finding every armored block in text
+///
+/// Text around and between the blocks, such as an email or a keyring
+/// export, is skipped. Blocks that cannot be decoded are yielded as an
+/// `ArmorBlockError`, and scanning continues after their header line.
+///
+/// # Examples
+/// ```rust
+/// use ascii_armor::{ArmorBlocks, ArmorDataType};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let text = "\
+///     Hi, here is the message:\n\
+///     \n\
+///     -----BEGIN PGP MESSAGE-----\n\
+///     \n\
+///     SGVsbG8=\n\
+///     =EHJM\n\
+///     -----END PGP MESSAGE-----\n\
+///     \n\
+///     And the signature:\n\
+///     -----BEGIN PGP SIGNATURE-----\n\
+///     \n\
+///     V29ybGQ=\n\
+///     -----END PGP SIGNATURE-----\n\
+/// ";
+///
+/// let blocks = ArmorBlocks::new(text).collect::<Result<Vec<_>, _>>()?;
+///
+/// assert_eq!(blocks.len(), 2);
+/// assert_eq!(blocks[0].data_type, ArmorDataType::PgpMessage);
+/// assert_eq!(blocks[0].data, b"Hello");
+/// assert!(text[blocks[0].span.clone()].ends_with("-----END PGP MESSAGE-----"));
+/// assert_eq!(blocks[1].data_type, ArmorDataType::PgpSignature);
+/// assert_eq!(blocks[1].data, b"World");
+/// # Ok(())
+/// # }
+/// ```
+pub struct ArmorBlocks<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> ArmorBlocks<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            position: 0,
+        }
+    }
+
+    /// Find the start of the next line that looks like a header line
+    fn find_header_line(&self) -> Option<usize> {
+        let mut start = self.position;
+
+        for line in self.text[self.position..].split_inclusive('\n') {
+            let trimmed = line.trim();
+
+            if trimmed.starts_with("-----BEGIN ") && trimmed.ends_with("-----") {
+                return Some(start)
+            }
+
+            start += line.len();
+        }
+
+        None
+    }
+
+    /// Decode the block starting at `start`, returning it with the offset
+    /// after its footer line
+    fn decode_block(&self, start: usize) -> Result<(ArmorBlock, usize), ArmorError> {
+        let input = &self.text.as_bytes()[start..];
+
+        let mut decoder = ArmorDecoder::new(input).map_err(armor_error)?.verify_checksum(false);
+        let mut data = vec![];
+        decoder.read_to_end(&mut data).map_err(armor_error)?;
+
+        let data_type = decoder.data_type.clone();
+        let data_headers = std::mem::take(&mut decoder.data_headers);
+        let checksum = decoder.checksum();
+        let checksum_status = decoder.checksum_status().unwrap_or(ArmorChecksumStatus::Absent);
+
+        let next = self.text.len() - decoder.into_inner().len();
+        let end = start + self.text[start..next].trim_end_matches(&['\r', '\n'][..]).len();
+
+        let block = ArmorBlock {
+            span: start..end,
+            data_type,
+            data_headers,
+            data,
+            checksum,
+            checksum_status,
+        };
+
+        Ok((block, next))
+    }
+}
+
+impl<'a> Iterator for ArmorBlocks<'a> {
+    type Item = Result<ArmorBlock, ArmorBlockError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.find_header_line()?;
+
+        match self.decode_block(start) {
+            Ok((block, next)) => {
+                self.position = next;
+                Some(Ok(block))
+            },
+            Err(error) => {
+                // Skip the header line and keep looking after it
+                let line = &self.text[start..];
+                let line = &line[..line.find('\n').unwrap_or(line.len())];
+                self.position = (start + line.len() + 1).min(self.text.len());
+
+                Some(Err(ArmorBlockError {
+                    span: start..start + line.trim_end_matches('\r').len(),
+                    error,
+                }))
+            },
+        }
+    }
+}
+
+/// The ArmorError behind an error of ArmorDecoder
+///
+/// Reading from text fails for no other reason, but should another error
+/// show up, the data is taken to be cut short.
+fn armor_error(error: io::Error) -> ArmorError {
+    match error.into_inner().map(|error| error.downcast::<ArmorError>()) {
+        Some(Ok(error)) => *error,
+        _ => ArmorError::ReaderTruncatedData,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArmorDataHeader;
+
+    #[test]
+    fn no_blocks() {
+        assert_eq!(ArmorBlocks::new("").count(), 0);
+        assert_eq!(ArmorBlocks::new("Just some text\nwithout armor.\n").count(), 0);
+    }
+
+    #[test]
+    fn spans_and_headers() {
+        let text = "\
+            Some text\r\n\
+            -----BEGIN PGP MESSAGE-----\r\n\
+            Comment: First\r\n\
+            \r\n\
+            SGVsbG8=\r\n\
+            =EHJM\r\n\
+            -----END PGP MESSAGE-----\r\n\
+            -----BEGIN PGP PUBLIC KEY BLOCK-----\r\n\
+            \r\n\
+            V29ybGQ=\r\n\
+            -----END PGP PUBLIC KEY BLOCK-----";
+
+        let blocks: Vec<_> = ArmorBlocks::new(text).map(Result::unwrap).collect();
+
+        assert_eq!(blocks.len(), 2);
+
+        assert_eq!(blocks[0].span, 11..100);
+        assert!(text[blocks[0].span.clone()].starts_with("-----BEGIN PGP MESSAGE-----"));
+        assert!(text[blocks[0].span.clone()].ends_with("-----END PGP MESSAGE-----"));
+        assert_eq!(
+            blocks[0].data_headers.get(&ArmorDataHeader::Comment),
+            Some(vec!["First"])
+        );
+        assert_eq!(blocks[0].checksum_status, ArmorChecksumStatus::Matches);
+
+        assert_eq!(blocks[1].span, 102..text.len());
+        assert_eq!(blocks[1].data_type, ArmorDataType::PgpPublicKeyBlock);
+        assert_eq!(blocks[1].checksum, None);
+        assert_eq!(blocks[1].checksum_status, ArmorChecksumStatus::Absent);
+    }
+
+    #[test]
+    fn reports_malformed_blocks() {
+        let text = "\
+            -----BEGIN PGP MESSAGE-----\n\
+            \n\
+            SGV*bG8=\n\
+            -----BEGIN UNKNOWN-----\r\n\
+            -----BEGIN PGP SIGNATURE-----\n\
+            \n\
+            V29ybGQ=\n\
+            -----END PGP SIGNATURE-----\n\
+            -----BEGIN PGP MESSAGE-----\n\
+            \n\
+            SGVsbG8=\n\
+        ";
+
+        let blocks: Vec<_> = ArmorBlocks::new(text).collect();
+
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(
+            blocks[0].as_ref().unwrap_err(),
+            &ArmorBlockError { span: 0..27, error: ArmorError::ReaderInvalidCharacter('*') }
+        );
+        assert_eq!(
+            blocks[1].as_ref().unwrap_err(),
+            &ArmorBlockError { span: 38..61, error: ArmorError::UnknownDataType(String::from("UNKNOWN")) }
+        );
+
+        let block = blocks[2].as_ref().unwrap();
+        assert_eq!(block.data_type, ArmorDataType::PgpSignature);
+        assert_eq!(block.data, b"World");
+
+        assert_eq!(blocks[3].as_ref().unwrap_err().error, ArmorError::ReaderMissingFooterLine);
+    }
+
+    #[test]
+    fn yields_blocks_with_mismatched_checksum() {
+        let text = "\
+            -----BEGIN PGP MESSAGE-----\n\
+            \n\
+            SGVsbG8=\n\
+            =uizE\n\
+            -----END PGP MESSAGE-----\n\
+        ";
+
+        let block = ArmorBlocks::new(text).next().unwrap().unwrap();
+
+        assert_eq!(block.data, b"Hello");
+        assert_eq!(block.checksum_status, ArmorChecksumStatus::Mismatch(ArmorChecksum::new("=uizE").unwrap()));
+    }
+}
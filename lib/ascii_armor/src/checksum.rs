@@ -5,7 +5,7 @@ use crate::crc24::Crc24;
 /// ASCII Armor checksum
 ///
 /// Useful for generating and verifying checksums
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ArmorChecksum {
     crc24: Crc24,
 }
@@ -23,10 +23,17 @@ impl ArmorChecksum {
     /// # }
     /// ```
     pub fn new(checksum: &str) -> Result<Self, ArmorError> {
-        let encoded = &checksum[1..];
+        let invalid = || ArmorError::InvalidChecksum(checksum.into());
+
+        // "=" followed by four Radix-64 characters for the three octets
+        let encoded = checksum.strip_prefix('=')
+            .filter(|encoded| encoded.len() == 4)
+            .ok_or_else(invalid)?
+        ;
+
         let decoded = match base64::decode(encoded.as_bytes()) {
-            Ok(x) => x,
-            Err(_) => return Err(ArmorError::InvalidChecksum(encoded.into())),
+            Ok(x) if x.len() == 3 => x,
+            _ => return Err(invalid()),
         };
 
         let crc24: Crc24 =
@@ -56,6 +63,13 @@ impl ArmorChecksum {
         }
     }
 
+    /// Create an instance of ArmorChecksum from a calculated CRC-24 value
+    pub(crate) fn from_crc24(crc24: Crc24) -> Self {
+        Self {
+            crc24,
+        }
+    }
+
     /// Get the checksum
     ///
     /// # Examples
@@ -98,9 +112,29 @@ impl ArmorChecksum {
     }
 }
 
+/// Outcome of verifying the checksum of ASCII Armor
+///
+/// The checksum is optional: RFC 9580 advises against writing it, so armor
+/// without one is not an error.
+///
+/// # Links
+/// - [RFC 9580, Section 6.1: Optional Checksum](https://www.rfc-editor.org/rfc/rfc9580#section-6.1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArmorChecksumStatus {
+    /// The armor has no checksum line
+    Absent,
+
+    /// The checksum matches the data
+    Matches,
+
+    /// The checksum does not match the data
+    Mismatch(ArmorChecksum),
+}
+
 #[cfg(test)]
 mod tests {
     use super::ArmorChecksum;
+    use crate::ArmorError;
 
     #[test]
     fn encoded_data_hello_world() {
@@ -117,6 +151,13 @@ mod tests {
         assert!(!ArmorChecksum::new("=ABCD").unwrap().verify(&data));
     }
 
+    #[test]
+    fn encoded_data_fails_for_malformed_checksum() {
+        for checksum in ["", "=", "uizE", "=uiz", "=uizEA", "=uiz=", "=u*zE"].iter() {
+            assert_eq!(ArmorChecksum::new(checksum), Err(ArmorError::InvalidChecksum(String::from(*checksum))));
+        }
+    }
+
     #[test]
     fn raw_data_empty() {
         let checksum = ArmorChecksum::from_data(b"");
@@ -0,0 +1,273 @@
+use std::io::Write;
+use std::io::Result;
+
+use crate::{ArmorBlocks, ArmorDataHeader, ArmorDataType, ArmorEncoder, ArmorError};
+use crate::ArmorChecksumStatus;
+use crate::ArmorDataHeaderMap;
+use crate::LINE_ENDING;
+
+/// CleartextSignedMessage for reading and writing cleartext signatures
+///
+/// The text stays readable, followed by an armored signature. Lines of the
+/// text that start with a dash, or with "From ", are dash-escaped by
+/// prefixing them with "- ".
+///
+/// # Examples
+/// ```rust
+/// use ascii_armor::CleartextSignedMessage;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let message = CleartextSignedMessage::parse("\
+///     -----BEGIN PGP SIGNED MESSAGE-----\n\
+///     Hash: SHA256\n\
+///     \n\
+///     Hello,\n\
+///     - -- \n\
+///     World\n\
+///     -----BEGIN PGP SIGNATURE-----\n\
+///     \n\
+///     SGVsbG8=\n\
+///     -----END PGP SIGNATURE-----\n\
+/// ")?;
+///
+/// assert_eq!(message.hashes, vec!["SHA256"]);
+/// assert_eq!(message.text, "Hello,\n-- \nWorld");
+/// assert_eq!(message.signed_text(), "Hello,\r\n--\r\nWorld");
+/// assert_eq!(message.signature, b"Hello");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Links
+/// - [RFC 4880, Section 7: Cleartext Signature Framework](https://tools.ietf.org/html/rfc4880#section-7)
+#[derive(Debug)]
+pub struct CleartextSignedMessage {
+    /// Names of the hash algorithms from the "Hash" headers
+    ///
+    /// When empty, MD5 is implied.
+    pub hashes: Vec<String>,
+
+    /// The text with dash-escaping removed, with lines separated by "\n"
+    /// and without a final line ending
+    pub text: String,
+
+    /// The signature packets, decoded from the "PGP SIGNATURE" block
+    pub signature: Vec<u8>,
+    pub signature_headers: ArmorDataHeaderMap,
+}
+
+impl CleartextSignedMessage {
+    pub fn new(hashes: Vec<String>, text: &str, signature: Vec<u8>) -> Self {
+        Self {
+            hashes,
+            text: text.replace("\r\n", "\n"),
+            signature,
+            signature_headers: ArmorDataHeaderMap::new(),
+        }
+    }
+
+    /// Parse a cleartext signed message
+    ///
+    /// Text before the header line is skipped. Headers other than "Hash"
+    /// are ignored.
+    pub fn parse(input: &str) -> std::result::Result<Self, ArmorError> {
+        let header_line = format!("-----BEGIN {}-----", ArmorDataType::PgpSignedMessage.to_string());
+        let signature_line = format!("-----BEGIN {}-----", ArmorDataType::PgpSignature.to_string());
+
+        // Lines without their line endings, with the offsets they start at
+        let mut lines = input.split_inclusive('\n').scan(0, |position, line| {
+            let start = *position;
+            *position += line.len();
+
+            Some((start, line.trim_end_matches(&['\r', '\n'][..])))
+        });
+
+        lines.by_ref()
+            .find(|(_, line)| line.trim_end() == header_line)
+            .ok_or(ArmorError::ReaderMissingHeaderLine)?
+        ;
+
+        let mut hashes = vec![];
+
+        for (_, line) in lines.by_ref() {
+            if line.trim().is_empty() {
+                break
+            }
+
+            let (key, value) = line.split_once(':')
+                .ok_or_else(|| ArmorError::CleartextInvalidHeader(line.into()))?
+            ;
+
+            if ArmorDataHeader::from_str(key.trim()) == Ok(ArmorDataHeader::Hash) {
+                hashes.extend(value.split(',').map(|hash| String::from(hash.trim())));
+            }
+        }
+
+        let mut text = vec![];
+        let mut signature_start = None;
+
+        for (start, line) in lines.by_ref() {
+            if line.trim_end() == signature_line {
+                signature_start = Some(start);
+                break
+            }
+
+            text.push(line.strip_prefix("- ").unwrap_or(line));
+        }
+
+        let signature_start = signature_start.ok_or(ArmorError::CleartextMissingSignature)?;
+
+        let block = ArmorBlocks::new(&input[signature_start..])
+            .next()
+            .ok_or(ArmorError::CleartextMissingSignature)?
+            .map_err(|block| block.error)?
+        ;
+
+        if block.span.start != 0 || block.data_type != ArmorDataType::PgpSignature {
+            return Err(ArmorError::CleartextMissingSignature)
+        }
+
+        if let ArmorChecksumStatus::Mismatch(checksum) = block.checksum_status {
+            return Err(ArmorError::ChecksumMismatch(checksum.get()))
+        }
+
+        Ok(Self {
+            hashes,
+            text: text.join("\n"),
+            signature: block.data,
+            signature_headers: block.data_headers,
+        })
+    }
+
+    /// The text as it is signed
+    ///
+    /// Trailing spaces and tabs are removed from every line, and lines end
+    /// with "\r\n". The last line has no line ending.
+    pub fn signed_text(&self) -> String {
+        self.text
+            .split('\n')
+            .map(|line| line.trim_end_matches(&[' ', '\t'][..]))
+            .collect::<Vec<&str>>()
+            .join("\r\n")
+    }
+
+    /// Write the message, dash-escaping the text and armoring the signature
+    pub fn write(&self, buffer: &mut dyn Write) -> Result<()> {
+        let data_type = ArmorDataType::PgpSignedMessage.to_string();
+        buffer.write_all(format!("-----BEGIN {}-----{}", data_type, LINE_ENDING).as_bytes())?;
+
+        if !self.hashes.is_empty() {
+            let hashes = self.hashes.join(", ");
+            buffer.write_all(format!("{}: {}{}", ArmorDataHeader::Hash.to_str(), hashes, LINE_ENDING).as_bytes())?;
+        }
+
+        buffer.write_all(LINE_ENDING.as_bytes())?;
+
+        for line in self.text.split('\n') {
+            if line.starts_with('-') || line.starts_with("From ") {
+                buffer.write_all(b"- ")?;
+            }
+
+            buffer.write_all(line.as_bytes())?;
+            buffer.write_all(LINE_ENDING.as_bytes())?;
+        }
+
+        let mut encoder = ArmorEncoder::new(buffer, ArmorDataType::PgpSignature);
+
+        for (key, value) in &self.signature_headers {
+            encoder = encoder.add_data_header(key.clone(), value);
+        }
+
+        encoder.write_all(&self.signature)?;
+        encoder.finish()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "\
+        -----BEGIN PGP SIGNED MESSAGE-----\r\n\
+        Hash: SHA256\r\n\
+        \r\n\
+        - -dash line\r\n\
+        - From here\r\n\
+        trailing spaces   \r\n\
+        \r\n\
+        - - already dash\r\n\
+        last line\r\n\
+        -----BEGIN PGP SIGNATURE-----\r\n\
+        \r\n\
+        SGVsbG8=\r\n\
+        =EHJM\r\n\
+        -----END PGP SIGNATURE-----\r\n\
+    ";
+
+    #[test]
+    fn parse_dash_escaped_text() {
+        let message = CleartextSignedMessage::parse(MESSAGE).unwrap();
+
+        assert_eq!(message.hashes, vec!["SHA256"]);
+        assert_eq!(message.text, "-dash line\nFrom here\ntrailing spaces   \n\n- already dash\nlast line");
+        assert_eq!(message.signature, b"Hello");
+    }
+
+    #[test]
+    fn signed_text_removes_trailing_whitespace() {
+        let message = CleartextSignedMessage::new(vec![], "One \t\r\nTwo\n\nThree ", vec![]);
+
+        assert_eq!(message.signed_text(), "One\r\nTwo\r\n\r\nThree");
+    }
+
+    #[test]
+    fn write_and_parse_again() {
+        let message = CleartextSignedMessage::parse(MESSAGE).unwrap();
+
+        let mut buffer = vec![];
+        message.write(&mut buffer).unwrap();
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), MESSAGE);
+    }
+
+    #[test]
+    fn multiple_hash_headers_and_empty_text() {
+        let message = CleartextSignedMessage::parse("\
+            Some text before\n\
+            -----BEGIN PGP SIGNED MESSAGE-----\n\
+            Hash: SHA256, SHA512\n\
+            Hash: SHA1\n\
+            \n\
+            \n\
+            -----BEGIN PGP SIGNATURE-----\n\
+            \n\
+            SGVsbG8=\n\
+            -----END PGP SIGNATURE-----\n\
+        ").unwrap();
+
+        assert_eq!(message.hashes, vec!["SHA256", "SHA512", "SHA1"]);
+        assert_eq!(message.text, "");
+    }
+
+    #[test]
+    fn parse_fails_for_malformed_message() {
+        assert_eq!(
+            CleartextSignedMessage::parse("Hello\n").unwrap_err(),
+            ArmorError::ReaderMissingHeaderLine
+        );
+        assert_eq!(
+            CleartextSignedMessage::parse("-----BEGIN PGP SIGNED MESSAGE-----\nHash SHA256\n\nHello\n").unwrap_err(),
+            ArmorError::CleartextInvalidHeader("Hash SHA256".into())
+        );
+        assert_eq!(
+            CleartextSignedMessage::parse("-----BEGIN PGP SIGNED MESSAGE-----\n\nHello\n").unwrap_err(),
+            ArmorError::CleartextMissingSignature
+        );
+        assert_eq!(
+            CleartextSignedMessage::parse(&MESSAGE.replace("=EHJM", "=uizE")).unwrap_err(),
+            ArmorError::ChecksumMismatch("=uizE".into())
+        );
+    }
+}
@@ -1,6 +1,7 @@
 pub type Crc24 = u32;
 
-const CRC24_INIT: u32 = 0xB704CE;
+/// The CRC-24 value of empty input, to start `update` from
+pub const CRC24_INIT: Crc24 = 0xB704CE;
 const CRC24_POLY: u32 = 0x1864CFB; // NOTE: RFC 4880 also mentions 0x864CFB
 
 /// Calculate the CRC-24 value from a given byte slice.
@@ -14,7 +15,20 @@ const CRC24_POLY: u32 = 0x1864CFB; // NOTE: RFC 4880 also mentions 0x864CFB
 /// # Links
 /// - [RFC 4880, Section 6.1](https://tools.ietf.org/html/rfc4880#section-6.1)
 pub fn calculate(input: &[u8]) -> Crc24 {
-    let mut crc = CRC24_INIT;
+    update(CRC24_INIT, input)
+}
+
+/// Continue a CRC-24 calculation with more input
+///
+/// # Examples
+/// ```rust
+/// use ascii_armor::crc24;
+///
+/// let crc = crc24::update(crc24::CRC24_INIT, b"Hello ");
+/// assert_eq!(crc24::update(crc, b"World"), crc24::calculate(b"Hello World"));
+/// ```
+pub fn update(crc: Crc24, input: &[u8]) -> Crc24 {
+    let mut crc = crc;
 
     for octet in input.iter() {
         crc ^= (*octet as Crc24) << 16;
@@ -48,4 +62,12 @@ mod tests {
         let input = b"A".repeat(10_000);
         assert_eq!(super::calculate(&input), 0x3BE9A0);
     }
+
+    #[test]
+    fn update_in_parts() {
+        let input = b"A".repeat(10_000);
+        let crc = input.chunks(7).fold(super::CRC24_INIT, super::update);
+
+        assert_eq!(crc, 0x3BE9A0);
+    }
 }
@@ -6,7 +6,7 @@ use crate::errors::ArmorError;
 ///
 /// # Links
 /// - [RFC 4880, Section 6.2](https://tools.ietf.org/html/rfc4880#section-6.2)
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ArmorDataHeader {
     /// "Version", which states the OpenPGP implementation and version
     /// used to encode the message.
@@ -51,17 +51,27 @@ pub enum ArmorDataHeader {
     /// translations it cares to; an implementation MAY ignore it and
     /// assume all text is UTF-8.
     Charset,
+
+    /// Any other key, kept so that it survives reading and writing again
+    ///
+    /// Writing fails for keys that `from_str` rejects.
+    Other(String),
 }
 
 impl ArmorDataHeader {
     /// Get the data header from a string slice
     ///
+    /// Keys other than the ones of RFC 4880 become `Other`, unless they are
+    /// empty or hold characters that cannot be part of a key.
+    ///
     /// # Examples
     /// ```rust
     /// use ascii_armor::ArmorDataHeader;
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// assert_eq!(ArmorDataHeader::from_str("MessageID")?, ArmorDataHeader::MessageId);
+    /// assert_eq!(ArmorDataHeader::from_str("X-Mailer")?, ArmorDataHeader::Other("X-Mailer".into()));
+    /// assert!(ArmorDataHeader::from_str("Not a key").is_err());
     /// # Ok(())
     /// # }
     /// ```
@@ -72,10 +82,16 @@ impl ArmorDataHeader {
             "MessageID" => Ok(Self::MessageId),
             "Hash" => Ok(Self::Hash),
             "Charset" => Ok(Self::Charset),
+            _ if Self::is_valid_key(input) => Ok(Self::Other(input.into())),
             _ => Err(ArmorError::UnknownDataHeader(input.into())),
         }
     }
 
+    /// Keys are printable US-ASCII, without spaces and colons
+    fn is_valid_key(input: &str) -> bool {
+        !input.is_empty() && input.chars().all(|c| c.is_ascii_graphic() && c != ':')
+    }
+
     /// Get the data header as a string slice
     ///
     /// # Examples
@@ -91,7 +107,105 @@ impl ArmorDataHeader {
             Self::MessageId => "MessageID",
             Self::Hash => "Hash",
             Self::Charset => "Charset",
+            Self::Other(key) => key,
+        }
+    }
+}
+
+/// Data headers in the order they were added
+///
+/// Every value is kept in its own position, also when keys are
+/// interleaved, so headers are written back exactly as they were read.
+///
+/// # Examples
+/// ```rust
+/// use ascii_armor::{ArmorDataHeader, ArmorDataHeaderMap};
+///
+/// let mut data_headers = ArmorDataHeaderMap::new();
+/// data_headers.add(ArmorDataHeader::Comment, "First");
+/// data_headers.add(ArmorDataHeader::Version, "Test");
+/// data_headers.add(ArmorDataHeader::Comment, "Second");
+///
+/// assert_eq!(data_headers.get(&ArmorDataHeader::Comment), Some(vec!["First", "Second"]));
+///
+/// let keys: Vec<&str> = data_headers.iter().map(|(key, _)| key.to_str()).collect();
+/// assert_eq!(keys, ["Comment", "Version", "Comment"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArmorDataHeaderMap {
+    entries: Vec<(ArmorDataHeader, String)>,
+}
+
+impl ArmorDataHeaderMap {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+        }
+    }
+
+    /// Add a value for the key, after all headers already added
+    pub fn add(&mut self, key: ArmorDataHeader, value: &str) {
+        self.entries.push((key, value.to_string()));
+    }
+
+    /// Get the values of the key, in the order they were added
+    pub fn get(&self, key: &ArmorDataHeader) -> Option<Vec<&str>> {
+        let values: Vec<&str> = self.entries
+            .iter()
+            .filter(|(existing, _)| existing == key)
+            .map(|(_, value)| value.as_str())
+            .collect()
+        ;
+
+        match values.is_empty() {
+            true => None,
+            false => Some(values),
+        }
+    }
+
+    /// The number of headers, counting every value
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the headers in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = (&ArmorDataHeader, &str)> {
+        self.entries.iter().map(|(key, value)| (key, value.as_str()))
+    }
+
+    /// Check that every header can be written as a single line, and read
+    /// back with the same key
+    ///
+    /// A line ending in a value would start another header, or even end
+    /// the header block, when the armor is read back. `Other` keys built
+    /// directly are not checked like those from `from_str` are.
+    pub(crate) fn check(&self) -> Result<(), ArmorError> {
+        for (key, value) in self {
+            if let ArmorDataHeader::Other(key) = key {
+                if !ArmorDataHeader::is_valid_key(key) {
+                    return Err(ArmorError::UnknownDataHeader(key.clone()))
+                }
+            }
+
+            if value.contains(&['\r', '\n'][..]) {
+                return Err(ArmorError::WriterInvalidDataHeaderValue(value.into()))
+            }
         }
+
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a ArmorDataHeaderMap {
+    type Item = (&'a ArmorDataHeader, &'a str);
+    type IntoIter = Box<dyn Iterator<Item = Self::Item> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
     }
 }
 
@@ -104,12 +218,65 @@ mod tests {
         assert_eq!(ArmorDataHeader::from_str("MessageID").unwrap(), ArmorDataHeader::MessageId);
     }
 
+    #[test]
+    fn str_to_enum_for_other_key() {
+        let header = ArmorDataHeader::from_str("X-Vendor-Header").unwrap();
+
+        assert_eq!(header, ArmorDataHeader::Other("X-Vendor-Header".into()));
+        assert_eq!(header.to_str(), "X-Vendor-Header");
+    }
+
     #[test]
     fn str_to_enum_fails_for_invalid_input() {
-        assert_eq!(
-            ArmorDataHeader::from_str("InvalidInput"),
-            Err(ArmorError::UnknownDataHeader("InvalidInput".into()))
-        );
+        for input in ["", "Invalid Input", "Invalid:Input", "Ungültig"].iter() {
+            assert_eq!(
+                ArmorDataHeader::from_str(input),
+                Err(ArmorError::UnknownDataHeader(String::from(*input)))
+            );
+        }
+    }
+
+    #[test]
+    fn map_keeps_insertion_order() {
+        let mut data_headers = ArmorDataHeaderMap::new();
+        data_headers.add(ArmorDataHeader::Other("Zebra".into()), "1");
+        data_headers.add(ArmorDataHeader::Comment, "2");
+        data_headers.add(ArmorDataHeader::Other("Zebra".into()), "3");
+        data_headers.add(ArmorDataHeader::Charset, "4");
+
+        let entries: Vec<(&str, &str)> = data_headers.iter().map(|(key, value)| (key.to_str(), value)).collect();
+
+        assert_eq!(entries, vec![("Zebra", "1"), ("Comment", "2"), ("Zebra", "3"), ("Charset", "4")]);
+        assert_eq!(data_headers.get(&ArmorDataHeader::Other("Zebra".into())), Some(vec!["1", "3"]));
+        assert_eq!(data_headers.get(&ArmorDataHeader::Version), None);
+        assert_eq!(data_headers.len(), 4);
+    }
+
+    #[test]
+    fn check_fails_for_values_with_line_endings() {
+        for value in ["Two\nlines", "Two\r\nlines", "Carriage\rreturn", "\r\n-----BEGIN PGP MESSAGE-----"].iter() {
+            let mut data_headers = ArmorDataHeaderMap::new();
+            data_headers.add(ArmorDataHeader::Comment, "Fine");
+            data_headers.add(ArmorDataHeader::Comment, value);
+
+            assert_eq!(data_headers.check(), Err(ArmorError::WriterInvalidDataHeaderValue(String::from(*value))));
+        }
+
+        let mut data_headers = ArmorDataHeaderMap::new();
+        data_headers.add(ArmorDataHeader::Comment, "Tabs\tand Ümlauts are fine");
+
+        assert_eq!(data_headers.check(), Ok(()));
+    }
+
+    #[test]
+    fn check_fails_for_invalid_other_keys() {
+        for key in ["", "Not a key", "A: b", "Two\nlines"].iter() {
+            let mut data_headers = ArmorDataHeaderMap::new();
+            data_headers.add(ArmorDataHeader::Other("X-Fine".into()), "Value");
+            data_headers.add(ArmorDataHeader::Other(String::from(*key)), "Value");
+
+            assert_eq!(data_headers.check(), Err(ArmorError::UnknownDataHeader(String::from(*key))));
+        }
     }
 
     #[test]
@@ -3,7 +3,7 @@ use crate::ArmorError;
 /// ASCII Armor data types
 ///
 /// Useful for reading/writing ASCII Armor headers and footers.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ArmorDataType {
     /// Used for signed, encrypted, or compressed files.
     PgpMessage,
@@ -0,0 +1,640 @@
+use std::io;
+use std::io::{BufRead, Read};
+
+use base64::{DecoderError, DecoderReader};
+
+use crate::crc24;
+use crate::crc24::Crc24;
+use crate::{ArmorChecksum, ArmorChecksumStatus};
+use crate::ArmorDataHeader;
+use crate::ArmorDataHeaderMap;
+use crate::ArmorDataType;
+use crate::ArmorError;
+
+/// Decoded octets read from the Radix-64 data at once
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// Octets of a line read at once, so that lines of any length, such as
+/// Radix-64 data that is not broken into lines, take bounded memory
+const LINE_CHUNK_SIZE: usize = 4096;
+
+/// ArmorDecoder for reading the data in ASCII Armor from a stream
+///
+/// Reads the header line and data headers when created, then decodes the
+/// Radix-64 data line by line while reading. The CRC-24 of the data is
+/// calculated on the way and verified against the checksum line at the
+/// end, which fails the last read on a mismatch. Nothing after the footer
+/// line is read.
+///
+/// Data is returned as it is decoded, before the checksum can be verified.
+/// Discard everything read when a later read fails.
+///
+/// # Examples
+/// ```rust
+/// use std::io::Read;
+///
+/// use ascii_armor::{ArmorDataType, ArmorDecoder};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let armor = "\
+///     -----BEGIN PGP MESSAGE-----\r\n\
+///     Version: Test\r\n\
+///     \r\n\
+///     SGVsbG8sIGJlYXV0aWZ1bCB3b3JsZCE=\r\n\
+///     =4oUH\r\n\
+///     -----END PGP MESSAGE-----\r\n\
+/// ";
+///
+/// let mut decoder = ArmorDecoder::new(armor.as_bytes())?;
+/// assert_eq!(decoder.data_type, ArmorDataType::PgpMessage);
+///
+/// let mut data = vec![];
+/// decoder.read_to_end(&mut data)?;
+///
+/// assert_eq!(data, b"Hello, beautiful world!");
+/// assert_eq!(decoder.checksum_matches(), Some(true));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Links
+/// - [RFC 4880, Section 6.2: Forming ASCII Armor](https://tools.ietf.org/html/rfc4880#section-6.2)
+pub struct ArmorDecoder<R: BufRead> {
+    pub data_type: ArmorDataType,
+    pub data_headers: ArmorDataHeaderMap,
+
+    /// Decodes the Radix-64 characters of the data lines
+    decoder: DecoderReader<DataLines<R>>,
+
+    /// Decoded data not yet read
+    decoded: Vec<u8>,
+    position: usize,
+
+    crc24: Crc24,
+    verify_checksum: bool,
+    state: State,
+}
+
+enum State {
+    Data,
+    Done,
+
+    /// Reading failed; returned again on every later read
+    Failed(io::ErrorKind, String),
+}
+
+/// Reader of the Radix-64 characters on the data lines, up to the checksum
+/// or footer line
+struct DataLines<R: BufRead> {
+    input: R,
+
+    /// The current line, read from `position` on
+    line: Vec<u8>,
+    position: usize,
+
+    /// Whether `line` holds only the start of a line, whose rest is read
+    /// next
+    is_partial: bool,
+
+    checksum: Option<ArmorChecksum>,
+    done: bool,
+}
+
+impl<R: BufRead> ArmorDecoder<R> {
+    /// Read up to the start of the Radix-64 data
+    ///
+    /// Skips any text before the header line, and a byte order mark before
+    /// it. Data headers that are not known are skipped.
+    pub fn new(mut input: R) -> io::Result<Self> {
+        let mut line = vec![];
+        let mut is_partial = false;
+
+        let data_type = loop {
+            if !read_line(&mut input, &mut line)? {
+                return Err(ArmorError::ReaderMissingHeaderLine.into())
+            }
+
+            // Only the start of a line can be the header line
+            let is_line_start = !is_partial;
+            is_partial = is_partial_line(&line);
+
+            if !is_line_start {
+                continue
+            }
+
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim_start_matches('\u{feff}').trim();
+
+            if let Some(data_type) = text.strip_prefix("-----BEGIN ").and_then(|x| x.strip_suffix("-----")) {
+                break ArmorDataType::from_str(data_type.trim())?
+            }
+        };
+
+        let mut lines = DataLines {
+            input,
+            line,
+            position: 0,
+            is_partial: false,
+            checksum: None,
+            done: false,
+        };
+
+        let data_headers = lines.read_data_headers()?;
+
+        Ok(Self {
+            data_type,
+            data_headers,
+            decoder: DecoderReader::new(lines, base64::STANDARD),
+            decoded: vec![],
+            position: 0,
+            crc24: crc24::CRC24_INIT,
+            verify_checksum: true,
+            state: State::Data,
+        })
+    }
+
+    /// Whether reading fails when the checksum does not match the data
+    ///
+    /// Enabled by default. When disabled, check `checksum_matches` after
+    /// reading to the end.
+    pub fn verify_checksum(mut self, enabled: bool) -> Self {
+        self.verify_checksum = enabled;
+        self
+    }
+
+    /// The checksum at the end of the armor, once read
+    pub fn checksum(&self) -> Option<ArmorChecksum> {
+        self.decoder.get_ref().checksum
+    }
+
+    /// Whether the checksum matches the data
+    ///
+    /// `None` until the end has been read, or when the armor has no
+    /// checksum line.
+    pub fn checksum_matches(&self) -> Option<bool> {
+        match self.checksum_status()? {
+            ArmorChecksumStatus::Absent => None,
+            ArmorChecksumStatus::Matches => Some(true),
+            ArmorChecksumStatus::Mismatch(_) => Some(false),
+        }
+    }
+
+    /// The outcome of verifying the checksum, once the end has been read
+    ///
+    /// Armor without a checksum line is read without error, as RFC 9580
+    /// allows; this tells it apart from armor whose checksum matched.
+    pub fn checksum_status(&self) -> Option<ArmorChecksumStatus> {
+        if !matches!(self.state, State::Done) {
+            return None
+        }
+
+        let status = match self.checksum() {
+            None => ArmorChecksumStatus::Absent,
+            Some(checksum) if checksum == ArmorChecksum::from_crc24(self.crc24) => ArmorChecksumStatus::Matches,
+            Some(checksum) => ArmorChecksumStatus::Mismatch(checksum),
+        };
+
+        Some(status)
+    }
+
+    /// Return the underlying stream, positioned after the footer line once
+    /// the end has been read
+    pub fn into_inner(self) -> R {
+        self.decoder.into_inner().input
+    }
+
+    /// Decode more data into `decoded`, and all of the rest once the footer
+    /// line has been read
+    ///
+    /// Only this last buffer is held back until the checksum is verified.
+    /// Earlier buffers are returned as they are decoded, so a caller must
+    /// discard what it read when a later read fails.
+    fn fill(&mut self) -> io::Result<()> {
+        let mut buffer = [0; READ_BUFFER_SIZE];
+        let n = self.decoder.read(&mut buffer).map_err(into_armor_error)?;
+
+        self.decoded.clear();
+        self.decoded.extend_from_slice(&buffer[..n]);
+        self.position = 0;
+
+        if self.decoder.get_ref().done {
+            self.decoder.read_to_end(&mut self.decoded).map_err(into_armor_error)?;
+        }
+
+        self.crc24 = crc24::update(self.crc24, &self.decoded);
+
+        if n == 0 || self.decoder.get_ref().done {
+            self.finish()?;
+        }
+
+        Ok(())
+    }
+
+    /// Verify the checksum
+    fn finish(&mut self) -> io::Result<()> {
+        self.state = State::Done;
+
+        if let Some(ArmorChecksumStatus::Mismatch(checksum)) = self.checksum_status() {
+            if self.verify_checksum {
+                return Err(ArmorError::ChecksumMismatch(checksum.get()).into())
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for ArmorDecoder<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.position < self.decoded.len() {
+                let n = (self.decoded.len() - self.position).min(buffer.len());
+                buffer[..n].copy_from_slice(&self.decoded[self.position..self.position + n]);
+                self.position += n;
+
+                return Ok(n)
+            }
+
+            match &self.state {
+                State::Done => return Ok(0),
+                State::Failed(kind, message) => return Err(io::Error::new(*kind, message.clone())),
+                State::Data => {},
+            }
+
+            if let Err(error) = self.fill() {
+                // Data decoded before a checksum mismatch is still withheld
+                self.decoded.clear();
+                self.state = State::Failed(error.kind(), error.to_string());
+
+                return Err(error)
+            }
+        }
+    }
+}
+
+impl<R: BufRead> DataLines<R> {
+    /// Read the data headers up to the blank line before the data
+    ///
+    /// A line without a colon is taken to be data, for armor that lacks the
+    /// blank line.
+    fn read_data_headers(&mut self) -> io::Result<ArmorDataHeaderMap> {
+        let mut data_headers = ArmorDataHeaderMap::new();
+
+        loop {
+            if !read_line(&mut self.input, &mut self.line)? {
+                return Err(ArmorError::ReaderMissingFooterLine.into())
+            }
+
+            let line = String::from_utf8_lossy(&self.line).trim().to_owned();
+
+            if line.is_empty() {
+                self.line.clear();
+                return Ok(data_headers)
+            }
+
+            let (key, value) = match line.split_once(':') {
+                Some(_) if is_partial_line(&self.line) => {
+                    return Err(ArmorError::ReaderLineTooLong(LINE_CHUNK_SIZE).into())
+                },
+                Some(header) => header,
+                None => {
+                    self.read_data_line()?;
+                    return Ok(data_headers)
+                },
+            };
+
+            if let Ok(key) = ArmorDataHeader::from_str(key.trim()) {
+                data_headers.add(key, value.trim());
+            }
+        }
+    }
+
+    /// Keep the line in `line` if it holds data, or handle the checksum or
+    /// footer line
+    fn read_data_line(&mut self) -> io::Result<()> {
+        self.position = 0;
+        self.is_partial = is_partial_line(&self.line);
+
+        let line = self.line.trim_ascii_start();
+
+        if line.starts_with(b"-----END") {
+            return self.finish()
+        }
+
+        if line.starts_with(b"=") {
+            let checksum = String::from_utf8_lossy(line).split_whitespace().collect::<String>();
+            self.checksum = Some(ArmorChecksum::new(&checksum)?);
+
+            loop {
+                if !read_line(&mut self.input, &mut self.line)? {
+                    return Err(ArmorError::ReaderMissingFooterLine.into())
+                }
+
+                if self.line.trim_ascii().starts_with(b"-----END") {
+                    return self.finish()
+                }
+
+                if !self.line.trim_ascii().is_empty() {
+                    return Err(ArmorError::ReaderMissingFooterLine.into())
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.line.clear();
+        self.done = true;
+
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for DataLines<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.line.len() {
+            if self.done {
+                return Ok(0)
+            }
+
+            let is_line_start = !self.is_partial;
+
+            if !read_line(&mut self.input, &mut self.line)? {
+                return Err(ArmorError::ReaderMissingFooterLine.into())
+            }
+
+            // The rest of a long line is data like its start
+            match is_line_start {
+                true => self.read_data_line()?,
+                false => {
+                    self.position = 0;
+                    self.is_partial = is_partial_line(&self.line);
+                },
+            }
+        }
+
+        let n = (self.line.len() - self.position).min(buffer.len());
+        buffer[..n].copy_from_slice(&self.line[self.position..self.position + n]);
+        self.position += n;
+
+        Ok(n)
+    }
+}
+
+/// Read a line into `line`, but no more than `LINE_CHUNK_SIZE` octets of
+/// it, returning false at the end of the input
+fn read_line<R: BufRead>(input: &mut R, line: &mut Vec<u8>) -> io::Result<bool> {
+    line.clear();
+
+    Ok(input.by_ref().take(LINE_CHUNK_SIZE as u64).read_until(b'\n', line)? > 0)
+}
+
+/// Whether `read_line` stopped before the end of the line
+fn is_partial_line(line: &[u8]) -> bool {
+    line.len() == LINE_CHUNK_SIZE && !line.ends_with(b"\n")
+}
+
+/// Turn an error of decoding Radix-64 into the ArmorError for it, and pass
+/// other errors on
+fn into_armor_error(error: io::Error) -> io::Error {
+    let error = match error.get_ref().and_then(|error| error.downcast_ref::<DecoderError>()) {
+        Some(DecoderError::UnexpectedChar { byte, .. }) => ArmorError::ReaderInvalidCharacter(*byte as char),
+        Some(_) => ArmorError::ReaderTruncatedData,
+        None => return error,
+    };
+
+    error.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn decode(input: &str) -> io::Result<Vec<u8>> {
+        let mut data = vec![];
+        ArmorDecoder::new(input.as_bytes())?.read_to_end(&mut data)?;
+
+        Ok(data)
+    }
+
+    #[test]
+    fn data_headers_and_data() {
+        let mut decoder = ArmorDecoder::new(&b"\
+            -----BEGIN PGP MESSAGE, PART 2/3-----\n\
+            Comment: First\n\
+            Comment: Second\n\
+            X-Custom: Kept\n\
+            \n\
+            SGVsbG8=\n\
+            =EHJM\n\
+            -----END PGP MESSAGE, PART 2/3-----\n\
+        "[..]).unwrap();
+
+        assert_eq!(decoder.data_type, ArmorDataType::PgpMessagePartXy(2, 3));
+        assert_eq!(
+            decoder.data_headers.get(&ArmorDataHeader::Comment),
+            Some(vec!["First", "Second"])
+        );
+        assert_eq!(
+            decoder.data_headers.get(&ArmorDataHeader::Other("X-Custom".into())),
+            Some(vec!["Kept"])
+        );
+        assert_eq!(decoder.data_headers.len(), 3);
+
+        let mut data = vec![];
+        decoder.read_to_end(&mut data).unwrap();
+
+        assert_eq!(data, b"Hello");
+        assert_eq!(decoder.checksum_matches(), Some(true));
+    }
+
+    #[test]
+    fn lines_that_are_not_groups_of_four() {
+        let data = decode("\
+            -----BEGIN PGP MESSAGE-----\r\n\
+            \r\n\
+            SGVsbG8sIGJl\r\n\
+            YXV0aWZ1b\r\n\
+            CB3b3JsZCE\r\n\
+            -----END PGP MESSAGE-----\r\n\
+        ").unwrap();
+
+        assert_eq!(data, b"Hello, beautiful world!");
+    }
+
+    #[test]
+    fn skip_text_before_header_line_and_stop_after_footer_line() {
+        let input = b"\
+            Some text\n\
+            -----BEGIN PGP MESSAGE-----\n\
+            \n\
+            SGVsbG8=\n\
+            -----END PGP MESSAGE-----\n\
+            More text\n\
+        ";
+
+        let mut decoder = ArmorDecoder::new(&input[..]).unwrap();
+        let mut data = vec![];
+        decoder.read_to_end(&mut data).unwrap();
+
+        assert_eq!(data, b"Hello");
+        assert_eq!(decoder.checksum_matches(), None);
+        assert_eq!(decoder.into_inner(), b"More text\n");
+    }
+
+    #[test]
+    fn skip_byte_order_mark_and_non_ascii_text() {
+        let data = decode("\u{feff}-----BEGIN PGP MESSAGE-----\n\nSGVsbG8=\n-----END PGP MESSAGE-----\n").unwrap();
+        assert_eq!(data, b"Hello");
+
+        let data = decode("Über\n-----BEGIN PGP MESSAGE-----\n\nSGVsbG8=\n-----END PGP MESSAGE-----\n").unwrap();
+        assert_eq!(data, b"Hello");
+    }
+
+    #[test]
+    fn missing_blank_line() {
+        let data = decode("\
+            -----BEGIN PGP MESSAGE-----\n\
+            SGVsbG8=\n\
+            -----END PGP MESSAGE-----\n\
+        ").unwrap();
+
+        assert_eq!(data, b"Hello");
+    }
+
+    #[test]
+    fn lines_longer_than_a_chunk() {
+        let data: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+        let encoded = String::from_utf8(base64::encode(&data)).unwrap();
+        let checksum = ArmorChecksum::from_crc24(crc24::update(crc24::CRC24_INIT, &data)).get();
+
+        // Data not broken into lines, also in armor without the blank line
+        for blank_line in ["\n", ""].iter() {
+            let input = format!(
+                "-----BEGIN PGP MESSAGE-----\n{}{}\n{}\n-----END PGP MESSAGE-----\n",
+                blank_line,
+                encoded,
+                checksum,
+            );
+
+            let mut decoder = ArmorDecoder::new(input.as_bytes()).unwrap();
+            let mut decoded = vec![];
+            decoder.read_to_end(&mut decoded).unwrap();
+
+            assert_eq!(decoded, data);
+            assert_eq!(decoder.checksum_matches(), Some(true));
+            assert!(decoder.decoder.get_ref().line.capacity() <= 2 * LINE_CHUNK_SIZE);
+        }
+
+        // Only a chunk that starts a line can be the header line
+        let input = format!(
+            "{}-----BEGIN PGP SIGNATURE-----\n-----BEGIN PGP MESSAGE-----\n\nSGVsbG8=\n-----END PGP MESSAGE-----\n",
+            "x".repeat(LINE_CHUNK_SIZE),
+        );
+
+        assert_eq!(ArmorDecoder::new(input.as_bytes()).unwrap().data_type, ArmorDataType::PgpMessage);
+
+        let input = format!(
+            "-----BEGIN PGP MESSAGE-----\nComment: {}\n\nSGVsbG8=\n-----END PGP MESSAGE-----\n",
+            "x".repeat(LINE_CHUNK_SIZE),
+        );
+
+        let error = ArmorDecoder::new(input.as_bytes()).err().unwrap();
+        assert_eq!(error.to_string(), ArmorError::ReaderLineTooLong(LINE_CHUNK_SIZE).to_string());
+    }
+
+    #[test]
+    fn fails_for_checksum_mismatch() {
+        let input = "\
+            -----BEGIN PGP MESSAGE-----\n\
+            \n\
+            SGVsbG8=\n\
+            =uizE\n\
+            -----END PGP MESSAGE-----\n\
+        ";
+
+        let mut decoder = ArmorDecoder::new(input.as_bytes()).unwrap();
+        let error = decoder.read_to_end(&mut vec![]).unwrap_err();
+
+        assert_eq!(error.to_string(), ArmorError::ChecksumMismatch("=uizE".into()).to_string());
+        assert!(decoder.read(&mut [0; 16]).is_err());
+
+        let mut decoder = ArmorDecoder::new(input.as_bytes()).unwrap().verify_checksum(false);
+        let mut data = vec![];
+        decoder.read_to_end(&mut data).unwrap();
+
+        assert_eq!(data, b"Hello");
+        assert_eq!(decoder.checksum_matches(), Some(false));
+    }
+
+    #[test]
+    fn checksum_status() {
+        let input = "\
+            -----BEGIN PGP MESSAGE-----\n\
+            \n\
+            SGVsbG8=\n\
+            =EHJM\n\
+            -----END PGP MESSAGE-----\n\
+        ";
+
+        let cases = [
+            (input.to_string(), ArmorChecksumStatus::Matches),
+            (input.replace("=EHJM\n", ""), ArmorChecksumStatus::Absent),
+            (input.replace("=EHJM", "=uizE"), ArmorChecksumStatus::Mismatch(ArmorChecksum::new("=uizE").unwrap())),
+        ];
+
+        for (input, expected) in cases.iter() {
+            let mut decoder = ArmorDecoder::new(input.as_bytes()).unwrap().verify_checksum(false);
+            assert_eq!(decoder.checksum_status(), None);
+
+            decoder.read_to_end(&mut vec![]).unwrap();
+            assert_eq!(decoder.checksum_status(), Some(*expected));
+        }
+    }
+
+    #[test]
+    fn fails_for_malformed_checksum() {
+        for checksum in ["=ab", "=", "=EHJMA", "=EH*M"].iter() {
+            let input = format!("-----BEGIN PGP MESSAGE-----\n\nSGVsbG8=\n{}\n-----END PGP MESSAGE-----\n", checksum);
+
+            let mut decoder = ArmorDecoder::new(input.as_bytes()).unwrap().verify_checksum(false);
+            let error = decoder.read_to_end(&mut vec![]).unwrap_err();
+
+            assert_eq!(error.to_string(), ArmorError::InvalidChecksum(String::from(*checksum)).to_string());
+        }
+    }
+
+    #[test]
+    fn fails_for_malformed_input() {
+        assert!(decode("SGVsbG8=\n").is_err());
+        assert!(decode("-----BEGIN PGP MESSAGE-----\n\nSGVsbG8=\n").is_err());
+        assert!(decode("-----BEGIN PGP MESSAGE-----\n\nSGVsb\n-----END PGP MESSAGE-----\n").is_err());
+        assert!(decode("-----BEGIN PGP MESSAGE-----\n\nSGVs*G8=\n-----END PGP MESSAGE-----\n").is_err());
+        assert!(decode("-----BEGIN PGP MESSAGE-----\n\nSGVsbG8=\n=ab\n-----END PGP MESSAGE-----\n").is_err());
+    }
+
+    #[test]
+    fn errors_of_radix64_data() {
+        let error = decode("-----BEGIN PGP MESSAGE-----\n\nSGVsb\n-----END PGP MESSAGE-----\n").unwrap_err();
+        assert_eq!(error.to_string(), ArmorError::ReaderTruncatedData.to_string());
+
+        let error = decode("-----BEGIN PGP MESSAGE-----\n\nSGVs\n*G8=\n-----END PGP MESSAGE-----\n").unwrap_err();
+        assert_eq!(error.to_string(), ArmorError::ReaderInvalidCharacter('*').to_string());
+    }
+
+    #[test]
+    fn everything_with_binary_data_from_file() {
+        let expected_data = fs::read("tests/resources/gnupg-icon.png").unwrap();
+        let armor = fs::read("tests/resources/gnupg-icon.png.asc").unwrap();
+
+        let mut decoder = ArmorDecoder::new(&armor[..]).unwrap();
+        let mut data = vec![];
+        decoder.read_to_end(&mut data).unwrap();
+
+        assert_eq!(data, expected_data);
+        assert_eq!(decoder.checksum_matches(), Some(true));
+    }
+}
@@ -0,0 +1,318 @@
+use std::io;
+use std::io::Write;
+
+use base64::EncoderWriter;
+
+use crate::crc24;
+use crate::crc24::Crc24;
+use crate::{ArmorChecksum, ArmorDataHeader, ArmorDataType, ArmorFormat};
+use crate::ArmorDataHeaderMap;
+
+/// ArmorEncoder for writing data in ASCII Armor to a stream
+///
+/// The header line and data headers are written with the first data. Data
+/// is encoded using Radix-64 as it arrives, and written in lines of 64
+/// characters unless another `ArmorFormat` is set. The checksum and footer
+/// line are written by `finish`.
+///
+/// # Examples
+/// ```rust
+/// use std::io::Write;
+///
+/// use ascii_armor::{ArmorDataHeader, ArmorDataType, ArmorEncoder};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut encoder = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpMessage)
+///     .add_data_header(ArmorDataHeader::Version, "Test")
+/// ;
+///
+/// encoder.write_all(b"Hello, ")?;
+/// encoder.write_all(b"beautiful world!")?;
+///
+/// assert_eq!(encoder.finish()?, b"\
+///     -----BEGIN PGP MESSAGE-----\r\n\
+///     Version: Test\r\n\
+///     \r\n\
+///     SGVsbG8sIGJlYXV0aWZ1bCB3b3JsZCE=\r\n\
+///     =4oUH\r\n\
+///     -----END PGP MESSAGE-----\r\n\
+/// ");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Links
+/// - [RFC 4880, Section 6.2: Forming ASCII Armor](https://tools.ietf.org/html/rfc4880#section-6.2)
+pub struct ArmorEncoder<W: Write> {
+    /// Encodes the data into lines broken at the line length of the format
+    encoder: EncoderWriter<LineWrapper<W>>,
+    data_type: ArmorDataType,
+    data_headers: ArmorDataHeaderMap,
+    format: ArmorFormat,
+    header_written: bool,
+    crc24: Crc24,
+}
+
+/// Writer that breaks Radix-64 characters into lines
+struct LineWrapper<W: Write> {
+    output: W,
+    format: ArmorFormat,
+
+    /// Characters written on the current line
+    column: usize,
+}
+
+impl<W: Write> ArmorEncoder<W> {
+    pub fn new(output: W, data_type: ArmorDataType) -> Self {
+        let format = ArmorFormat::new();
+        let lines = LineWrapper {
+            output,
+            format,
+            column: 0,
+        };
+
+        Self {
+            encoder: EncoderWriter::new(lines, base64::STANDARD),
+            data_type,
+            data_headers: ArmorDataHeaderMap::new(),
+            format,
+            header_written: false,
+            crc24: crc24::CRC24_INIT,
+        }
+    }
+
+    /// Add a data header
+    ///
+    /// Multiple data headers can be added, even with the same key. An
+    /// invalid `Other` key or a value with a line ending makes the first
+    /// write, or `finish`, fail.
+    pub fn add_data_header(mut self, key: ArmorDataHeader, value: &str) -> Self {
+        self.data_headers.add(key, value);
+        self
+    }
+
+    /// Set how the armor is formatted
+    pub fn format(mut self, format: ArmorFormat) -> Self {
+        self.format = format;
+        self.encoder.get_mut().format = format;
+        self
+    }
+
+    /// Write the remaining data, the checksum and the footer line, and
+    /// return the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_header()?;
+
+        let mut lines = self.encoder.finish()?;
+        let line_ending = self.format.get_line_ending().to_str();
+
+        if lines.column > 0 {
+            lines.output.write_all(line_ending.as_bytes())?;
+        }
+
+        let mut output = lines.output;
+
+        if self.format.get_checksum() {
+            let checksum = ArmorChecksum::from_crc24(self.crc24);
+            write!(output, "{}{}", checksum.get(), line_ending)?;
+        }
+
+        write!(output, "-----END {}-----{}", self.data_type.to_string(), line_ending)?;
+
+        Ok(output)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if self.header_written {
+            return Ok(())
+        }
+
+        self.data_headers.check()?;
+
+        // Nothing is encoded yet, so the header goes straight to the output
+        let output = &mut self.encoder.get_mut().output;
+        let line_ending = self.format.get_line_ending().to_str();
+        write!(output, "-----BEGIN {}-----{}", self.data_type.to_string(), line_ending)?;
+
+        for (key, value) in &self.data_headers {
+            if *key == ArmorDataHeader::Version && !self.format.get_version_header() {
+                continue
+            }
+
+            write!(output, "{}: {}{}", key.to_str(), value, line_ending)?;
+        }
+
+        output.write_all(line_ending.as_bytes())?;
+        self.header_written = true;
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for ArmorEncoder<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.write_header()?;
+        self.crc24 = crc24::update(self.crc24, data);
+        self.encoder.write_all(data)?;
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.encoder.flush()
+    }
+}
+
+impl<W: Write> Write for LineWrapper<W> {
+    fn write(&mut self, mut encoded: &[u8]) -> io::Result<usize> {
+        let length = encoded.len();
+        let line_length = self.format.get_line_length();
+
+        while !encoded.is_empty() {
+            let n = (line_length - self.column).min(encoded.len());
+            self.output.write_all(&encoded[..n])?;
+
+            encoded = &encoded[n..];
+            self.column += n;
+
+            if self.column == line_length {
+                self.output.write_all(self.format.get_line_ending().to_str().as_bytes())?;
+                self.column = 0;
+            }
+        }
+
+        Ok(length)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::ArmorDecoder;
+
+    #[test]
+    fn empty_data() {
+        let output = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpSignature).finish().unwrap();
+
+        assert_eq!(output, b"\
+            -----BEGIN PGP SIGNATURE-----\r\n\
+            \r\n\
+            =twTO\r\n\
+            -----END PGP SIGNATURE-----\r\n\
+        ");
+    }
+
+    #[test]
+    fn data_and_checksum() {
+        let mut encoder = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpMessage);
+        encoder.write_all(b"Hello").unwrap();
+
+        assert_eq!(encoder.finish().unwrap(), b"\
+            -----BEGIN PGP MESSAGE-----\r\n\
+            \r\n\
+            SGVsbG8=\r\n\
+            =EHJM\r\n\
+            -----END PGP MESSAGE-----\r\n\
+        ");
+    }
+
+    #[test]
+    fn lines_of_64_characters() {
+        // 48 octets encode to exactly one line
+        let mut encoder = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpMessage);
+        encoder.write_all(&[0; 96]).unwrap();
+
+        let output = String::from_utf8(encoder.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = output.split("\r\n").collect();
+
+        assert_eq!(lines[2], "A".repeat(64));
+        assert_eq!(lines[3], "A".repeat(64));
+        assert!(lines[4].starts_with('='));
+    }
+
+    #[test]
+    fn custom_format() {
+        let format = ArmorFormat::new()
+            .line_length(4).unwrap()
+            .line_ending(crate::ArmorLineEnding::Lf)
+            .checksum(false)
+            .version_header(false)
+        ;
+
+        let mut encoder = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpMessage)
+            .add_data_header(ArmorDataHeader::Version, "Test")
+            .add_data_header(ArmorDataHeader::Comment, "Kept")
+            .format(format)
+        ;
+        encoder.write_all(b"Hello").unwrap();
+
+        assert_eq!(encoder.finish().unwrap(), b"\
+            -----BEGIN PGP MESSAGE-----\n\
+            Comment: Kept\n\
+            \n\
+            SGVs\n\
+            bG8=\n\
+            -----END PGP MESSAGE-----\n\
+        ");
+    }
+
+    #[test]
+    fn data_header_values_with_line_endings_fail() {
+        let mut encoder = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpMessage)
+            .add_data_header(ArmorDataHeader::Comment, "Hi\r\n\r\n-----BEGIN PGP MESSAGE-----")
+        ;
+
+        let error = encoder.write_all(b"Hello").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(encoder.encoder.get_ref().output.is_empty());
+
+        let encoder = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpMessage)
+            .add_data_header(ArmorDataHeader::Comment, "Hi\n")
+        ;
+
+        assert_eq!(encoder.finish().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn invalid_other_keys_fail() {
+        let encoder = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpMessage)
+            .add_data_header(ArmorDataHeader::Other("Not a key".into()), "Value")
+        ;
+
+        let error = encoder.finish().unwrap_err();
+
+        assert_eq!(error.to_string(), "Unknown armor data header `Not a key`.");
+    }
+
+    #[test]
+    fn same_output_for_any_write_sizes() {
+        let data = std::fs::read("tests/resources/gnupg-icon.png").unwrap();
+
+        let mut encoder = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpMessage);
+        encoder.write_all(&data).unwrap();
+        let expected = encoder.finish().unwrap();
+
+        for size in [1, 2, 5, 47, 49, 1000].iter() {
+            let mut encoder = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpMessage);
+
+            for chunk in data.chunks(*size) {
+                encoder.write_all(chunk).unwrap();
+            }
+
+            assert_eq!(encoder.finish().unwrap(), expected, "{}", size);
+        }
+
+        let mut decoder = ArmorDecoder::new(&expected[..]).unwrap();
+        let mut decoded = vec![];
+        decoder.read_to_end(&mut decoded).unwrap();
+
+        assert_eq!(decoded, data);
+        assert_eq!(decoder.checksum_matches(), Some(true));
+    }
+}
@@ -1,12 +1,45 @@
+use std::io;
+
 /// ASCII Armor errors
 #[derive(Debug, PartialEq)]
 pub enum ArmorError {
     UnknownDataHeader(String),
     UnknownDataType(String),
     InvalidChecksum(String),
+    ChecksumMismatch(String),
 
     // NOTE: Consider moving to own ArmorReaderError enum
     ReaderUnknownChecksum,
+    ReaderMissingHeaderLine,
+    ReaderMissingFooterLine,
+    ReaderTruncatedData,
+    ReaderMissingBlankLine,
+    ReaderInvalidHeaderLine(String),
+    ReaderMismatchedFooterLine(String),
+    ReaderLineTooLong(usize),
+    ReaderInvalidCharacter(char),
+
+    /// Malformed input, at a line and column counted from 1
+    Syntax {
+        line: usize,
+        column: usize,
+        error: Box<ArmorError>,
+    },
+
+    MultipartInvalidPartSize,
+    MultipartNoParts,
+    MultipartMissingPart(usize),
+    MultipartDuplicatePart(usize),
+    MultipartMissingMessageId,
+    MultipartMismatch,
+
+    CleartextInvalidHeader(String),
+    CleartextMissingSignature,
+
+    WriterInvalidLineLength(usize),
+    WriterInvalidDataHeaderValue(String),
+    WriterMissingDataType,
+    WriterMissingData,
 }
 
 impl std::fmt::Display for ArmorError {
@@ -15,13 +48,41 @@ impl std::fmt::Display for ArmorError {
             ArmorError::UnknownDataHeader(ref header) => write!(f, "Unknown armor data header `{}`.", header),
             ArmorError::UnknownDataType(ref data_type) => write!(f, "Unknown armor data type `{}`.", data_type),
             ArmorError::InvalidChecksum(ref checksum) => write!(f, "Invalid checksum `{}`.", checksum),
+            ArmorError::ChecksumMismatch(ref checksum) => write!(f, "Checksum verification of `{}` failed.", checksum),
             ArmorError::ReaderUnknownChecksum => write!(f, "Cannot find valid checksum in ASCII Armor."),
+            ArmorError::ReaderMissingHeaderLine => write!(f, "Cannot find the header line of ASCII Armor."),
+            ArmorError::ReaderMissingFooterLine => write!(f, "Unexpected end of ASCII Armor before its footer line."),
+            ArmorError::ReaderTruncatedData => write!(f, "Truncated Radix-64 data in ASCII Armor."),
+            ArmorError::ReaderMissingBlankLine => write!(f, "Missing blank line after the data headers of ASCII Armor."),
+            ArmorError::ReaderInvalidHeaderLine(ref line) => write!(f, "Invalid data header line `{}`.", line),
+            ArmorError::ReaderMismatchedFooterLine(ref data_type) => write!(f, "Footer line for `{}` does not match the header line.", data_type),
+            ArmorError::ReaderLineTooLong(length) => write!(f, "Line longer than {} characters in ASCII Armor.", length),
+            ArmorError::ReaderInvalidCharacter(c) => write!(f, "Invalid character `{}` in Radix-64 data.", c),
+            ArmorError::Syntax { line, column, ref error } => write!(f, "Line {}, column {}: {}", line, column, error),
+            ArmorError::MultipartInvalidPartSize => write!(f, "Parts of a multi-part message must hold at least one octet."),
+            ArmorError::MultipartNoParts => write!(f, "Cannot find any part of a multi-part message."),
+            ArmorError::MultipartMissingPart(number) => write!(f, "Part {} of the multi-part message is missing.", number),
+            ArmorError::MultipartDuplicatePart(number) => write!(f, "Part {} of the multi-part message appears more than once.", number),
+            ArmorError::MultipartMissingMessageId => write!(f, "Parts without a total number of parts need a MessageID."),
+            ArmorError::MultipartMismatch => write!(f, "The parts do not belong to the same multi-part message."),
+            ArmorError::CleartextInvalidHeader(ref line) => write!(f, "Invalid header `{}` in cleartext signed message.", line),
+            ArmorError::CleartextMissingSignature => write!(f, "Cannot find the signature of the cleartext signed message."),
+            ArmorError::WriterInvalidLineLength(length) => write!(f, "Line length {} is not a multiple of 4 between 4 and 76 characters.", length),
+            ArmorError::WriterInvalidDataHeaderValue(ref value) => write!(f, "Data header value {:?} does not fit on a single line.", value),
+            ArmorError::WriterMissingDataType => write!(f, "Cannot write ASCII Armor without a data type."),
+            ArmorError::WriterMissingData => write!(f, "Cannot write ASCII Armor without data."),
         }
     }
 }
 
 impl std::error::Error for ArmorError {}
 
+impl From<ArmorError> for io::Error {
+    fn from(error: ArmorError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ArmorError;
@@ -49,4 +110,31 @@ mod tests {
             "Invalid checksum `InvalidInput`."
         );
     }
+
+    #[test]
+    fn checksum_mismatch_error_formats_correctly() {
+        assert_eq!(
+            ArmorError::ChecksumMismatch("=uizE".into()).to_string(),
+            "Checksum verification of `=uizE` failed."
+        );
+    }
+
+    #[test]
+    fn syntax_error_formats_correctly() {
+        let error = ArmorError::Syntax {
+            line: 3,
+            column: 1,
+            error: Box::new(ArmorError::ReaderMissingBlankLine),
+        };
+
+        assert_eq!(error.to_string(), "Line 3, column 1: Missing blank line after the data headers of ASCII Armor.");
+    }
+
+    #[test]
+    fn multipart_missing_part_error_formats_correctly() {
+        assert_eq!(
+            ArmorError::MultipartMissingPart(2).to_string(),
+            "Part 2 of the multi-part message is missing."
+        );
+    }
 }
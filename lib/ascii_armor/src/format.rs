@@ -0,0 +1,199 @@
+use crate::ArmorError;
+
+/// The encoded output stream must be represented in lines of no more
+/// than 76 characters each according to RFC 4880.
+pub const MAX_LINE_LENGTH: usize = 76;
+
+/// GnuPG uses lines of 64 characters
+const DEFAULT_LINE_LENGTH: usize = 64;
+
+/// Line ending of written ASCII Armor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArmorLineEnding {
+    /// "\r\n", as RFC 4880 prescribes for transport
+    Crlf,
+
+    /// "\n", as is common for files on Unix
+    Lf,
+}
+
+impl ArmorLineEnding {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Self::Crlf => "\r\n",
+            Self::Lf => "\n",
+        }
+    }
+}
+
+/// ArmorFormat for choosing how ASCII Armor is written
+///
+/// Defaults to lines of 64 characters ending with "\r\n", a checksum, and
+/// the Version data headers that were added.
+///
+/// # Examples
+/// ```rust
+/// use ascii_armor::{ArmorError, ArmorFormat, ArmorLineEnding};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let format = ArmorFormat::new()
+///     .line_length(76)?
+///     .line_ending(ArmorLineEnding::Lf)
+///     .checksum(false)
+/// ;
+///
+/// assert_eq!(format.get_line_length(), 76);
+/// assert_eq!(ArmorFormat::new().line_length(80).unwrap_err(), ArmorError::WriterInvalidLineLength(80));
+/// assert_eq!(ArmorFormat::new().line_length(6).unwrap_err(), ArmorError::WriterInvalidLineLength(6));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Links
+/// - [RFC 4880, Section 6.3: Encoding Binary in Radix-64](https://tools.ietf.org/html/rfc4880#section-6.3)
+/// - [RFC 9580, Section 6.1: Optional Checksum](https://www.rfc-editor.org/rfc/rfc9580#section-6.1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArmorFormat {
+    line_length: usize,
+    line_ending: ArmorLineEnding,
+    checksum: bool,
+    version_header: bool,
+}
+
+impl ArmorFormat {
+    pub fn new() -> Self {
+        Self {
+            line_length: DEFAULT_LINE_LENGTH,
+            line_ending: ArmorLineEnding::Crlf,
+            checksum: true,
+            version_header: true,
+        }
+    }
+
+    /// Set the number of Radix-64 characters per line
+    ///
+    /// Fails for lengths beyond the 76 characters RFC 4880 allows, and for
+    /// lengths that are not a multiple of 4. Those could put the padding on
+    /// a line of its own, where it looks like the checksum line.
+    pub fn line_length(mut self, line_length: usize) -> Result<Self, ArmorError> {
+        if line_length == 0 || !line_length.is_multiple_of(4) || line_length > MAX_LINE_LENGTH {
+            return Err(ArmorError::WriterInvalidLineLength(line_length))
+        }
+
+        self.line_length = line_length;
+        Ok(self)
+    }
+
+    pub fn line_ending(mut self, line_ending: ArmorLineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Write the checksum after the data or leave it out, as RFC 9580
+    /// allows
+    pub fn checksum(mut self, checksum: bool) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Write the Version data headers or leave them out
+    pub fn version_header(mut self, version_header: bool) -> Self {
+        self.version_header = version_header;
+        self
+    }
+
+    pub fn get_line_length(&self) -> usize {
+        self.line_length
+    }
+
+    pub fn get_line_ending(&self) -> ArmorLineEnding {
+        self.line_ending
+    }
+
+    pub fn get_checksum(&self) -> bool {
+        self.checksum
+    }
+
+    pub fn get_version_header(&self) -> bool {
+        self.version_header
+    }
+}
+
+impl Default for ArmorFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_length_within_limits() {
+        assert_eq!(ArmorFormat::new().get_line_length(), 64);
+        assert_eq!(ArmorFormat::new().line_length(4).unwrap().get_line_length(), 4);
+        assert_eq!(ArmorFormat::new().line_length(76).unwrap().get_line_length(), 76);
+    }
+
+    #[test]
+    fn line_length_fails_beyond_limits() {
+        for line_length in [0, 1, 2, 3, 6, 7, 75, 77, 80, usize::MAX].iter() {
+            assert_eq!(
+                ArmorFormat::new().line_length(*line_length),
+                Err(ArmorError::WriterInvalidLineLength(*line_length))
+            );
+        }
+    }
+
+    #[test]
+    fn every_line_length_round_trips() {
+        use std::io::{Read, Write};
+
+        use crate::{Armor, ArmorDataType, ArmorDecoder, ArmorEncoder, ArmorParseMode, ArmorWriterBuilder};
+
+        for line_length in 1..=MAX_LINE_LENGTH {
+            let format = match ArmorFormat::new().line_length(line_length) {
+                Ok(format) => format,
+                Err(error) => {
+                    assert_ne!(line_length % 4, 0);
+                    assert_eq!(error, ArmorError::WriterInvalidLineLength(line_length));
+                    continue
+                },
+            };
+
+            for length in 0..=20 {
+                let data: Vec<u8> = (0..length).collect();
+
+                let mut encoder = ArmorEncoder::new(vec![], ArmorDataType::PgpMessage).format(format);
+                encoder.write_all(&data).unwrap();
+                let encoded = encoder.finish().unwrap();
+
+                let mut written = vec![];
+                ArmorWriterBuilder::new()
+                    .data_type(ArmorDataType::PgpMessage)
+                    .data(&data)
+                    .format(format)
+                    .build()
+                    .write_unchecked(&mut written)
+                    .unwrap()
+                ;
+
+                assert_eq!(encoded, written, "{} {}", line_length, length);
+
+                let mut decoded = vec![];
+                ArmorDecoder::new(&encoded[..]).unwrap().read_to_end(&mut decoded).unwrap();
+                assert_eq!(decoded, data, "{} {}", line_length, length);
+
+                let armor = Armor::parse(std::str::from_utf8(&encoded).unwrap(), ArmorParseMode::Strict).unwrap();
+                assert_eq!(armor.data, data, "{} {}", line_length, length);
+            }
+        }
+    }
+
+    #[test]
+    fn line_endings() {
+        assert_eq!(ArmorLineEnding::Crlf.to_str(), "\r\n");
+        assert_eq!(ArmorLineEnding::Lf.to_str(), "\n");
+    }
+}
@@ -0,0 +1,154 @@
+use crate::armor::boundary_line;
+use crate::ArmorDataType;
+
+/// The byte order mark that some editors put before UTF-8 text
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// The kind of an OpenPGP input, told from its first octets
+///
+/// Useful for choosing how to read a file before reading it.
+///
+/// # Examples
+/// ```rust
+/// use ascii_armor::{ArmorDataType, InputKind};
+///
+/// assert_eq!(InputKind::detect(&[0xc3, 0x0d, 0x04]), InputKind::Binary);
+/// assert_eq!(InputKind::detect(b"-----BEGIN PGP MESSAGE-----\n"), InputKind::Armored(ArmorDataType::PgpMessage));
+/// assert_eq!(InputKind::detect(b"-----BEGIN PGP SIGNED MESSAGE-----\n"), InputKind::CleartextSigned);
+/// assert_eq!(InputKind::detect(b"Hello, world!\n"), InputKind::Unknown);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputKind {
+    /// OpenPGP packets, which start with a packet tag
+    Binary,
+
+    /// ASCII Armor of a data type, possibly preceded by other text
+    Armored(ArmorDataType),
+
+    /// A cleartext signed message
+    CleartextSigned,
+
+    /// Neither of the above, or a header line of an unknown data type
+    Unknown,
+}
+
+impl InputKind {
+    /// Detect the kind of input from its first octets
+    ///
+    /// The input is searched for the first header line of ASCII Armor, so
+    /// the octets should hold at least that line, such as the buffer of a
+    /// `BufReader` after `fill_buf`. Text before it may be in any encoding,
+    /// and may start with a byte order mark. Only input without a header
+    /// line that starts with a packet tag is taken to be binary.
+    pub fn detect(input: &[u8]) -> Self {
+        let text = input.strip_prefix(UTF8_BOM).unwrap_or(input);
+
+        // Only complete lines are inspected
+        let lines = match text.iter().rposition(|octet| *octet == b'\n') {
+            Some(end) => &text[..end],
+            None => &[],
+        };
+
+        let header_line = lines
+            .split(|octet| *octet == b'\n')
+            .filter_map(|line| std::str::from_utf8(line).ok())
+            .find_map(|line| boundary_line(line, "BEGIN", false))
+        ;
+
+        match header_line.map(ArmorDataType::from_str) {
+            Some(Ok(ArmorDataType::PgpSignedMessage)) => Self::CleartextSigned,
+            Some(Ok(data_type)) => Self::Armored(data_type),
+            Some(Err(_)) => Self::Unknown,
+            None if input.first().copied().is_some_and(is_packet_tag) => Self::Binary,
+            None => Self::Unknown,
+        }
+    }
+}
+
+/// Whether the octet is the tag of a known packet type, in the old or the
+/// new packet format
+///
+/// # Links
+/// - [RFC 9580, Section 4.2: Packet Headers](https://www.rfc-editor.org/rfc/rfc9580#section-4.2)
+/// - [RFC 9580, Section 5: Packet Types](https://www.rfc-editor.org/rfc/rfc9580#section-5)
+fn is_packet_tag(octet: u8) -> bool {
+    if octet & 0x80 == 0 {
+        return false
+    }
+
+    let packet_type = match octet & 0x40 {
+        0 => (octet >> 2) & 0x0f,
+        _ => octet & 0x3f,
+    };
+
+    matches!(packet_type, 1..=21 | 60..=63)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_packets() {
+        // Symmetric-Key Encrypted Session Key packets, in the new and the
+        // old packet format
+        assert_eq!(InputKind::detect(&[0xc3, 0x0d, 0x04, 0x09]), InputKind::Binary);
+        assert_eq!(InputKind::detect(&[0x8c, 0x0d, 0x04, 0x09]), InputKind::Binary);
+    }
+
+    #[test]
+    fn armor_after_other_text() {
+        let input = b"Hello,\r\n\r\nhere is my key:\r\n-----BEGIN PGP PUBLIC KEY BLOCK-----\r\n";
+
+        assert_eq!(InputKind::detect(input), InputKind::Armored(ArmorDataType::PgpPublicKeyBlock));
+    }
+
+    #[test]
+    fn armor_from_file() {
+        let data = std::fs::read("tests/resources/gnupg-icon.png.asc").unwrap();
+
+        assert_eq!(InputKind::detect(&data[..100]), InputKind::Armored(ArmorDataType::PgpMessage));
+    }
+
+    #[test]
+    fn multipart_and_cleartext_signed() {
+        assert_eq!(
+            InputKind::detect(b"-----BEGIN PGP MESSAGE, PART 1/2-----\n"),
+            InputKind::Armored(ArmorDataType::PgpMessagePartXy(1, 2))
+        );
+        assert_eq!(InputKind::detect(b"-----BEGIN PGP SIGNED MESSAGE-----\r\n"), InputKind::CleartextSigned);
+    }
+
+    #[test]
+    fn armor_after_byte_order_mark() {
+        let input = b"\xef\xbb\xbf-----BEGIN PGP MESSAGE-----\r\n";
+
+        assert_eq!(InputKind::detect(input), InputKind::Armored(ArmorDataType::PgpMessage));
+    }
+
+    #[test]
+    fn armor_after_non_ascii_text() {
+        // "Ü" is 0xc3 0x9c in UTF-8, and 0xc3 is also a packet tag
+        let input = "Über\n-----BEGIN PGP MESSAGE-----\n".as_bytes();
+        assert_eq!(InputKind::detect(input), InputKind::Armored(ArmorDataType::PgpMessage));
+
+        let input = b"\xdcber, in Latin-1\n-----BEGIN PGP SIGNATURE-----\n";
+        assert_eq!(InputKind::detect(input), InputKind::Armored(ArmorDataType::PgpSignature));
+    }
+
+    #[test]
+    fn unknown_input() {
+        for input in [
+            &b""[..],
+            b"Hello",
+            b"-----BEGIN PGP NONSENSE-----\n",
+            b"-----BEGIN PGP MESSAGE-----",
+            b"\xef\xbb\xbfHello\n",
+            // Reserved packet types
+            &[0xc0, 0x0d],
+            &[0x80, 0x0d],
+        ].iter() {
+            assert_eq!(InputKind::detect(input), InputKind::Unknown, "{:?}", input);
+        }
+    }
+}
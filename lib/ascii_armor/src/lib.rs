@@ -1,22 +1,36 @@
 /// Crc24 calculation
 pub mod crc24;
 
+/// Splitting and joining multi-part messages
+pub mod multipart;
+
+mod armor;
+mod blocks;
 mod checksum;
+mod cleartext;
 mod data_headers;
+mod decoder;
+mod encoder;
 mod data_types;
 mod errors;
+mod format;
+mod input_kind;
 mod reader;
 mod writer;
 
-pub use checksum::ArmorChecksum;
-pub use data_headers::ArmorDataHeader;
+pub use armor::{Armor, ArmorParseMode};
+pub use blocks::{ArmorBlock, ArmorBlockError, ArmorBlocks};
+pub use checksum::{ArmorChecksum, ArmorChecksumStatus};
+pub use cleartext::CleartextSignedMessage;
+pub use data_headers::{ArmorDataHeader, ArmorDataHeaderMap};
 pub use data_types::ArmorDataType;
+pub use decoder::ArmorDecoder;
+pub use encoder::ArmorEncoder;
 pub use errors::ArmorError;
+pub use format::{ArmorFormat, ArmorLineEnding};
+pub use input_kind::InputKind;
 pub use reader::ArmorReader;
 pub use writer::ArmorWriter;
 pub use writer::ArmorWriterBuilder;
 
-use std::collections::HashMap;
-pub(crate) type ArmorDataHeaderMap = HashMap<ArmorDataHeader, Vec<String>>;
-
 pub(crate) const LINE_ENDING: &str = "\r\n";
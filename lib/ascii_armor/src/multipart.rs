@@ -0,0 +1,258 @@
+//! Multi-part messages
+//!
+//! A message too large to send at once can be split into several armored
+//! parts, with the data types "PGP MESSAGE, PART X/Y" or "PGP MESSAGE, PART
+//! X". All parts carry the same MessageID.
+//!
+//! # Examples
+//! ```rust
+//! use ascii_armor::multipart;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let message = [0x42; 100];
+//! let parts = multipart::split(&message, 40)?;
+//!
+//! assert_eq!(parts.len(), 3);
+//! assert!(parts[0].starts_with("-----BEGIN PGP MESSAGE, PART 1/3-----"));
+//!
+//! // Parts are joined in any order
+//! assert_eq!(multipart::join(&[&parts[2], &parts[0], &parts[1]])?, message);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Links
+//! - [RFC 4880, Section 6.2: Forming ASCII Armor](https://tools.ietf.org/html/rfc4880#section-6.2)
+
+use std::io::Write;
+
+use sha::sha256::Sha256;
+
+use crate::{ArmorBlock, ArmorBlocks};
+use crate::ArmorChecksumStatus;
+use crate::ArmorDataHeader;
+use crate::ArmorDataType;
+use crate::ArmorEncoder;
+use crate::ArmorError;
+
+/// Compute the MessageID of a finished message
+///
+/// The first 24 octets of its SHA-256 digest, encoded as 32 Radix-64
+/// characters. Being derived from the message, it cannot leak anything
+/// else, as RFC 4880 requires.
+///
+/// # Examples
+/// ```rust
+/// use ascii_armor::multipart;
+///
+/// assert_eq!(multipart::message_id(b"Hello"), "GF+NsyJx/iX1Yab8k4suJkMG7DBO2lGA");
+/// ```
+pub fn message_id(message: &[u8]) -> String {
+    let digest = Sha256::digest(message);
+    let encoded = base64::encode(&digest[..24]);
+
+    String::from_utf8(encoded).unwrap()
+}
+
+/// Split a message into armored parts of at most `max_part_size` octets of
+/// data each
+///
+/// The parts are numbered "PART X/Y" and carry the MessageID of the whole
+/// message.
+pub fn split(message: &[u8], max_part_size: usize) -> Result<Vec<String>, ArmorError> {
+    if max_part_size == 0 {
+        return Err(ArmorError::MultipartInvalidPartSize)
+    }
+
+    let message_id = message_id(message);
+    let total = message.len().div_ceil(max_part_size).max(1);
+
+    let chunks: Vec<&[u8]> = match message.is_empty() {
+        true => vec![message],
+        false => message.chunks(max_part_size).collect(),
+    };
+
+    let parts = chunks.iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut encoder = ArmorEncoder::new(Vec::new(), ArmorDataType::PgpMessagePartXy(i + 1, total))
+                .add_data_header(ArmorDataHeader::MessageId, &message_id)
+            ;
+
+            // Writing to a Vec cannot fail
+            encoder.write_all(chunk).unwrap();
+            let part = encoder.finish().unwrap();
+
+            String::from_utf8(part).unwrap()
+        })
+        .collect()
+    ;
+
+    Ok(parts)
+}
+
+/// Reassemble a message from the texts holding its parts
+///
+/// The parts may be given in any order, and may be surrounded by other
+/// text. Armored blocks that are not parts of a multi-part message are
+/// ignored. Fails when an armored block cannot be decoded, or when a part
+/// is missing, appears twice, has a wrong checksum, or belongs to another
+/// message.
+pub fn join(texts: &[&str]) -> Result<Vec<u8>, ArmorError> {
+    let mut parts: Vec<(usize, Option<usize>, ArmorBlock)> = vec![];
+
+    for text in texts {
+        for block in ArmorBlocks::new(text) {
+            let block = block.map_err(|block| block.error)?;
+
+            let (number, total) = match block.data_type {
+                ArmorDataType::PgpMessagePartXy(x, y) => (x, Some(y)),
+                ArmorDataType::PgpMessagePartX(x) => (x, None),
+                _ => continue,
+            };
+
+            if let ArmorChecksumStatus::Mismatch(checksum) = block.checksum_status {
+                return Err(ArmorError::ChecksumMismatch(checksum.get()))
+            }
+
+            parts.push((number, total, block));
+        }
+    }
+
+    if parts.is_empty() {
+        return Err(ArmorError::MultipartNoParts)
+    }
+
+    let message_ids: Vec<Option<&str>> = parts.iter()
+        .map(|(_, _, block)| block.data_headers.get(&ArmorDataHeader::MessageId).and_then(|ids| ids.first().copied()))
+        .collect()
+    ;
+
+    if message_ids.iter().any(|id| *id != message_ids[0]) {
+        return Err(ArmorError::MultipartMismatch)
+    }
+
+    // Without "/Y", only the MessageID tells that the parts belong together
+    if message_ids[0].is_none() && parts.iter().any(|(_, total, _)| total.is_none()) {
+        return Err(ArmorError::MultipartMissingMessageId)
+    }
+
+    let mut totals: Vec<usize> = parts.iter().filter_map(|(_, total, _)| *total).collect();
+    totals.sort_unstable();
+    totals.dedup();
+
+    let total = match totals[..] {
+        [] => parts.iter().map(|(number, _, _)| *number).max().unwrap_or(0),
+        [total] => total,
+        _ => return Err(ArmorError::MultipartMismatch),
+    };
+
+    parts.sort_by_key(|(number, _, _)| *number);
+
+    let mut message = vec![];
+
+    for (i, (number, _, block)) in parts.iter().enumerate() {
+        let expected = i + 1;
+
+        if *number < expected {
+            return Err(ArmorError::MultipartDuplicatePart(*number))
+        }
+
+        if *number > total {
+            return Err(ArmorError::MultipartMismatch)
+        }
+
+        if *number > expected {
+            return Err(ArmorError::MultipartMissingPart(expected))
+        }
+
+        message.extend(&block.data);
+    }
+
+    if parts.len() < total {
+        return Err(ArmorError::MultipartMissingPart(parts.len() + 1))
+    }
+
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_and_join() {
+        let message: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+
+        for max_part_size in [1, 7, 48, 999, 1000, 5000].iter() {
+            let parts = split(&message, *max_part_size).unwrap();
+            assert_eq!(parts.len(), message.len().div_ceil(*max_part_size));
+
+            let mut parts: Vec<&str> = parts.iter().map(|part| &part[..]).collect();
+            parts.reverse();
+
+            assert_eq!(join(&parts).unwrap(), message, "{}", max_part_size);
+        }
+    }
+
+    #[test]
+    fn split_is_deterministic() {
+        assert_eq!(split(b"Hello, world!", 5).unwrap(), split(b"Hello, world!", 5).unwrap());
+    }
+
+    #[test]
+    fn split_empty_message() {
+        let parts = split(b"", 10).unwrap();
+
+        assert_eq!(parts.len(), 1);
+        assert_eq!(join(&[&parts[0]]).unwrap(), b"");
+    }
+
+    #[test]
+    fn split_fails_for_empty_parts() {
+        assert_eq!(split(b"Hello", 0), Err(ArmorError::MultipartInvalidPartSize));
+    }
+
+    #[test]
+    fn join_parts_embedded_in_text() {
+        let parts = split(b"Hello, world!", 5).unwrap();
+        let text = format!("First mail:\n{}\nSecond mail:\n{}{}Bye\n", parts[1], parts[2], parts[0]);
+
+        assert_eq!(join(&[&text]).unwrap(), b"Hello, world!");
+    }
+
+    #[test]
+    fn join_parts_without_total() {
+        let text = "\
+            -----BEGIN PGP MESSAGE, PART 2-----\n\
+            MessageID: ABC\n\
+            \n\
+            V29ybGQ=\n\
+            -----END PGP MESSAGE, PART 2-----\n\
+            -----BEGIN PGP MESSAGE, PART 1-----\n\
+            MessageID: ABC\n\
+            \n\
+            SGVsbG8=\n\
+            -----END PGP MESSAGE, PART 1-----\n\
+        ";
+
+        assert_eq!(join(&[text]).unwrap(), b"HelloWorld");
+        assert_eq!(join(&[&text.replace("MessageID: ABC\n", "")]), Err(ArmorError::MultipartMissingMessageId));
+    }
+
+    #[test]
+    fn join_fails_for_invalid_parts() {
+        let parts = split(b"Hello, world!", 5).unwrap();
+        let other = split(b"Hello, World!", 5).unwrap();
+
+        assert_eq!(join(&[]), Err(ArmorError::MultipartNoParts));
+        assert_eq!(join(&[&parts[0], &parts[2]]), Err(ArmorError::MultipartMissingPart(2)));
+        assert_eq!(join(&[&parts[0], &parts[1]]), Err(ArmorError::MultipartMissingPart(3)));
+        assert_eq!(join(&[&parts[0], &parts[1], &parts[1], &parts[2]]), Err(ArmorError::MultipartDuplicatePart(2)));
+        assert_eq!(join(&[&parts[0], &other[1], &parts[2]]), Err(ArmorError::MultipartMismatch));
+
+        // Changes the data of the second part, "LCB3b3I=", to ", Wor"
+        let corrupted = parts[1].replace("LCB3b3I=", "LCBXb3I=");
+        assert_eq!(join(&[&parts[0], &corrupted, &parts[2]]), Err(ArmorError::ChecksumMismatch(String::from("=mNyZ"))));
+    }
+}
@@ -91,10 +91,7 @@ impl ArmorReader {
 
                 let value = data_header[1].trim();
 
-                output
-                    .entry(key.unwrap())
-                    .or_insert_with(Vec::new)
-                    .push(String::from(value))
+                output.add(key.unwrap(), value)
             })
         ;
 
@@ -195,7 +192,7 @@ mod tests {
 
         assert_eq!(
             armor.data_headers.get(&ArmorDataHeader::Version),
-            Some(&vec![String::new()])
+            Some(vec![""])
         );
     }
 
@@ -210,7 +207,7 @@ mod tests {
 
         assert_eq!(
             armor.data_headers.get(&ArmorDataHeader::Version),
-            Some(&vec![String::from("OpenPrivacy 0.99")])
+            Some(vec!["OpenPrivacy 0.99"])
         );
     }
 
@@ -226,9 +223,9 @@ mod tests {
 
         assert_eq!(
             armor.data_headers.get(&ArmorDataHeader::Comment),
-            Some(&vec![
-                String::from("Comment on first line"),
-                String::from("And also on second line"),
+            Some(vec![
+                "Comment on first line",
+                "And also on second line",
             ])
         );
     }
@@ -246,14 +243,14 @@ mod tests {
 
         assert_eq!(
             armor.data_headers.get(&ArmorDataHeader::Comment),
-            Some(&vec![
-                String::from("Comment on first line"),
-                String::from("And also on second line"),
+            Some(vec![
+                "Comment on first line",
+                "And also on second line",
             ])
         );
         assert_eq!(
             armor.data_headers.get(&ArmorDataHeader::Charset),
-            Some(&vec![String::from("UTF-8")])
+            Some(vec!["UTF-8"])
         );
     }
 
@@ -1,13 +1,8 @@
 use std::io::Write;
 use std::io::Result;
 
-use crate::{ArmorChecksum, ArmorDataHeader, ArmorDataType};
+use crate::{ArmorChecksum, ArmorDataHeader, ArmorDataType, ArmorError, ArmorFormat};
 use crate::ArmorDataHeaderMap;
-use crate::LINE_ENDING;
-
-/// The encoded output stream must be represented in lines of no more
-/// than 76 characters each according to RFC 4880. GnuPG uses 64.
-const LINE_LENGTH: usize = 64;
 
 /// ArmorWriter for creating ASCII Armor
 ///
@@ -21,11 +16,48 @@ pub struct ArmorWriter<'a> {
     data_headers: ArmorDataHeaderMap,
     data: Option<&'a [u8]>,
     checksum: Option<ArmorChecksum>,
+    format: ArmorFormat,
 }
 
 impl<'a> ArmorWriter<'_> {
+    /// Write to a buffer, failing when the data type or the data is missing
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ascii_armor::{ArmorDataType, ArmorWriterBuilder};
+    ///
+    /// let mut buffer: Vec<u8> = vec![];
+    ///
+    /// assert!(ArmorWriterBuilder::new().data(b"Hello").build().write(&mut buffer).is_err());
+    /// assert!(buffer.is_empty());
+    ///
+    /// let armor = ArmorWriterBuilder::new()
+    ///     .data_type(ArmorDataType::PgpMessage)
+    ///     .data(b"Hello")
+    ///     .build()
+    /// ;
+    ///
+    /// assert!(armor.write(&mut buffer).is_ok());
+    /// ```
+    pub fn write(&self, buffer: &mut dyn Write) -> Result<()> {
+        if self.data_type.is_none() {
+            return Err(ArmorError::WriterMissingDataType.into())
+        }
+
+        if self.data.is_none() {
+            return Err(ArmorError::WriterMissingData.into())
+        }
+
+        self.write_unchecked(buffer)
+    }
+
     /// Write to a buffer without failing on missing properties
+    ///
+    /// Still fails before writing anything when a data header has an
+    /// invalid `Other` key, or a value that does not fit on a single line.
     pub fn write_unchecked(&self, buffer: &mut dyn Write) -> Result<()> {
+        self.data_headers.check()?;
+
         if let Some(data_type) = &self.data_type {
             self.write_header_line(data_type, buffer)?;
         }
@@ -35,7 +67,10 @@ impl<'a> ArmorWriter<'_> {
 
         if let Some(data) = self.data {
             self.write_data(data, buffer)?;
-            self.write_checksum(&self.checksum.unwrap().to_owned(), buffer)?;
+        }
+
+        if let Some(checksum) = self.checksum.filter(|_| self.format.get_checksum()) {
+            self.write_checksum(&checksum, buffer)?;
         }
 
         if let Some(data_type) = &self.data_type {
@@ -46,25 +81,27 @@ impl<'a> ArmorWriter<'_> {
     }
 
     fn write_header_line(&self, data_type: &ArmorDataType, buffer: &mut dyn Write) -> Result<()> {
-        let header_line = format!("-----BEGIN {}-----{}", data_type.to_string(), LINE_ENDING);
+        let header_line = format!("-----BEGIN {}-----{}", data_type.to_string(), self.line_ending());
         buffer.write_all(header_line.as_bytes())?;
 
         Ok(())
     }
 
     fn write_footer_line(&self, data_type: &ArmorDataType, buffer: &mut dyn Write) -> Result<()> {
-        let header_line = format!("-----END {}-----{}", data_type.to_string(), LINE_ENDING);
+        let header_line = format!("-----END {}-----{}", data_type.to_string(), self.line_ending());
         buffer.write_all(header_line.as_bytes())?;
 
         Ok(())
     }
 
     fn write_data_headers(&self, data_headers: &ArmorDataHeaderMap, buffer: &mut dyn Write) -> Result<()> {
-        for (key, values) in data_headers {
-            for value in values {
-                let data_header_line = format!("{}: {}{}", key.to_str(), value, LINE_ENDING);
-                buffer.write_all(data_header_line.as_bytes())?;
+        for (key, value) in data_headers {
+            if *key == ArmorDataHeader::Version && !self.format.get_version_header() {
+                continue
             }
+
+            let data_header_line = format!("{}: {}{}", key.to_str(), value, self.line_ending());
+            buffer.write_all(data_header_line.as_bytes())?;
         }
 
         Ok(())
@@ -73,7 +110,7 @@ impl<'a> ArmorWriter<'_> {
     fn write_data(&self, data: &[u8], buffer: &mut dyn Write) -> Result<()> {
         let encoded = base64::encode(data);
 
-        for line in encoded.chunks(LINE_LENGTH) {
+        for line in encoded.chunks(self.format.get_line_length()) {
             buffer.write_all(&line)?;
             self.write_newline(buffer)?;
         }
@@ -89,9 +126,13 @@ impl<'a> ArmorWriter<'_> {
     }
 
     fn write_newline(&self, buffer: &mut dyn Write) -> Result<()> {
-        buffer.write_all(LINE_ENDING.as_bytes())?;
+        buffer.write_all(self.line_ending().as_bytes())?;
         Ok(())
     }
+
+    fn line_ending(&self) -> &'static str {
+        self.format.get_line_ending().to_str()
+    }
 }
 
 /// ArmorWriterBuilder for building ArmorWriter data
@@ -126,6 +167,7 @@ pub struct ArmorWriterBuilder<'a> {
     data_headers: ArmorDataHeaderMap,
     data: Option<&'a [u8]>,
     checksum: Option<ArmorChecksum>,
+    format: ArmorFormat,
 }
 
 impl<'a> ArmorWriterBuilder<'a> {
@@ -136,6 +178,7 @@ impl<'a> ArmorWriterBuilder<'a> {
             data_headers: ArmorDataHeaderMap::new(),
             data: None,
             checksum: None,
+            format: ArmorFormat::new(),
         }
     }
 
@@ -149,13 +192,11 @@ impl<'a> ArmorWriterBuilder<'a> {
 
     /// Add a data header
     ///
-    /// Multiple data headers can be added, even with the same key
+    /// Multiple data headers can be added, even with the same key. An
+    /// invalid `Other` key or a value with a line ending makes writing
+    /// fail.
     pub fn add_data_header(mut self, key: ArmorDataHeader, value: &str) -> Self {
-        self.data_headers
-            .entry(key)
-            .or_insert_with(Vec::new)
-            .push(value.to_string())
-        ;
+        self.data_headers.add(key, value);
         self
     }
 
@@ -169,6 +210,12 @@ impl<'a> ArmorWriterBuilder<'a> {
         self
     }
 
+    /// Set how the armor is formatted
+    pub fn format(mut self, format: ArmorFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     /// Build the ArmorWriter
     pub fn build(self) -> ArmorWriter<'a> {
         ArmorWriter {
@@ -176,6 +223,7 @@ impl<'a> ArmorWriterBuilder<'a> {
             data_headers: self.data_headers,
             data: self.data,
             checksum: self.checksum,
+            format: self.format,
         }
     }
 }
@@ -184,6 +232,7 @@ impl<'a> ArmorWriterBuilder<'a> {
 mod tests {
     use crate::ArmorDataType;
     use crate::ArmorDataHeader;
+    use crate::{ArmorError, ArmorFormat, ArmorLineEnding};
     use super::ArmorWriterBuilder;
 
     #[test]
@@ -249,20 +298,120 @@ mod tests {
         let mut buffer: Vec<u8> = vec![];
         armor.write_unchecked(&mut buffer).unwrap();
 
-        let mut expected = *b"\
+        let expected = b"\
             Comment: Comment on first line\r\n\
             Comment: And also on second line\r\n\
             Charset: UTF-8\r\n\
             \r\n\
         ";
 
-        assert_eq!(buffer.len(), expected.len());
+        assert_eq!(buffer, expected);
+    }
 
-        // HashMap elements have an arbitrary order, hence sort:
-        buffer.sort();
-        expected.sort();
+    #[test]
+    fn custom_data_headers_in_order_added() {
+        let armor = ArmorWriterBuilder::new()
+            .add_data_header(ArmorDataHeader::Other("X-Zebra".into()), "First")
+            .add_data_header(ArmorDataHeader::Version, "OpenPrivacy 0.99")
+            .add_data_header(ArmorDataHeader::Other("X-Alpha".into()), "Second")
+            .add_data_header(ArmorDataHeader::Other("X-Zebra".into()), "Third")
+            .build()
+        ;
 
-        assert_eq!(buffer, expected);
+        let mut buffer: Vec<u8> = vec![];
+        armor.write_unchecked(&mut buffer).unwrap();
+
+        assert_eq!(buffer, b"\
+            X-Zebra: First\r\n\
+            Version: OpenPrivacy 0.99\r\n\
+            X-Alpha: Second\r\n\
+            X-Zebra: Third\r\n\
+            \r\n\
+        ");
+    }
+
+    #[test]
+    fn lf_line_endings_76_columns_without_checksum_and_version() {
+        let format = ArmorFormat::new()
+            .line_length(76).unwrap()
+            .line_ending(ArmorLineEnding::Lf)
+            .checksum(false)
+            .version_header(false)
+        ;
+
+        let armor = ArmorWriterBuilder::new()
+            .data_type(ArmorDataType::PgpMessage)
+            .add_data_header(ArmorDataHeader::Version, "OpenPrivacy 0.99")
+            .add_data_header(ArmorDataHeader::Comment, "Hello")
+            .data(&[0; 60])
+            .format(format)
+            .build()
+        ;
+
+        let mut buffer: Vec<u8> = vec![];
+        armor.write(&mut buffer).unwrap();
+
+        let expected = format!("\
+            -----BEGIN PGP MESSAGE-----\n\
+            Comment: Hello\n\
+            \n\
+            {}\n\
+            {}\n\
+            -----END PGP MESSAGE-----\n\
+        ", "A".repeat(76), "A".repeat(4));
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), expected);
+    }
+
+    #[test]
+    fn write_fails_for_missing_properties() {
+        let cases = vec![
+            (ArmorWriterBuilder::new().data(b"Hello"), ArmorError::WriterMissingDataType),
+            (ArmorWriterBuilder::new().data_type(ArmorDataType::PgpMessage), ArmorError::WriterMissingData),
+        ];
+
+        for (builder, expected) in cases.into_iter() {
+            let mut buffer: Vec<u8> = vec![];
+            let error = builder.build().write(&mut buffer).unwrap_err();
+
+            assert_eq!(error.to_string(), expected.to_string());
+            assert!(buffer.is_empty());
+        }
+    }
+
+    #[test]
+    fn write_fails_for_data_header_values_with_line_endings() {
+        let armor = ArmorWriterBuilder::new()
+            .data_type(ArmorDataType::PgpMessage)
+            .add_data_header(ArmorDataHeader::Comment, "Hi\r\n\r\n-----BEGIN PGP MESSAGE-----")
+            .data(b"Hello")
+            .build()
+        ;
+
+        let mut buffer: Vec<u8> = vec![];
+        let error = armor.write_unchecked(&mut buffer).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            ArmorError::WriterInvalidDataHeaderValue("Hi\r\n\r\n-----BEGIN PGP MESSAGE-----".into()).to_string()
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn write_fails_for_invalid_other_keys() {
+        let armor = ArmorWriterBuilder::new()
+            .data_type(ArmorDataType::PgpMessage)
+            .add_data_header(ArmorDataHeader::Other("A: b".into()), "c")
+            .data(b"Hello")
+            .build()
+        ;
+
+        let mut buffer: Vec<u8> = vec![];
+        let error = armor.write(&mut buffer).unwrap_err();
+
+        assert_eq!(error.to_string(), ArmorError::UnknownDataHeader("A: b".into()).to_string());
+        assert!(buffer.is_empty());
     }
 
     #[test]
@@ -303,7 +452,7 @@ mod tests {
         let mut buffer: Vec<u8> = vec![];
         armor.write_unchecked(&mut buffer).unwrap();
 
-        let mut expected = *b"\
+        let expected = b"\
             -----BEGIN PGP MESSAGE-----\r\n\
             Version: OpenPrivacy 0.99\r\n\
             Comment: Note that some transport methods are sensitive to line length.  While\r\n\
@@ -350,12 +499,6 @@ mod tests {
             -----END PGP MESSAGE-----\r\n\
         ";
 
-        assert_eq!(buffer.len(), expected.len());
-
-        // HashMap elements have an arbitrary order, hence sort:
-        buffer.sort();
-        expected.sort();
-
         assert_eq!(buffer, expected);
     }
 }
@@ -2,7 +2,7 @@ use crate::{OCTETS_PER_BLOCK, PAD_BYTE};
 use crate::SEXTETS_PER_BLOCK;
 use crate::U6;
 use crate::tables;
-use crate::DecoderError;
+use crate::{DecoderError, Engine, Padding, STANDARD};
 
 /// Decode base64 encoded data.
 ///
@@ -15,13 +15,59 @@ use crate::DecoderError;
 /// # }
 /// ```
 pub fn decode(input: &[u8]) -> Result<Vec<u8>, DecoderError> {
-    let mut input = remove_whitespaces(input);
-    input = remove_padding(&input).to_owned(); // TODO: Optimize
+    STANDARD.decode(input)
+}
+
+/// Decode using the alphabet of an engine, handling the padding as its
+/// policy says
+///
+/// Offsets in errors refer to the input as given, whitespace included.
+pub(crate) fn decode_with(input: &[u8], engine: &Engine) -> Result<Vec<u8>, DecoderError> {
+    if engine.is_strict() {
+        if let Some(offset) = input.iter().position(|c| c.is_ascii_whitespace()) {
+            return Err(DecoderError::UnexpectedChar { byte: input[offset], offset })
+        }
+    }
+
+    let mut symbols = remove_whitespaces(input);
+    let unpadded_length = remove_padding(&symbols).len();
+    let padded_length = unpadded_length.div_ceil(SEXTETS_PER_BLOCK) * SEXTETS_PER_BLOCK;
+    let is_padded = symbols.len() > unpadded_length;
+
+    let is_valid_padding = match engine.padding() {
+        Padding::Indifferent if engine.is_strict() => !is_padded || symbols.len() == padded_length,
+        Padding::Indifferent => true,
+        Padding::Required => symbols.len() == padded_length,
+        Padding::Forbidden => !is_padded,
+    };
+
+    if !is_valid_padding {
+        return Err(DecoderError::InvalidPadding { offset: symbol_offset(input, unpadded_length) })
+    }
+
+    // A single symbol holds six bits, which is not enough for an octet
+    if unpadded_length % SEXTETS_PER_BLOCK == 1 {
+        return Err(DecoderError::InvalidLength { offset: symbol_offset(input, unpadded_length - 1) })
+    }
+
+    symbols.truncate(unpadded_length);
+
+    let result = match engine.is_constant_time() {
+        true => convert_sextets_to_octets_constant_time(&mut symbols, engine.alphabet().symbols()),
+        false => convert_sextets_to_octets(&mut symbols, engine.alphabet().decode_table()),
+    };
+
+    if let Err(index) = result {
+        let offset = symbol_offset(input, index);
+        return Err(DecoderError::UnexpectedChar { byte: input[offset], offset })
+    }
 
-    convert_sextets_to_octets(&mut input)?;
+    if engine.is_strict() && !has_canonical_trailing_bits(&symbols) {
+        return Err(DecoderError::NonCanonical { offset: symbol_offset(input, unpadded_length - 1) })
+    }
 
-    let chunks = input.chunks(SEXTETS_PER_BLOCK);
-    let capacity = chunks.len() / SEXTETS_PER_BLOCK * OCTETS_PER_BLOCK;
+    let chunks = symbols.chunks(SEXTETS_PER_BLOCK);
+    let capacity = chunks.len() * OCTETS_PER_BLOCK;
 
     let mut output= Vec::with_capacity(capacity);
 
@@ -37,18 +83,93 @@ pub fn decode(input: &[u8]) -> Result<Vec<u8>, DecoderError> {
     Ok(output)
 }
 
-fn convert_sextets_to_octets(input: &mut [u8]) -> Result<(), DecoderError> {
-    for element in input {
-        *element = decode_sextet_to_octet(*element)?;
+/// The offset in the input of the symbol at an index once whitespace is
+/// removed, or the end of the input for the index after the last symbol
+fn symbol_offset(input: &[u8], index: usize) -> usize {
+    input
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| !c.is_ascii_whitespace())
+        .nth(index)
+        .map(|(offset, _)| offset)
+        .unwrap_or(input.len())
+}
+
+/// Whether the bits of the last sextet that do not make up a whole octet
+/// are zero, as encoders write them
+///
+/// Otherwise several encodings decode to the same octets. The bits are
+/// tested with a mask rather than a branch, as the sextet is secret data.
+fn has_canonical_trailing_bits(sextets: &[u8]) -> bool {
+    let unused_bits = match sextets.len() % SEXTETS_PER_BLOCK {
+        2 => 0b1111,
+        3 => 0b11,
+        _ => 0,
+    };
+
+    let last = sextets.last().copied().unwrap_or_default();
+
+    equal_mask(last & unused_bits, 0) == 0xff
+}
+
+/// Convert symbols to their sextets, or fail with the index of the first
+/// symbol outside the alphabet
+fn convert_sextets_to_octets(input: &mut [u8], table: &[u8; 256]) -> Result<(), usize> {
+    for (i, element) in input.iter_mut().enumerate() {
+        *element = decode_sextet_to_octet(*element, table).ok_or(i)?;
+    }
+
+    Ok(())
+}
+
+/// Convert without looking up tables or branching on the input, so that
+/// neither cache timing nor branch prediction depends on secret data
+///
+/// Every symbol is compared to all symbols of the alphabet, and the index
+/// of the first symbol outside of it is kept with masks as well. The input
+/// is converted in place, so that no allocation adds to the timing.
+pub fn convert_sextets_to_octets_constant_time(input: &mut [u8], symbols: &[U6; 64]) -> Result<(), usize> {
+    let mut is_valid = 0xff;
+    let mut invalid_index = 0;
+
+    for (index, element) in input.iter_mut().enumerate() {
+        let mut value = 0;
+        let mut is_found = 0;
+
+        for (i, symbol) in symbols.iter().enumerate() {
+            let is_equal = equal_mask(*element, *symbol);
+
+            value |= is_equal & i as u8;
+            is_found |= is_equal;
+        }
+
+        // All ones for the first symbol not found, all zeros otherwise
+        let is_first_invalid = ((!is_found & is_valid & 1) as usize).wrapping_neg();
+
+        invalid_index = (index & is_first_invalid) | (invalid_index & !is_first_invalid);
+        is_valid &= is_found;
+        *element = value;
+    }
+
+    if is_valid == 0 {
+        return Err(invalid_index)
     }
 
     Ok(())
 }
 
-fn decode_sextet_to_octet(input: U6) -> Result<u8, DecoderError> {
-    match tables::STD_DECODE[input as usize] {
-        tables::INVALID_VALUE => Err(DecoderError::UnexpectedChar(input)),
-        x => Ok(x),
+/// 0xff when both octets are equal, 0x00 otherwise, without branching
+fn equal_mask(a: u8, b: u8) -> u8 {
+    // Only zero wraps around when subtracting one, setting the high bits
+    let difference = (a ^ b) as u32;
+
+    (difference.wrapping_sub(1) >> 8) as u8
+}
+
+fn decode_sextet_to_octet(input: U6, table: &[u8; 256]) -> Option<u8> {
+    match table[input as usize] {
+        tables::INVALID_VALUE => None,
+        x => Some(x),
     }
 }
 
@@ -236,6 +357,109 @@ mod tests {
         assert_eq!(super::decode(encoded).unwrap(), expected);
     }
 
+    #[test]
+    fn equal_mask_for_all_octets() {
+        for a in 0..=255 {
+            for b in 0..=255 {
+                assert_eq!(super::equal_mask(a, b), if a == b { 0xff } else { 0x00 });
+            }
+        }
+    }
+
+    #[test]
+    fn constant_time_conversion_finds_first_invalid_symbol() {
+        let symbols = crate::Alphabet::STANDARD.symbols();
+
+        let mut input = *b"SG*s*G8";
+        assert_eq!(super::convert_sextets_to_octets_constant_time(&mut input, symbols), Err(2));
+
+        let mut input = *b"*GVs";
+        assert_eq!(super::convert_sextets_to_octets_constant_time(&mut input, symbols), Err(0));
+
+        let mut input = *b"SGVs";
+        assert_eq!(super::convert_sextets_to_octets_constant_time(&mut input, symbols), Ok(()));
+        assert_eq!(input, [18, 6, 21, 44]);
+    }
+
+    #[test]
+    fn decode_fails_with_offsets() {
+        use crate::DecoderError;
+
+        assert_eq!(super::decode(b"SGVs\r\n*G8="), Err(DecoderError::UnexpectedChar { byte: b'*', offset: 6 }));
+        assert_eq!(super::decode(b"SG=s"), Err(DecoderError::UnexpectedChar { byte: b'=', offset: 2 }));
+        assert_eq!(super::decode(b"SGVsb"), Err(DecoderError::InvalidLength { offset: 4 }));
+        assert_eq!(super::decode(b"SGVs\nb==="), Err(DecoderError::InvalidLength { offset: 5 }));
+    }
+
+    #[test]
+    fn decode_strict() {
+        use crate::{DecoderError, Padding, STANDARD, URL_SAFE_NO_PAD};
+
+        let strict = STANDARD.strict(true);
+
+        for input in [&b"SA=="[..], b"SGU=", b"SGVs", b"SA", b"SGU", b""].iter() {
+            assert_eq!(strict.decode(input), STANDARD.decode(input), "{:?}", input);
+        }
+
+        assert_eq!(STANDARD.decode(b"SB==").unwrap(), b"H");
+        assert_eq!(strict.decode(b"SB=="), Err(DecoderError::NonCanonical { offset: 1 }));
+        assert_eq!(strict.decode(b"SGV="), Err(DecoderError::NonCanonical { offset: 2 }));
+        assert_eq!(strict.constant_time(true).decode(b"SB=="), Err(DecoderError::NonCanonical { offset: 1 }));
+        assert_eq!(strict.constant_time(true).decode(b"SGU=").unwrap(), b"He");
+        assert_eq!(strict.decode(b"SA="), Err(DecoderError::InvalidPadding { offset: 2 }));
+        assert_eq!(strict.decode(b"SGVs===="), Err(DecoderError::InvalidPadding { offset: 4 }));
+        assert_eq!(strict.decode(b" SGVs"), Err(DecoderError::UnexpectedChar { byte: b' ', offset: 0 }));
+
+        let strict_required = crate::Engine::new(crate::Alphabet::STANDARD, Padding::Required).strict(true);
+        assert_eq!(strict_required.decode(b"SA"), Err(DecoderError::InvalidPadding { offset: 2 }));
+        assert_eq!(URL_SAFE_NO_PAD.strict(true).decode(b"SA=="), Err(DecoderError::InvalidPadding { offset: 2 }));
+    }
+
+    #[test]
+    fn decode_never_panics() {
+        use crate::{Alphabet, Engine, Padding};
+
+        let mut engines = vec![];
+
+        for padding in [Padding::Indifferent, Padding::Required, Padding::Forbidden].iter() {
+            for strict in [false, true].iter() {
+                engines.push(Engine::new(Alphabet::STANDARD, *padding).strict(*strict));
+            }
+        }
+
+        // All inputs of up to two octets
+        for a in 0..=255 {
+            for b in 0..=255 {
+                for engine in engines.iter() {
+                    let _ = engine.decode(&[a]);
+                    let _ = engine.decode(&[a, b]);
+                }
+            }
+        }
+
+        // All inputs of up to six octets built from symbols, padding,
+        // whitespace and an invalid character
+        let octets = b"AQ/= \n*";
+        let mut input = vec![];
+
+        for length in 0..=6 {
+            for n in 0..octets.len().pow(length) {
+                let mut n = n;
+                input.clear();
+
+                for _ in 0..length {
+                    input.push(octets[n % octets.len()]);
+                    n /= octets.len();
+                }
+
+                for engine in engines.iter() {
+                    let _ = engine.decode(&input);
+                    let _ = engine.constant_time(true).decode(&input);
+                }
+            }
+        }
+    }
+
     #[test]
     fn remove_padding_0() {
         assert_eq!(super::remove_padding(b"SGVs"), b"SGVs");
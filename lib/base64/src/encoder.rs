@@ -1,7 +1,7 @@
 use crate::{OCTETS_PER_BLOCK, SEXTETS_PER_BLOCK};
 use crate::PAD_BYTE;
 use crate::U6;
-use crate::tables;
+use crate::STANDARD;
 
 /// Encode octets to sextets using base64.
 ///
@@ -13,6 +13,11 @@ use crate::tables;
 /// assert_eq!(base64::encode(&[0x48, 0x65, 0x6C, 0x6C, 0x6F]), b"SGVsbG8=");
 /// ```
 pub fn encode(input: &[u8]) -> Vec<U6> {
+    STANDARD.encode(input)
+}
+
+/// Encode using the symbols of an alphabet, padding the last chunk or not
+pub(crate) fn encode_with(input: &[u8], table: &[U6; 64], pad: bool) -> Vec<U6> {
     let chunks = input.chunks(OCTETS_PER_BLOCK);
     let capacity = chunks.len() / OCTETS_PER_BLOCK * SEXTETS_PER_BLOCK;
 
@@ -20,19 +25,25 @@ pub fn encode(input: &[u8]) -> Vec<U6> {
 
     for chunk in chunks {
         let chunk_encoded = match chunk.len() {
-            3 => encode_3_octets_chunk(chunk),
-            2 => encode_2_octets_chunk(chunk),
-            1 => encode_1_octet_chunk(chunk),
+            3 => encode_3_octets_chunk(chunk, table),
+            2 => encode_2_octets_chunk(chunk, table),
+            1 => encode_1_octet_chunk(chunk, table),
             n => unreachable!("A chunk must contain 1, 2 or 3 octets, but {} found.", n),
         };
 
-        output.extend(&chunk_encoded);
+        // Every octet takes up at least one sextet, the rest is padding
+        let length = match pad {
+            true => SEXTETS_PER_BLOCK,
+            false => chunk.len() + 1,
+        };
+
+        output.extend(&chunk_encoded[..length]);
     }
 
     output
 }
 
-fn encode_3_octets_chunk(input: &[u8]) -> [U6; SEXTETS_PER_BLOCK] {
+fn encode_3_octets_chunk(input: &[u8], table: &[U6; 64]) -> [U6; SEXTETS_PER_BLOCK] {
     // Source   Text (ASCII)    M              |a              |n
     //          Octets          77 (0x4d)      |97 (0x61)      |110 (0x6e)
     // Bits                     0 1 0 0 1 1¦0 1|0 1 1 0¦0 0 0 1|0 1¦1 0 1 1 1 0
@@ -54,14 +65,14 @@ fn encode_3_octets_chunk(input: &[u8]) -> [U6; SEXTETS_PER_BLOCK] {
     ];
 
     [
-        tables::STD_ENCODE[sextets[0] as usize],
-        tables::STD_ENCODE[sextets[1] as usize],
-        tables::STD_ENCODE[sextets[2] as usize],
-        tables::STD_ENCODE[sextets[3] as usize],
+        table[sextets[0] as usize],
+        table[sextets[1] as usize],
+        table[sextets[2] as usize],
+        table[sextets[3] as usize],
     ]
 }
 
-fn encode_2_octets_chunk(input: &[u8]) -> [U6; SEXTETS_PER_BLOCK] {
+fn encode_2_octets_chunk(input: &[u8], table: &[U6; 64]) -> [U6; SEXTETS_PER_BLOCK] {
     let octets_joined =
         (input[0] as u32) << 8 |
         (input[1] as u32) << 0
@@ -76,14 +87,14 @@ fn encode_2_octets_chunk(input: &[u8]) -> [U6; SEXTETS_PER_BLOCK] {
     ];
 
     [
-        tables::STD_ENCODE[sextets[0] as usize],
-        tables::STD_ENCODE[sextets[1] as usize],
-        tables::STD_ENCODE[sextets[2] as usize],
+        table[sextets[0] as usize],
+        table[sextets[1] as usize],
+        table[sextets[2] as usize],
         PAD_BYTE,
     ]
 }
 
-fn encode_1_octet_chunk(input: &[u8]) -> [U6; SEXTETS_PER_BLOCK] {
+fn encode_1_octet_chunk(input: &[u8], table: &[U6; 64]) -> [U6; SEXTETS_PER_BLOCK] {
     let octets_joined = input[0];
 
     let sextets: [U6; 2] = [
@@ -94,8 +105,8 @@ fn encode_1_octet_chunk(input: &[u8]) -> [U6; SEXTETS_PER_BLOCK] {
     ];
 
     [
-        tables::STD_ENCODE[sextets[0] as usize],
-        tables::STD_ENCODE[sextets[1] as usize],
+        table[sextets[0] as usize],
+        table[sextets[1] as usize],
         PAD_BYTE,
         PAD_BYTE,
     ]
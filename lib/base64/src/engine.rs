@@ -0,0 +1,323 @@
+use crate::{AlphabetError, DecoderError};
+use crate::U6;
+use crate::decoder;
+use crate::encoder;
+use crate::tables;
+use crate::PAD_BYTE;
+
+/// The standard alphabet and padding, as used by `encode` and `decode`
+pub const STANDARD: Engine = Engine::new(Alphabet::STANDARD, Padding::Indifferent);
+
+/// The URL and file name safe alphabet, with padding
+pub const URL_SAFE: Engine = Engine::new(Alphabet::URL_SAFE, Padding::Indifferent);
+
+/// The URL and file name safe alphabet, without padding
+pub const URL_SAFE_NO_PAD: Engine = Engine::new(Alphabet::URL_SAFE, Padding::Forbidden);
+
+/// The 64 symbols that sextets are encoded to
+///
+/// # Examples
+/// ```rust
+/// use base64::{Alphabet, AlphabetError};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let alphabet = Alphabet::new(b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz")?;
+/// assert_eq!(alphabet.symbols()[0], b'.');
+///
+/// let error = Alphabet::new(b"AACDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/");
+/// assert_eq!(error.unwrap_err(), AlphabetError::DuplicateSymbol(b'A'));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Links
+/// - [RFC 4648, Section 4: Base 64 Encoding](https://tools.ietf.org/html/rfc4648#section-4)
+/// - [RFC 4648, Section 5: Base 64 Encoding with URL and Filename Safe Alphabet](https://tools.ietf.org/html/rfc4648#section-5)
+#[derive(Clone, Copy, PartialEq)]
+pub struct Alphabet {
+    encode: [U6; 64],
+    decode: [u8; 256],
+}
+
+impl Alphabet {
+    /// "A" to "Z", "a" to "z", "0" to "9", "+" and "/"
+    pub const STANDARD: Alphabet = Alphabet {
+        encode: *tables::STD_ENCODE,
+        decode: *tables::STD_DECODE,
+    };
+
+    /// Like the standard alphabet, but with "-" and "_" instead of "+" and
+    /// "/"
+    pub const URL_SAFE: Alphabet = Alphabet::from_symbols(
+        *b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+    );
+
+    /// Create an alphabet from its symbols, in the order of the sextets
+    ///
+    /// The symbols must be printable US-ASCII characters other than the
+    /// padding character "=", and must all differ.
+    pub fn new(symbols: &[u8; 64]) -> Result<Self, AlphabetError> {
+        for (i, symbol) in symbols.iter().enumerate() {
+            if !symbol.is_ascii_graphic() || *symbol == PAD_BYTE {
+                return Err(AlphabetError::InvalidSymbol(*symbol))
+            }
+
+            if symbols[..i].contains(symbol) {
+                return Err(AlphabetError::DuplicateSymbol(*symbol))
+            }
+        }
+
+        Ok(Self::from_symbols(*symbols))
+    }
+
+    /// Create an alphabet from valid symbols, deriving the decoding table
+    const fn from_symbols(symbols: [U6; 64]) -> Self {
+        let mut decode = [tables::INVALID_VALUE; 256];
+        let mut i = 0;
+
+        while i < symbols.len() {
+            decode[symbols[i] as usize] = i as u8;
+            i += 1;
+        }
+
+        Self {
+            encode: symbols,
+            decode,
+        }
+    }
+
+    pub fn symbols(&self) -> &[U6; 64] {
+        &self.encode
+    }
+
+    pub(crate) fn decode_table(&self) -> &[u8; 256] {
+        &self.decode
+    }
+}
+
+impl std::fmt::Debug for Alphabet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("Alphabet")
+            .field(&String::from_utf8_lossy(&self.encode))
+            .finish()
+    }
+}
+
+/// How padding is written and read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Padding {
+    /// Write padding, and accept input with or without it
+    Indifferent,
+
+    /// Write padding, and reject input without it
+    Required,
+
+    /// Write no padding, and reject input with it
+    Forbidden,
+}
+
+/// Engine for encoding and decoding with an alphabet and padding policy
+///
+/// # Examples
+/// ```rust
+/// use base64::{Alphabet, Engine, Padding};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// assert_eq!(base64::URL_SAFE_NO_PAD.encode(&[0xfb, 0xff]), b"-_8");
+/// assert_eq!(base64::URL_SAFE_NO_PAD.decode(b"-_8")?, &[0xfb, 0xff]);
+/// assert!(base64::URL_SAFE_NO_PAD.decode(b"-_8=").is_err());
+///
+/// let engine = Engine::new(Alphabet::STANDARD, Padding::Required);
+/// assert_eq!(engine.decode(b"SGVsbG8=")?, b"Hello");
+/// assert!(engine.decode(b"SGVsbG8").is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Engine {
+    alphabet: Alphabet,
+    padding: Padding,
+    constant_time: bool,
+    strict: bool,
+}
+
+impl Engine {
+    pub const fn new(alphabet: Alphabet, padding: Padding) -> Self {
+        Self {
+            alphabet,
+            padding,
+            constant_time: false,
+            strict: false,
+        }
+    }
+
+    /// Decode in constant time, for secret data such as private keys
+    ///
+    /// Symbols are decoded without table lookups and without branching on
+    /// their values, at the cost of speed. Where the first invalid symbol
+    /// is, is only searched for once decoding has failed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let engine = base64::STANDARD.constant_time(true);
+    ///
+    /// assert_eq!(engine.decode(b"SGVsbG8=")?, b"Hello");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub const fn constant_time(mut self, enabled: bool) -> Self {
+        self.constant_time = enabled;
+        self
+    }
+
+    pub fn is_constant_time(&self) -> bool {
+        self.constant_time
+    }
+
+    /// Decode only the canonical encoding, as written by `encode`
+    ///
+    /// Whitespace, padding of the wrong length and set bits in the last
+    /// symbol that encode no octet are rejected. The padding policy still
+    /// decides whether padding must be there.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use base64::DecoderError;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let engine = base64::STANDARD.strict(true);
+    ///
+    /// assert_eq!(engine.decode(b"SGVsbG8=")?, b"Hello");
+    /// assert_eq!(engine.decode(b"SGVsbG9=").unwrap_err(), DecoderError::NonCanonical { offset: 6 });
+    /// assert_eq!(engine.decode(b"SGVsbG8==").unwrap_err(), DecoderError::InvalidPadding { offset: 7 });
+    /// assert_eq!(engine.decode(b"SGVs\nbG8=").unwrap_err(), DecoderError::UnexpectedChar { byte: b'\n', offset: 4 });
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Links
+    /// - [RFC 4648, Section 3.5: Canonical Encoding](https://tools.ietf.org/html/rfc4648#section-3.5)
+    pub const fn strict(mut self, enabled: bool) -> Self {
+        self.strict = enabled;
+        self
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+
+    pub fn padding(&self) -> Padding {
+        self.padding
+    }
+
+    /// Encode octets to the symbols of the alphabet
+    pub fn encode(&self, input: &[u8]) -> Vec<U6> {
+        encoder::encode_with(input, &self.alphabet.encode, self.padding != Padding::Forbidden)
+    }
+
+    /// Decode symbols of the alphabet, ignoring whitespace
+    ///
+    /// Fails on symbols outside the alphabet, on padding that the policy
+    /// does not allow, and on a single symbol left over. Never panics,
+    /// whatever the input.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecoderError> {
+        decoder::decode_with(input, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_safe_alphabet() {
+        // Octets that encode to the last two symbols of the alphabet
+        let data = [0xfb, 0xef, 0xff];
+
+        assert_eq!(STANDARD.encode(&data), b"++//");
+        assert_eq!(URL_SAFE.encode(&data), b"--__");
+        assert_eq!(URL_SAFE.decode(b"--__").unwrap(), data);
+        assert!(URL_SAFE.decode(b"++//").is_err());
+    }
+
+    #[test]
+    fn encode_padding() {
+        assert_eq!(URL_SAFE.encode(b"Hello"), b"SGVsbG8=");
+        assert_eq!(URL_SAFE_NO_PAD.encode(b"Hello"), b"SGVsbG8");
+        assert_eq!(URL_SAFE_NO_PAD.encode(b"Hell"), b"SGVsbA");
+        assert_eq!(URL_SAFE_NO_PAD.encode(b"Hel"), b"SGVs");
+        assert_eq!(URL_SAFE_NO_PAD.encode(b""), b"");
+    }
+
+    #[test]
+    fn decode_padding() {
+        let required = Engine::new(Alphabet::STANDARD, Padding::Required);
+
+        for (input, indifferent, required_ok, forbidden_ok) in [
+            (&b"SGVsbG8="[..], true, true, false),
+            (b"SGVsbG8", true, false, true),
+            (b"SGVsbA==", true, true, false),
+            (b"SGVsbA=", true, false, false),
+            (b"SGVs", true, true, true),
+        ].iter() {
+            assert_eq!(STANDARD.decode(input).is_ok(), *indifferent, "{:?}", input);
+            assert_eq!(required.decode(input).is_ok(), *required_ok, "{:?}", input);
+            assert_eq!(URL_SAFE_NO_PAD.decode(input).is_ok(), *forbidden_ok, "{:?}", input);
+        }
+
+        assert_eq!(required.decode(b"SGVsbG8"), Err(DecoderError::InvalidPadding { offset: 7 }));
+        assert_eq!(required.decode(b"SGVsbA=\r\n"), Err(DecoderError::InvalidPadding { offset: 6 }));
+    }
+
+    #[test]
+    fn custom_alphabet() {
+        // The alphabet of crypt(3)
+        let alphabet = Alphabet::new(b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz").unwrap();
+        let engine = Engine::new(alphabet, Padding::Forbidden);
+
+        let encoded = engine.encode(b"Hello World!");
+        assert_eq!(encoded, b"G4JgP4wUJqxmP4EV");
+        assert_eq!(engine.decode(&encoded).unwrap(), b"Hello World!");
+    }
+
+    #[test]
+    fn custom_alphabet_fails_for_invalid_symbols() {
+        let mut symbols = *Alphabet::STANDARD.symbols();
+
+        symbols[5] = b'=';
+        assert_eq!(Alphabet::new(&symbols), Err(AlphabetError::InvalidSymbol(b'=')));
+
+        symbols[5] = b' ';
+        assert_eq!(Alphabet::new(&symbols), Err(AlphabetError::InvalidSymbol(b' ')));
+
+        symbols[5] = b'A';
+        assert_eq!(Alphabet::new(&symbols), Err(AlphabetError::DuplicateSymbol(b'A')));
+    }
+
+    #[test]
+    fn constant_time_decoding_matches_table_decoding() {
+        let data: Vec<u8> = (0..=255).collect();
+
+        for engine in [STANDARD, URL_SAFE, URL_SAFE_NO_PAD].iter() {
+            let constant_time = engine.constant_time(true);
+            let encoded = engine.encode(&data);
+
+            assert_eq!(constant_time.decode(&encoded).unwrap(), data);
+        }
+
+        let constant_time = STANDARD.constant_time(true);
+
+        assert_eq!(constant_time.decode(b"SGVs*G8="), Err(DecoderError::UnexpectedChar { byte: b'*', offset: 4 }));
+        assert!(matches!(constant_time.decode(b"SGVsbG8"), Ok(ref x) if x == b"Hello"));
+    }
+
+    #[test]
+    fn standard_alphabet_matches_its_symbols() {
+        assert_eq!(Alphabet::new(Alphabet::STANDARD.symbols()).unwrap(), Alphabet::STANDARD);
+    }
+}
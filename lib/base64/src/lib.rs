@@ -1,9 +1,18 @@
 mod tables;
 mod encoder;
 mod decoder;
+mod engine;
+mod stream;
 
 pub use self::encoder::encode;
 pub use self::decoder::decode;
+pub use self::engine::{Alphabet, Engine, Padding};
+pub use self::engine::{STANDARD, URL_SAFE, URL_SAFE_NO_PAD};
+pub use self::stream::{DecoderReader, EncoderWriter};
+
+// Public only for the timing measurements in tests/constant-time
+#[doc(hidden)]
+pub use self::decoder::convert_sextets_to_octets_constant_time;
 
 type U6 = u8;
 
@@ -11,17 +20,72 @@ const OCTETS_PER_BLOCK: usize = 3;
 const SEXTETS_PER_BLOCK: usize = 4;
 const PAD_BYTE: u8 = b'=';
 
-#[derive(Debug)]
+/// Error of decoding, with the offset in the input where it was found
+#[derive(Debug, PartialEq)]
 pub enum DecoderError {
-    UnexpectedChar(u8),
+    /// A character outside the alphabet, or whitespace in strict mode
+    UnexpectedChar { byte: u8, offset: usize },
+
+    /// Padding that is missing, too short or too long, or not allowed
+    InvalidPadding { offset: usize },
+
+    /// A single symbol left over, which cannot encode an octet
+    InvalidLength { offset: usize },
+
+    /// A last symbol with bits set that encode no octet, in strict mode
+    NonCanonical { offset: usize },
+}
+
+impl DecoderError {
+    /// The offset in the input where decoding failed
+    pub fn offset(&self) -> usize {
+        match *self {
+            DecoderError::UnexpectedChar { offset, .. } => offset,
+            DecoderError::InvalidPadding { offset } => offset,
+            DecoderError::InvalidLength { offset } => offset,
+            DecoderError::NonCanonical { offset } => offset,
+        }
+    }
+
+    /// Move the offset by the length of the input before the decoded part
+    pub(crate) fn shift_offset(mut self, length: usize) -> Self {
+        match self {
+            DecoderError::UnexpectedChar { ref mut offset, .. } => *offset += length,
+            DecoderError::InvalidPadding { ref mut offset } => *offset += length,
+            DecoderError::InvalidLength { ref mut offset } => *offset += length,
+            DecoderError::NonCanonical { ref mut offset } => *offset += length,
+        }
+
+        self
+    }
 }
 
 impl std::fmt::Display for DecoderError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
-            DecoderError::UnexpectedChar(ref x) => write!(f, "Unexpected character `{}` found.", x),
+            DecoderError::UnexpectedChar { byte, offset } => write!(f, "Unexpected character `{}` found at offset {}.", byte, offset),
+            DecoderError::InvalidPadding { offset } => write!(f, "Padding at offset {} is missing, has the wrong length or is not allowed.", offset),
+            DecoderError::InvalidLength { offset } => write!(f, "Single symbol at offset {} cannot be decoded.", offset),
+            DecoderError::NonCanonical { offset } => write!(f, "Symbol at offset {} has unused bits set.", offset),
         }
     }
 }
 
 impl std::error::Error for DecoderError {}
+
+#[derive(Debug, PartialEq)]
+pub enum AlphabetError {
+    InvalidSymbol(u8),
+    DuplicateSymbol(u8),
+}
+
+impl std::fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            AlphabetError::InvalidSymbol(x) => write!(f, "Symbol `{}` cannot be part of an alphabet.", x),
+            AlphabetError::DuplicateSymbol(x) => write!(f, "Symbol `{}` appears more than once in the alphabet.", x),
+        }
+    }
+}
+
+impl std::error::Error for AlphabetError {}
@@ -0,0 +1,343 @@
+use std::io;
+use std::io::{Read, Write};
+
+use crate::{OCTETS_PER_BLOCK, SEXTETS_PER_BLOCK};
+use crate::Engine;
+use crate::PAD_BYTE;
+
+/// Octets read from the underlying stream at once
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// EncoderWriter for encoding data as it is written
+///
+/// Octets are encoded in groups of three. Octets that do not fill a group
+/// are kept until more are written, and encoded by `finish`.
+///
+/// # Examples
+/// ```rust
+/// use std::io::Write;
+///
+/// use base64::EncoderWriter;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut encoder = EncoderWriter::new(Vec::new(), base64::STANDARD);
+///
+/// encoder.write_all(b"Hel")?;
+/// encoder.write_all(b"lo")?;
+///
+/// assert_eq!(encoder.finish()?, b"SGVsbG8=");
+/// # Ok(())
+/// # }
+/// ```
+pub struct EncoderWriter<W: Write> {
+    output: W,
+    engine: Engine,
+
+    /// Octets not yet encoded, less than a group of three
+    pending: Vec<u8>,
+}
+
+impl<W: Write> EncoderWriter<W> {
+    pub fn new(output: W, engine: Engine) -> Self {
+        Self {
+            output,
+            engine,
+            pending: Vec::with_capacity(OCTETS_PER_BLOCK),
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.output
+    }
+
+    /// Return the underlying writer, to write to it directly
+    ///
+    /// Only useful before data is written, or after it has been written in
+    /// whole groups of three octets; otherwise the encoded data is split.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+
+    /// Encode the remaining octets and return the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        let pending = std::mem::take(&mut self.pending);
+        self.output.write_all(&self.engine.encode(&pending))?;
+
+        Ok(self.output)
+    }
+}
+
+impl<W: Write> Write for EncoderWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        // Only whole groups of three octets are encoded without padding
+        let length = (self.pending.len() + data.len()) / OCTETS_PER_BLOCK * OCTETS_PER_BLOCK;
+
+        if length == 0 {
+            self.pending.extend_from_slice(data);
+            return Ok(data.len())
+        }
+
+        let split = length - self.pending.len();
+        let mut group = std::mem::take(&mut self.pending);
+        group.extend_from_slice(&data[..split]);
+
+        self.output.write_all(&self.engine.encode(&group))?;
+        self.pending.extend_from_slice(&data[split..]);
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// DecoderReader for decoding data as it is read
+///
+/// Whitespace is skipped, unless the engine is strict. Symbols are decoded
+/// in groups of four; once padding shows up, the rest is decoded at the end
+/// of the stream, where the padding policy of the engine applies. Offsets
+/// in errors refer to the whole stream.
+///
+/// # Examples
+/// ```rust
+/// use std::io::Read;
+///
+/// use base64::DecoderReader;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut decoder = DecoderReader::new(&b"SGVsbG8s\r\nIHdvcmxk\r\nIQ==\r\n"[..], base64::STANDARD);
+///
+/// let mut data = vec![];
+/// decoder.read_to_end(&mut data)?;
+///
+/// assert_eq!(data, b"Hello, world!");
+/// # Ok(())
+/// # }
+/// ```
+pub struct DecoderReader<R: Read> {
+    input: R,
+    engine: Engine,
+
+    /// Symbols not yet decoded, whitespace included, and the offset of the
+    /// first of them in the stream
+    pending: Vec<u8>,
+    offset: usize,
+
+    /// Decoded octets not yet read, from `position` on
+    decoded: Vec<u8>,
+    position: usize,
+    state: State,
+}
+
+enum State {
+    Data,
+    Done,
+
+    /// Decoding failed; returned again on every later read
+    Failed(io::ErrorKind, String),
+}
+
+impl<R: Read> DecoderReader<R> {
+    pub fn new(input: R, engine: Engine) -> Self {
+        Self {
+            input,
+            engine,
+            pending: vec![],
+            offset: 0,
+            decoded: vec![],
+            position: 0,
+            state: State::Data,
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.input
+    }
+
+    /// Return the underlying stream
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+
+    /// Read more symbols and decode the whole groups among them, or all of
+    /// them at the end of the stream
+    fn fill(&mut self) -> io::Result<()> {
+        let mut buffer = [0; READ_BUFFER_SIZE];
+        let n = self.input.read(&mut buffer)?;
+
+        if n == 0 {
+            let pending = std::mem::take(&mut self.pending);
+            self.decode(&pending)?;
+            self.state = State::Done;
+
+            return Ok(())
+        }
+
+        self.pending.extend_from_slice(&buffer[..n]);
+
+        // Padding ends the data, so everything from its group on waits for
+        // the end of the stream
+        let mut symbols = 0;
+        let mut length = 0;
+
+        for (i, c) in self.pending.iter().enumerate() {
+            if *c == PAD_BYTE {
+                break
+            }
+
+            if !c.is_ascii_whitespace() {
+                symbols += 1;
+
+                if symbols % SEXTETS_PER_BLOCK == 0 {
+                    length = i + 1;
+                }
+            }
+        }
+
+        let groups: Vec<u8> = self.pending.drain(..length).collect();
+        self.decode(&groups)
+    }
+
+    fn decode(&mut self, symbols: &[u8]) -> io::Result<()> {
+        let offset = self.offset;
+
+        self.decoded = self.engine.decode(symbols)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.shift_offset(offset)))?
+        ;
+        self.offset += symbols.len();
+        self.position = 0;
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecoderReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.decoded.len() {
+            match &self.state {
+                State::Done => return Ok(0),
+                State::Failed(kind, message) => return Err(io::Error::new(*kind, message.clone())),
+                State::Data => {},
+            }
+
+            if let Err(error) = self.fill() {
+                self.state = State::Failed(error.kind(), error.to_string());
+                return Err(error)
+            }
+        }
+
+        let n = buffer.len().min(self.decoded.len() - self.position);
+        buffer[..n].copy_from_slice(&self.decoded[self.position..self.position + n]);
+        self.position += n;
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Alphabet, DecoderError, Padding, STANDARD, URL_SAFE_NO_PAD};
+
+    /// Reader that returns at most `size` octets per read
+    struct ChunkedReader<'a> {
+        data: &'a [u8],
+        size: usize,
+    }
+
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            let n = self.size.min(buffer.len()).min(self.data.len());
+            buffer[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+
+            Ok(n)
+        }
+    }
+
+    fn decode_in_chunks(data: &[u8], size: usize, engine: Engine) -> io::Result<Vec<u8>> {
+        let mut decoder = DecoderReader::new(ChunkedReader { data, size }, engine);
+        let mut decoded = vec![];
+        decoder.read_to_end(&mut decoded)?;
+
+        Ok(decoded)
+    }
+
+    #[test]
+    fn encode_in_chunks() {
+        let data = std::fs::read("tests/resources/gnupg-icon.png").unwrap();
+        let expected = crate::encode(&data);
+
+        for size in [1, 2, 3, 4, 5, 47, 1000].iter() {
+            let mut encoder = EncoderWriter::new(Vec::new(), STANDARD);
+
+            for chunk in data.chunks(*size) {
+                encoder.write_all(chunk).unwrap();
+            }
+
+            assert_eq!(encoder.finish().unwrap(), expected, "{}", size);
+        }
+    }
+
+    #[test]
+    fn encode_without_padding() {
+        let mut encoder = EncoderWriter::new(Vec::new(), URL_SAFE_NO_PAD);
+        encoder.write_all(&[0xfb, 0xff]).unwrap();
+
+        assert_eq!(encoder.finish().unwrap(), b"-_8");
+    }
+
+    #[test]
+    fn decode_lines_in_chunks() {
+        let data = std::fs::read("tests/resources/gnupg-icon.png").unwrap();
+        let encoded: Vec<u8> = crate::encode(&data)
+            .chunks(64)
+            .flat_map(|line| line.iter().chain(b"\r\n"))
+            .copied()
+            .collect()
+        ;
+
+        for size in [1, 2, 3, 5, 63, 66, 5000].iter() {
+            assert_eq!(decode_in_chunks(&encoded, *size, STANDARD).unwrap(), data, "{}", size);
+        }
+    }
+
+    #[test]
+    fn decode_padding_split_across_chunks() {
+        let required = Engine::new(Alphabet::STANDARD, Padding::Required);
+
+        for size in 1..=8 {
+            assert_eq!(decode_in_chunks(b"SGVsbA==", size, required).unwrap(), b"Hell");
+            assert!(decode_in_chunks(b"SGVsbA=", size, required).is_err());
+            assert!(decode_in_chunks(b"SGVsbA==SGVs", size, STANDARD).is_err());
+        }
+    }
+
+    #[test]
+    fn decode_fails_for_invalid_character() {
+        let mut decoder = DecoderReader::new(&b"SGVs*G8="[..], STANDARD);
+
+        assert_eq!(decoder.read_to_end(&mut vec![]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(decoder.read(&mut [0; 16]).is_err());
+    }
+
+    #[test]
+    fn decode_fails_with_offset_in_stream() {
+        let data = b"SGVsbG8s\r\nIHdvcmxk\r\nIQ*=\r\n";
+        let expected = DecoderError::UnexpectedChar { byte: b'*', offset: 22 }.to_string();
+
+        for size in [1, 3, 10, 100].iter() {
+            let error = decode_in_chunks(data, *size, STANDARD).unwrap_err();
+            assert_eq!(error.to_string(), expected, "{}", size);
+        }
+    }
+
+    #[test]
+    fn decode_strict_rejects_whitespace() {
+        let strict = STANDARD.strict(true);
+
+        assert_eq!(decode_in_chunks(b"SGVsbG8=", 3, strict).unwrap(), b"Hello");
+        assert!(decode_in_chunks(b"SGVs\nbG8=", 3, strict).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use std::io;
+use std::io::Read;
+
+/// Reads bits from a byte stream, most significant bit first
+pub struct BitReader<R: Read> {
+    input: R,
+    buffer: Vec<u8>,
+    position: usize,
+    bits: u64,
+    count: u32,
+}
+
+impl<R: Read> BitReader<R> {
+    const BUFFER_SIZE: usize = 1 << 13;
+
+    pub fn new(input: R) -> Self {
+        Self {
+            input,
+            buffer: vec![],
+            position: 0,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    /// Read `n` bits, at most 32
+    pub fn bits(&mut self, n: u32) -> io::Result<u32> {
+        while self.count < n {
+            let byte = match self.next_byte()? {
+                Some(byte) => byte,
+                None => return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Unexpected end of compressed data.",
+                )),
+            };
+
+            self.bits = self.bits << 8 | byte as u64;
+            self.count += 8;
+        }
+
+        self.count -= n;
+
+        Ok((self.bits >> self.count) as u32 & ((1u64 << n) - 1) as u32)
+    }
+
+    pub fn bit(&mut self) -> io::Result<bool> {
+        Ok(self.bits(1)? == 1)
+    }
+
+    /// Skip to the start of the next byte
+    pub fn align(&mut self) {
+        self.count -= self.count % 8;
+    }
+
+    /// Whether the input ends at the current position, which must be aligned
+    pub fn is_at_end(&mut self) -> io::Result<bool> {
+        if self.count > 0 {
+            return Ok(false)
+        }
+
+        match self.next_byte()? {
+            Some(_) => {
+                self.position -= 1;
+                Ok(false)
+            },
+            None => Ok(true),
+        }
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        if self.position == self.buffer.len() {
+            self.buffer.resize(Self::BUFFER_SIZE, 0);
+
+            let n = self.input.read(&mut self.buffer)?;
+            self.buffer.truncate(n);
+            self.position = 0;
+
+            if n == 0 {
+                return Ok(None)
+            }
+        }
+
+        self.position += 1;
+
+        Ok(Some(self.buffer[self.position - 1]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_bits_most_significant_first() {
+        let mut reader = BitReader::new(&[0b1010_1100, 0xFF][..]);
+
+        assert_eq!(reader.bits(2).unwrap(), 0b10);
+        assert_eq!(reader.bits(3).unwrap(), 0b101);
+        assert_eq!(reader.bits(5).unwrap(), 0b10011);
+
+        reader.align();
+        assert!(reader.is_at_end().unwrap());
+        assert!(reader.bit().is_err());
+    }
+}
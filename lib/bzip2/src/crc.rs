@@ -0,0 +1,45 @@
+/// CRC-32 as used by BZip2: polynomial 0x04C11DB7, most significant bit
+/// first, unlike the reflected CRC-32 of zlib and gzip
+#[derive(Debug, Clone)]
+pub struct Crc32 {
+    value: u32,
+}
+
+impl Crc32 {
+    const POLYNOMIAL: u32 = 0x04C1_1DB7;
+
+    pub fn new() -> Self {
+        Self { value: 0xFFFF_FFFF }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for &octet in data {
+            self.value ^= (octet as u32) << 24;
+
+            for _ in 0..8 {
+                self.value = match self.value & 0x8000_0000 {
+                    0 => self.value << 1,
+                    _ => self.value << 1 ^ Self::POLYNOMIAL,
+                };
+            }
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        !self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Crc32;
+
+    #[test]
+    fn check_value() {
+        // CRC-32/BZIP2 check value
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+
+        assert_eq!(crc.value(), 0xFC89_1918);
+    }
+}
@@ -0,0 +1,457 @@
+use std::io;
+use std::io::Read;
+
+use super::bits::BitReader;
+use super::crc::Crc32;
+use super::errors::Bzip2Error;
+use super::huffman::{Huffman, MAX_CODE_LENGTH};
+
+const STREAM_MAGIC: &[u8] = b"BZh";
+const BLOCK_MAGIC: u64 = 0x3141_5926_5359;
+const END_OF_STREAM_MAGIC: u64 = 0x1772_4538_5090;
+
+/// The number of symbols coded with the same Huffman group
+const GROUP_SIZE: usize = 50;
+
+const MIN_GROUPS: usize = 2;
+const MAX_GROUPS: usize = 6;
+
+const RUN_A: u16 = 0;
+const RUN_B: u16 = 1;
+
+/// Decompresses BZip2 data while reading
+///
+/// Decodes one block at a time, so memory use is bounded by the block size
+/// of the stream (at most 900 kB). Concatenated streams are read as one.
+/// Checks the CRC of each block and of the whole stream.
+///
+/// # Examples
+/// ```rust
+/// use std::io::Read;
+///
+/// use bzip2::Bzip2Decoder;
+///
+/// // Python: bz2.compress(b"hello")
+/// let compressed = [
+///     0x42, 0x5A, 0x68, 0x39, 0x31, 0x41, 0x59, 0x26, 0x53, 0x59, 0x19, 0x31,
+///     0x65, 0x3D, 0x00, 0x00, 0x00, 0x81, 0x00, 0x02, 0x44, 0xA0, 0x00, 0x21,
+///     0x9A, 0x68, 0x33, 0x4D, 0x07, 0x33, 0x8B, 0xB9, 0x22, 0x9C, 0x28, 0x48,
+///     0x0C, 0x98, 0xB2, 0x9E, 0x80,
+/// ];
+///
+/// let mut decompressed = String::new();
+/// Bzip2Decoder::new(&compressed[..]).read_to_string(&mut decompressed).unwrap();
+///
+/// assert_eq!(decompressed, "hello");
+/// ```
+///
+/// # Links
+/// - [BZip2 format specification](https://github.com/dsnet/compress/blob/master/doc/bzip2-format.pdf)
+pub struct Bzip2Decoder<R: Read> {
+    input: BitReader<R>,
+    output: Vec<u8>,
+    delivered: usize,
+
+    /// The largest block size allowed by the stream header
+    block_size: usize,
+
+    stream_checksum: u32,
+    state: State,
+}
+
+enum State {
+    StreamHeader,
+    Block,
+    Done,
+}
+
+impl<R: Read> Bzip2Decoder<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            input: BitReader::new(input),
+            output: vec![],
+            delivered: 0,
+            block_size: 0,
+            stream_checksum: 0,
+            state: State::StreamHeader,
+        }
+    }
+
+    fn step(&mut self) -> io::Result<()> {
+        match self.state {
+            State::StreamHeader => {
+                self.read_stream_header()?;
+                self.state = State::Block;
+            },
+            State::Block => match (self.input.bits(24)? as u64) << 24 | self.input.bits(24)? as u64 {
+                BLOCK_MAGIC => self.read_block()?,
+                END_OF_STREAM_MAGIC => {
+                    if self.input.bits(32)? != self.stream_checksum {
+                        return Err(Bzip2Error::StreamChecksumMismatch.into())
+                    }
+
+                    self.input.align();
+
+                    self.state = match self.input.is_at_end()? {
+                        true => State::Done,
+                        false => State::StreamHeader,
+                    };
+                },
+                _ => return Err(Bzip2Error::InvalidBlockMagic.into()),
+            },
+            State::Done => {},
+        }
+
+        Ok(())
+    }
+
+    fn read_stream_header(&mut self) -> io::Result<()> {
+        for &expected in STREAM_MAGIC {
+            if self.input.bits(8)? != expected as u32 {
+                return Err(Bzip2Error::InvalidStreamHeader.into())
+            }
+        }
+
+        let level = self.input.bits(8)? as u8;
+
+        if !(b'1'..=b'9').contains(&level) {
+            return Err(Bzip2Error::InvalidStreamHeader.into())
+        }
+
+        self.block_size = (level - b'0') as usize * 100_000;
+        self.stream_checksum = 0;
+
+        Ok(())
+    }
+
+    fn read_block(&mut self) -> io::Result<()> {
+        let expected_checksum = self.input.bits(32)?;
+
+        if self.input.bit()? {
+            return Err(Bzip2Error::RandomisedBlock.into())
+        }
+
+        let origin = self.input.bits(24)? as usize;
+        let symbols = self.read_symbol_map()?;
+        let (groups, selectors) = self.read_huffman_groups(symbols.len() + 2)?;
+
+        let block = self.read_mtf_values(&symbols, &groups, &selectors)?;
+
+        if origin >= block.len() {
+            return Err(Bzip2Error::InvalidOriginPointer.into())
+        }
+
+        let start = self.output.len();
+        undo_run_lengths(inverse_bwt(&block, origin), &mut self.output);
+
+        let mut checksum = Crc32::new();
+        checksum.update(&self.output[start..]);
+
+        if checksum.value() != expected_checksum {
+            return Err(Bzip2Error::BlockChecksumMismatch.into())
+        }
+
+        self.stream_checksum = self.stream_checksum.rotate_left(1) ^ expected_checksum;
+
+        Ok(())
+    }
+
+    /// Read which octet values occur in the block, in two levels of 16
+    fn read_symbol_map(&mut self) -> io::Result<Vec<u8>> {
+        let ranges = self.input.bits(16)?;
+        let mut symbols = vec![];
+
+        for range in 0..16 {
+            if ranges & (0x8000 >> range) == 0 {
+                continue
+            }
+
+            let used = self.input.bits(16)?;
+
+            for i in 0..16 {
+                if used & (0x8000 >> i) != 0 {
+                    symbols.push((range * 16 + i) as u8);
+                }
+            }
+        }
+
+        if symbols.is_empty() {
+            return Err(Bzip2Error::InvalidHuffmanGroups.into())
+        }
+
+        Ok(symbols)
+    }
+
+    /// Read the Huffman codes, and which one codes each group of symbols
+    fn read_huffman_groups(&mut self, alphabet_size: usize) -> io::Result<(Vec<Huffman>, Vec<u8>)> {
+        let group_count = self.input.bits(3)? as usize;
+        let selector_count = self.input.bits(15)? as usize;
+
+        if !(MIN_GROUPS..=MAX_GROUPS).contains(&group_count) || selector_count == 0 {
+            return Err(Bzip2Error::InvalidHuffmanGroups.into())
+        }
+
+        // Selectors are move-to-front coded, each index in unary
+        let mut order: Vec<u8> = (0..group_count as u8).collect();
+        let mut selectors = Vec::with_capacity(selector_count);
+
+        for _ in 0..selector_count {
+            let mut index = 0;
+
+            while self.input.bit()? {
+                index += 1;
+
+                if index >= group_count {
+                    return Err(Bzip2Error::InvalidHuffmanGroups.into())
+                }
+            }
+
+            let selector = order.remove(index);
+            order.insert(0, selector);
+            selectors.push(selector);
+        }
+
+        // Code lengths are delta coded, starting from a 5-bit value
+        let mut groups = Vec::with_capacity(group_count);
+
+        for _ in 0..group_count {
+            let mut length = self.input.bits(5)? as usize;
+            let mut lengths = Vec::with_capacity(alphabet_size);
+
+            for _ in 0..alphabet_size {
+                loop {
+                    if !(1..=MAX_CODE_LENGTH).contains(&length) {
+                        return Err(Bzip2Error::InvalidCodeLengths.into())
+                    }
+
+                    if !self.input.bit()? {
+                        break
+                    }
+
+                    match self.input.bit()? {
+                        false => length += 1,
+                        true => length -= 1,
+                    }
+                }
+
+                lengths.push(length as u8);
+            }
+
+            groups.push(Huffman::new(&lengths)?);
+        }
+
+        Ok((groups, selectors))
+    }
+
+    /// Decode the Huffman coded symbols, undoing the zero run-length and
+    /// move-to-front coding, into the BWT-transformed block
+    fn read_mtf_values(&mut self, symbols: &[u8], groups: &[Huffman], selectors: &[u8]) -> io::Result<Vec<u8>> {
+        let end_of_block = symbols.len() as u16 + 1;
+
+        let mut order = symbols.to_vec();
+        let mut block = Vec::with_capacity(self.block_size);
+        let mut run = 0;
+        let mut run_bit = 0;
+
+        for decoded in 0.. {
+            let selector = *selectors.get(decoded / GROUP_SIZE).ok_or(Bzip2Error::InvalidHuffmanGroups)?;
+            let symbol = groups[selector as usize].decode(&mut self.input)?;
+
+            if symbol == RUN_A || symbol == RUN_B {
+                // Bijective base 2: RUNA adds 1, RUNB adds 2, at each position
+                if run_bit > 20 {
+                    return Err(Bzip2Error::BlockTooLarge.into())
+                }
+
+                run += (symbol as usize + 1) << run_bit;
+                run_bit += 1;
+                continue
+            }
+
+            if run > 0 {
+                if block.len() + run > self.block_size {
+                    return Err(Bzip2Error::BlockTooLarge.into())
+                }
+
+                block.resize(block.len() + run, order[0]);
+                run = 0;
+                run_bit = 0;
+            }
+
+            if symbol == end_of_block {
+                break
+            }
+
+            if block.len() == self.block_size {
+                return Err(Bzip2Error::BlockTooLarge.into())
+            }
+
+            let index = symbol as usize - 1;
+
+            if index >= order.len() {
+                return Err(Bzip2Error::InvalidSymbol.into())
+            }
+
+            let octet = order.remove(index);
+            order.insert(0, octet);
+            block.push(octet);
+        }
+
+        Ok(block)
+    }
+}
+
+/// Undo the Burrows–Wheeler transform, given the last column and the row
+/// of the original data
+fn inverse_bwt(block: &[u8], origin: usize) -> impl Iterator<Item = u8> + '_ {
+    let mut starts = [0usize; 256];
+
+    for &octet in block {
+        starts[octet as usize] += 1;
+    }
+
+    let mut sum = 0;
+
+    for start in starts.iter_mut() {
+        let count = *start;
+        *start = sum;
+        sum += count;
+    }
+
+    // Each entry holds the octet of its row in the low bits, and the row
+    // that follows in the original data above them
+    let mut next: Vec<u32> = block.iter().map(|&octet| octet as u32).collect();
+
+    for (i, &octet) in block.iter().enumerate() {
+        next[starts[octet as usize]] |= (i as u32) << 8;
+        starts[octet as usize] += 1;
+    }
+
+    let mut row = next[origin] >> 8;
+
+    (0..block.len()).map(move |_| {
+        let entry = next[row as usize];
+        row = entry >> 8;
+        entry as u8
+    })
+}
+
+/// Undo the initial run-length encoding: four equal octets are followed by
+/// a count of further repetitions
+fn undo_run_lengths(data: impl Iterator<Item = u8>, output: &mut Vec<u8>) {
+    let mut previous = None;
+    let mut repeated = 0;
+
+    for octet in data {
+        if repeated == 4 {
+            output.resize(output.len() + octet as usize, previous.unwrap_or(0));
+            repeated = 0;
+            previous = None;
+            continue
+        }
+
+        if Some(octet) == previous {
+            repeated += 1;
+        } else {
+            previous = Some(octet);
+            repeated = 1;
+        }
+
+        output.push(octet);
+    }
+}
+
+impl<R: Read> Read for Bzip2Decoder<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.delivered < self.output.len() {
+                let n = (self.output.len() - self.delivered).min(buffer.len());
+                buffer[..n].copy_from_slice(&self.output[self.delivered..self.delivered + n]);
+                self.delivered += n;
+
+                return Ok(n)
+            }
+
+            if matches!(self.state, State::Done) || buffer.is_empty() {
+                return Ok(0)
+            }
+
+            self.output.clear();
+            self.delivered = 0;
+
+            self.step()?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+
+    // Python: bz2.compress(b"hello hello hello hello")
+    const HELLO: [u8; 46] = hex!("
+        425A6839 31415926 535902F8 B0BD0000 03910040 000244A0 0030CD00 54869671
+        9B38A3C5 DC914E14 2400BE2C 2F40
+    ");
+
+    fn decompress(input: &[u8]) -> io::Result<Vec<u8>> {
+        let mut output = vec![];
+        Bzip2Decoder::new(input).read_to_end(&mut output)?;
+
+        Ok(output)
+    }
+
+    #[test]
+    fn decode_python_output() {
+        assert_eq!(decompress(&HELLO).unwrap(), b"hello hello hello hello");
+    }
+
+    #[test]
+    fn decode_concatenated_streams() {
+        let input = [&HELLO[..], &HELLO[..]].concat();
+
+        assert_eq!(decompress(&input).unwrap(), b"hello hello hello hello".repeat(2));
+    }
+
+    #[test]
+    fn decode_empty_stream() {
+        // Python: bz2.compress(b"")
+        assert_eq!(decompress(&hex!("425A6839 17724538 50900000 0000")).unwrap(), b"");
+    }
+
+    #[test]
+    fn fails_for_invalid_stream_header() {
+        assert!(decompress(b"BZh0").is_err());
+        assert!(decompress(b"BZx9").is_err());
+    }
+
+    #[test]
+    fn fails_for_block_checksum_mismatch() {
+        let mut input = HELLO;
+        input[10] ^= 1;
+
+        let error = decompress(&input).unwrap_err();
+        assert_eq!(error.to_string(), Bzip2Error::BlockChecksumMismatch.to_string());
+    }
+
+    #[test]
+    fn fails_for_truncated_data() {
+        assert!(decompress(&HELLO[..30]).is_err());
+    }
+
+    #[test]
+    fn undo_run_lengths_with_counts() {
+        let mut output = vec![];
+        undo_run_lengths([1, 1, 1, 1, 3, 2, 2, 2, 2, 0, 5].iter().copied(), &mut output);
+
+        assert_eq!(output, vec![1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 5]);
+    }
+
+    #[test]
+    fn inverse_bwt_of_banana() {
+        // BWT of "banana" is "nnbaaa", with the original in row 3
+        assert_eq!(inverse_bwt(b"nnbaaa", 3).collect::<Vec<u8>>(), b"banana");
+    }
+}
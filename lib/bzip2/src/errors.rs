@@ -0,0 +1,51 @@
+use std::io;
+
+/// Errors found while decompressing
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bzip2Error {
+    InvalidStreamHeader,
+    InvalidBlockMagic,
+    RandomisedBlock,
+    InvalidHuffmanGroups,
+    InvalidCodeLengths,
+    InvalidSymbol,
+    BlockTooLarge,
+    InvalidOriginPointer,
+    BlockChecksumMismatch,
+    StreamChecksumMismatch,
+}
+
+impl std::fmt::Display for Bzip2Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Bzip2Error::InvalidStreamHeader => write!(f, "Invalid BZip2 stream header."),
+            Bzip2Error::InvalidBlockMagic => write!(f, "Invalid BZip2 block magic."),
+            Bzip2Error::RandomisedBlock => write!(f, "Randomised BZip2 blocks are not supported."),
+            Bzip2Error::InvalidHuffmanGroups => write!(f, "Invalid BZip2 Huffman groups or selectors."),
+            Bzip2Error::InvalidCodeLengths => write!(f, "Invalid Huffman code lengths."),
+            Bzip2Error::InvalidSymbol => write!(f, "Invalid Huffman code."),
+            Bzip2Error::BlockTooLarge => write!(f, "BZip2 block is larger than its stream allows."),
+            Bzip2Error::InvalidOriginPointer => write!(f, "Invalid BZip2 origin pointer."),
+            Bzip2Error::BlockChecksumMismatch => write!(f, "BZip2 block CRC mismatch."),
+            Bzip2Error::StreamChecksumMismatch => write!(f, "BZip2 stream CRC mismatch."),
+        }
+    }
+}
+
+impl std::error::Error for Bzip2Error {}
+
+impl From<Bzip2Error> for io::Error {
+    fn from(error: Bzip2Error) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bzip2Error;
+
+    #[test]
+    fn block_checksum_error_formats_correctly() {
+        assert_eq!(Bzip2Error::BlockChecksumMismatch.to_string(), "BZip2 block CRC mismatch.");
+    }
+}
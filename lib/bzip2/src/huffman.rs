@@ -0,0 +1,63 @@
+use std::io;
+use std::io::Read;
+
+use super::bits::BitReader;
+use super::errors::Bzip2Error;
+
+/// The longest code allowed in BZip2 data
+pub const MAX_CODE_LENGTH: usize = 20;
+
+/// Canonical Huffman code for decoding
+pub struct Huffman {
+    counts: [u16; MAX_CODE_LENGTH + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Build the code from the code length of each symbol, between 1 and
+    /// `MAX_CODE_LENGTH`
+    pub fn new(lengths: &[u8]) -> Result<Self, Bzip2Error> {
+        let mut counts = [0u16; MAX_CODE_LENGTH + 1];
+
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+
+        let mut left: i64 = 1;
+
+        for count in &counts[1..] {
+            left = (left << 1) - *count as i64;
+
+            if left < 0 {
+                return Err(Bzip2Error::InvalidCodeLengths)
+            }
+        }
+
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16).collect();
+        symbols.sort_by_key(|&symbol| lengths[symbol as usize]);
+
+        Ok(Self { counts, symbols })
+    }
+
+    /// Decode one symbol, reading one bit at a time
+    pub fn decode<R: Read>(&self, input: &mut BitReader<R>) -> io::Result<u16> {
+        let mut code: i64 = 0;
+        let mut first: i64 = 0;
+        let mut index: i64 = 0;
+
+        for count in &self.counts[1..] {
+            code |= input.bits(1)? as i64;
+            let count = *count as i64;
+
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize])
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(Bzip2Error::InvalidSymbol.into())
+    }
+}
@@ -0,0 +1,9 @@
+mod bits;
+mod crc;
+mod huffman;
+
+pub mod decoder;
+pub mod errors;
+
+pub use self::decoder::Bzip2Decoder;
+pub use self::errors::Bzip2Error;
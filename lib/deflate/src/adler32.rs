@@ -0,0 +1,65 @@
+/// Adler-32 checksum of zlib streams
+///
+/// # Links
+/// - [RFC 1950, Section 8](https://tools.ietf.org/html/rfc1950#section-8)
+#[derive(Debug, Clone)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    const MODULUS: u32 = 65521;
+
+    /// The most octets that can be summed before `b` could overflow
+    const MAX_RUN: usize = 5552;
+
+    pub fn new() -> Self {
+        Self { a: 1, b: 0 }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for chunk in data.chunks(Self::MAX_RUN) {
+            for &octet in chunk {
+                self.a += octet as u32;
+                self.b += self.a;
+            }
+
+            self.a %= Self::MODULUS;
+            self.b %= Self::MODULUS;
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        self.b << 16 | self.a
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Adler32;
+
+    #[test]
+    fn checksum_of_wikipedia() {
+        let mut adler = Adler32::new();
+        adler.update(b"Wiki");
+        adler.update(b"pedia");
+
+        assert_eq!(adler.value(), 0x11E6_0398);
+    }
+
+    #[test]
+    fn checksum_of_long_input() {
+        let mut adler = Adler32::new();
+        adler.update(&vec![0xFF; 100_000]);
+
+        // Python: zlib.adler32(b"\xff" * 100000)
+        assert_eq!(adler.value(), 0x149A_302C);
+    }
+}
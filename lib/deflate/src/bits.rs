@@ -0,0 +1,131 @@
+use std::io;
+use std::io::Read;
+
+/// Reads bits from a byte stream, least significant bit first
+pub struct BitReader<R: Read> {
+    input: R,
+    buffer: Vec<u8>,
+    position: usize,
+    bits: u64,
+    count: u32,
+}
+
+impl<R: Read> BitReader<R> {
+    const BUFFER_SIZE: usize = 1 << 13;
+
+    pub fn new(input: R) -> Self {
+        Self {
+            input,
+            buffer: vec![],
+            position: 0,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    /// Read `n` bits, at most 32
+    pub fn bits(&mut self, n: u32) -> io::Result<u32> {
+        while self.count < n {
+            let byte = self.next_byte()?;
+            self.bits |= (byte as u64) << self.count;
+            self.count += 8;
+        }
+
+        let value = (self.bits & ((1 << n) - 1)) as u32;
+        self.bits >>= n;
+        self.count -= n;
+
+        Ok(value)
+    }
+
+    /// Skip to the start of the next byte
+    pub fn align(&mut self) {
+        let skip = self.count % 8;
+        self.bits >>= skip;
+        self.count -= skip;
+    }
+
+    fn next_byte(&mut self) -> io::Result<u8> {
+        if self.position == self.buffer.len() {
+            self.buffer.resize(Self::BUFFER_SIZE, 0);
+
+            let n = self.input.read(&mut self.buffer)?;
+            self.buffer.truncate(n);
+            self.position = 0;
+
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Unexpected end of compressed data.",
+                ))
+            }
+        }
+
+        self.position += 1;
+
+        Ok(self.buffer[self.position - 1])
+    }
+}
+
+/// Collects bits into bytes, least significant bit first
+#[derive(Default)]
+pub struct BitWriter {
+    pub output: Vec<u8>,
+    bits: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    /// Write the `n` lowest bits of `value`
+    pub fn write_bits(&mut self, value: u32, n: u32) {
+        self.bits |= (value as u64) << self.count;
+        self.count += n;
+
+        while self.count >= 8 {
+            self.output.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Write a Huffman code, which is packed starting with its most
+    /// significant bit
+    pub fn write_code(&mut self, code: u16, length: u8) {
+        let reversed = code.reverse_bits() >> (16 - length as u32);
+        self.write_bits(reversed as u32, length as u32);
+    }
+
+    /// Pad with zero bits to the start of the next byte
+    pub fn align(&mut self) {
+        if self.count > 0 {
+            self.write_bits(0, 8 - self.count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_bits_least_significant_first() {
+        let mut reader = BitReader::new(&[0b1010_1100, 0xFF][..]);
+
+        assert_eq!(reader.bits(2).unwrap(), 0b00);
+        assert_eq!(reader.bits(3).unwrap(), 0b011);
+        assert_eq!(reader.bits(5).unwrap(), 0b11_101);
+
+        reader.align();
+        assert!(reader.bits(1).is_err());
+    }
+
+    #[test]
+    fn write_bits_and_codes() {
+        let mut writer = BitWriter::default();
+        writer.write_bits(0b1, 1);
+        writer.write_code(0b011, 3);
+        writer.align();
+
+        assert_eq!(writer.output, vec![0b0000_1101]);
+    }
+}
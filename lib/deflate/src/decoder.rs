@@ -0,0 +1,310 @@
+use std::io;
+use std::io::Read;
+
+use super::bits::BitReader;
+use super::errors::InflateError;
+use super::huffman::Huffman;
+use super::tables::*;
+
+enum State {
+    BlockHeader,
+    Stored(usize),
+    Compressed(Box<(Huffman, Huffman)>),
+    Done,
+}
+
+/// Decompresses raw deflate data while reading
+///
+/// Keeps only the last 32 KiB of output for back references, so memory use
+/// does not depend on the size of the data.
+///
+/// # Examples
+/// ```rust
+/// use std::io::{Read, Write};
+///
+/// use deflate::{DeflateDecoder, DeflateEncoder};
+///
+/// let mut encoder = DeflateEncoder::new(Vec::new());
+/// encoder.write_all(b"Hello Hello Hello").unwrap();
+/// let compressed = encoder.finish().unwrap();
+///
+/// let mut decompressed = vec![];
+/// DeflateDecoder::new(&compressed[..]).read_to_end(&mut decompressed).unwrap();
+///
+/// assert_eq!(decompressed, b"Hello Hello Hello");
+/// ```
+///
+/// # Links
+/// - [RFC 1951](https://tools.ietf.org/html/rfc1951)
+pub struct DeflateDecoder<R: Read> {
+    pub(crate) input: BitReader<R>,
+    buffer: Vec<u8>,
+    delivered: usize,
+    state: State,
+    last_block: bool,
+}
+
+impl<R: Read> DeflateDecoder<R> {
+    /// How much output to decode at a time
+    const CHUNK_SIZE: usize = 1 << 15;
+
+    pub fn new(input: R) -> Self {
+        Self::with_bit_reader(BitReader::new(input))
+    }
+
+    pub(crate) fn with_bit_reader(input: BitReader<R>) -> Self {
+        Self {
+            input,
+            buffer: Vec::with_capacity(3 * WINDOW_SIZE),
+            delivered: 0,
+            state: State::BlockHeader,
+            last_block: false,
+        }
+    }
+
+    /// Whether the end of the last block has been reached
+    pub(crate) fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    fn step(&mut self) -> io::Result<()> {
+        match &mut self.state {
+            State::BlockHeader if self.last_block => {
+                self.state = State::Done;
+            },
+            State::BlockHeader => {
+                self.state = self.read_block_header()?;
+            },
+            State::Stored(remaining) => {
+                let n = (*remaining).min(Self::CHUNK_SIZE);
+
+                for _ in 0..n {
+                    self.buffer.push(self.input.bits(8)? as u8);
+                }
+
+                *remaining -= n;
+
+                if *remaining == 0 {
+                    self.state = State::BlockHeader;
+                }
+            },
+            State::Compressed(codes) => {
+                let (literal, distance) = &**codes;
+
+                if decode_symbols(&mut self.input, &mut self.buffer, literal, distance, Self::CHUNK_SIZE)? {
+                    self.state = State::BlockHeader;
+                }
+            },
+            State::Done => {},
+        }
+
+        Ok(())
+    }
+
+    fn read_block_header(&mut self) -> io::Result<State> {
+        self.last_block = self.input.bits(1)? == 1;
+
+        match self.input.bits(2)? {
+            0 => {
+                self.input.align();
+
+                let length = self.input.bits(16)?;
+                let complement = self.input.bits(16)?;
+
+                if length != !complement & 0xFFFF {
+                    return Err(InflateError::StoredLengthMismatch.into())
+                }
+
+                match length {
+                    0 => Ok(State::BlockHeader),
+                    length => Ok(State::Stored(length as usize)),
+                }
+            },
+            1 => Ok(State::Compressed(Box::new((
+                Huffman::new(&fixed_literal_lengths())?,
+                Huffman::new(&fixed_distance_lengths())?,
+            )))),
+            2 => Ok(State::Compressed(Box::new(self.read_dynamic_codes()?))),
+            _ => Err(InflateError::InvalidBlockType.into()),
+        }
+    }
+
+    /// RFC 1951, Section 3.2.7.
+    fn read_dynamic_codes(&mut self) -> io::Result<(Huffman, Huffman)> {
+        let literals = self.input.bits(5)? as usize + 257;
+        let distances = self.input.bits(5)? as usize + 1;
+        let code_lengths = self.input.bits(4)? as usize + 4;
+
+        if literals > 286 || distances > 30 {
+            return Err(InflateError::InvalidCodeLengths.into())
+        }
+
+        let mut code_length_lengths = [0; 19];
+
+        for &symbol in &CODE_LENGTH_ORDER[..code_lengths] {
+            code_length_lengths[symbol] = self.input.bits(3)? as u8;
+        }
+
+        let code_length_code = Huffman::new(&code_length_lengths)?;
+        let mut lengths = Vec::with_capacity(literals + distances);
+
+        while lengths.len() < literals + distances {
+            let (length, repeat) = match code_length_code.decode(&mut self.input)? {
+                symbol @ 0..=15 => (symbol as u8, 1),
+                16 => {
+                    let previous = *lengths.last().ok_or(InflateError::InvalidCodeLengths)?;
+                    (previous, 3 + self.input.bits(2)?)
+                },
+                17 => (0, 3 + self.input.bits(3)?),
+                _ => (0, 11 + self.input.bits(7)?),
+            };
+
+            if lengths.len() + repeat as usize > literals + distances {
+                return Err(InflateError::InvalidCodeLengths.into())
+            }
+
+            lengths.resize(lengths.len() + repeat as usize, length);
+        }
+
+        if lengths[END_OF_BLOCK as usize] == 0 {
+            return Err(InflateError::InvalidCodeLengths.into())
+        }
+
+        Ok((Huffman::new(&lengths[..literals])?, Huffman::new(&lengths[literals..])?))
+    }
+}
+
+/// Decode symbols of a compressed block into `output`, until `limit`
+/// octets have been added or the block ends
+///
+/// Returns whether the block ended.
+fn decode_symbols<R: Read>(
+    input: &mut BitReader<R>,
+    output: &mut Vec<u8>,
+    literal: &Huffman,
+    distance: &Huffman,
+    limit: usize,
+) -> io::Result<bool> {
+    let start = output.len();
+
+    while output.len() - start < limit {
+        let symbol = literal.decode(input)?;
+
+        if symbol < END_OF_BLOCK {
+            output.push(symbol as u8);
+            continue
+        }
+
+        if symbol == END_OF_BLOCK {
+            return Ok(true)
+        }
+
+        let index = (symbol - 257) as usize;
+
+        if index >= LENGTH_BASE.len() {
+            return Err(InflateError::InvalidSymbol.into())
+        }
+
+        let length = LENGTH_BASE[index] as usize + input.bits(LENGTH_EXTRA[index] as u32)? as usize;
+
+        let index = distance.decode(input)? as usize;
+
+        if index >= DISTANCE_BASE.len() {
+            return Err(InflateError::InvalidSymbol.into())
+        }
+
+        let distance = DISTANCE_BASE[index] as usize + input.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+
+        if distance > output.len() {
+            return Err(InflateError::InvalidDistance(distance).into())
+        }
+
+        // Byte by byte, since the match may overlap the octets it produces
+        for _ in 0..length {
+            output.push(output[output.len() - distance]);
+        }
+    }
+
+    Ok(false)
+}
+
+impl<R: Read> Read for DeflateDecoder<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.delivered < self.buffer.len() {
+                let n = (self.buffer.len() - self.delivered).min(buffer.len());
+                buffer[..n].copy_from_slice(&self.buffer[self.delivered..self.delivered + n]);
+                self.delivered += n;
+
+                return Ok(n)
+            }
+
+            if self.is_done() || buffer.is_empty() {
+                return Ok(0)
+            }
+
+            // Keep only the window needed for back references
+            if self.buffer.len() > 2 * WINDOW_SIZE {
+                let drop = self.buffer.len() - WINDOW_SIZE;
+                self.buffer.drain(..drop);
+                self.delivered -= drop;
+            }
+
+            self.step()?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+
+    fn inflate(input: &[u8]) -> io::Result<Vec<u8>> {
+        let mut output = vec![];
+        DeflateDecoder::new(input).read_to_end(&mut output)?;
+
+        Ok(output)
+    }
+
+    #[test]
+    fn stored_block() {
+        assert_eq!(inflate(&hex!("01 05 00 FA FF 48 65 6C 6C 6F")).unwrap(), b"Hello");
+    }
+
+    #[test]
+    fn fixed_block_with_back_reference() {
+        // Python: zlib.compress(b"hello hello hello hello")[2:-4]
+        assert_eq!(
+            inflate(&hex!("CB 48 CD C9 C9 57 C8 40 27 01")).unwrap(),
+            b"hello hello hello hello"
+        );
+    }
+
+    #[test]
+    fn empty_fixed_block() {
+        assert_eq!(inflate(&hex!("03 00")).unwrap(), b"");
+    }
+
+    #[test]
+    fn fails_for_invalid_block_type() {
+        assert!(inflate(&hex!("07 00")).is_err());
+    }
+
+    #[test]
+    fn fails_for_stored_length_mismatch() {
+        assert!(inflate(&hex!("01 05 00 FA FE 48 65 6C 6C 6F")).is_err());
+    }
+
+    #[test]
+    fn fails_for_distance_before_start() {
+        // Fixed block: length 3 (symbol 257, code 0000001), distance 1 (code 00000)
+        assert!(inflate(&hex!("03 02 00")).is_err());
+    }
+
+    #[test]
+    fn fails_for_truncated_data() {
+        assert!(inflate(&hex!("CB 48 CD C9 C9 57")).is_err());
+    }
+}
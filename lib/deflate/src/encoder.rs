@@ -0,0 +1,528 @@
+use std::io;
+use std::io::Write;
+
+use super::bits::BitWriter;
+use super::huffman::{canonical_codes, code_lengths, MAX_CODE_LENGTH};
+use super::tables::*;
+
+/// A literal octet, or a match of `length` octets `distance` back
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Symbol {
+    Literal(u8),
+    Match { length: u16, distance: u16 },
+}
+
+/// Compresses data written to it into raw deflate data
+///
+/// Finds repeated strings with hash chains, and writes each block with
+/// whichever of dynamic Huffman codes, fixed codes or no compression is
+/// smallest. Call `finish` to write the last block.
+///
+/// # Links
+/// - [RFC 1951](https://tools.ietf.org/html/rfc1951)
+pub struct DeflateEncoder<W: Write> {
+    output: W,
+    bits: BitWriter,
+
+    /// Uncompressed data, starting `WINDOW_SIZE` octets before `position`
+    /// at most
+    buffer: Vec<u8>,
+
+    /// The absolute position of the start of `buffer`
+    offset: usize,
+
+    /// The absolute position of the first octet not yet compressed
+    position: usize,
+
+    /// The absolute position of the first octet not yet written in a block
+    block_start: usize,
+
+    /// The most recent absolute position of each hash, plus one
+    head: Vec<usize>,
+
+    /// The previous absolute position with the same hash, plus one, for
+    /// each position in the window
+    previous: Vec<usize>,
+
+    symbols: Vec<Symbol>,
+}
+
+impl<W: Write> DeflateEncoder<W> {
+    /// How much input to buffer before compressing
+    const CHUNK_SIZE: usize = 1 << 16;
+
+    /// The number of symbols per block
+    const BLOCK_SYMBOLS: usize = 1 << 15;
+
+    /// How many earlier positions to try for each match
+    const MAX_CHAIN: usize = 128;
+
+    /// Stop looking once a match is at least this long
+    const GOOD_LENGTH: usize = 128;
+
+    const HASH_BITS: u32 = 15;
+
+    pub fn new(output: W) -> Self {
+        Self {
+            output,
+            bits: BitWriter::default(),
+            buffer: Vec::with_capacity(WINDOW_SIZE + Self::CHUNK_SIZE),
+            offset: 0,
+            position: 0,
+            block_start: 0,
+            head: vec![0; 1 << Self::HASH_BITS],
+            previous: vec![0; WINDOW_SIZE],
+            symbols: Vec::with_capacity(Self::BLOCK_SYMBOLS),
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+
+    /// Compress the remaining data, write the last block and return the
+    /// underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.compress(true)?;
+        self.write_block(true);
+        self.bits.align();
+        self.output.write_all(&self.bits.output)?;
+        self.output.flush()?;
+
+        Ok(self.output)
+    }
+
+    /// Find matches in the buffered data, keeping enough look-ahead for the
+    /// longest match unless this is the end of the data
+    fn compress(&mut self, is_end: bool) -> io::Result<()> {
+        let end = self.offset + self.buffer.len();
+        let limit = match is_end {
+            true => end,
+            false => end.saturating_sub(MAX_MATCH),
+        };
+
+        while self.position < limit {
+            let symbol = self.find_match(end);
+
+            let length = match symbol {
+                Symbol::Literal(_) => 1,
+                Symbol::Match { length, .. } => length as usize,
+            };
+
+            for position in self.position..self.position + length {
+                self.insert_hash(position, end);
+            }
+
+            self.position += length;
+            self.symbols.push(symbol);
+
+            if self.symbols.len() == Self::BLOCK_SYMBOLS {
+                self.write_block(false);
+                self.output.write_all(&self.bits.output)?;
+                self.bits.output.clear();
+            }
+        }
+
+        // Keep only the window needed for matches
+        let keep_from = self.position.saturating_sub(WINDOW_SIZE).max(self.offset).min(self.block_start);
+
+        if keep_from > self.offset {
+            self.buffer.drain(..keep_from - self.offset);
+            self.offset = keep_from;
+        }
+
+        Ok(())
+    }
+
+    fn hash(&self, position: usize) -> usize {
+        let i = position - self.offset;
+        let value = (self.buffer[i] as u32) << 16 | (self.buffer[i + 1] as u32) << 8 | self.buffer[i + 2] as u32;
+
+        (value.wrapping_mul(2654435761) >> (32 - Self::HASH_BITS)) as usize
+    }
+
+    fn insert_hash(&mut self, position: usize, end: usize) {
+        if position + MIN_MATCH > end {
+            return
+        }
+
+        let hash = self.hash(position);
+        self.previous[position % WINDOW_SIZE] = self.head[hash];
+        self.head[hash] = position + 1;
+    }
+
+    fn find_match(&self, end: usize) -> Symbol {
+        let position = self.position;
+        let literal = Symbol::Literal(self.buffer[position - self.offset]);
+
+        if position + MIN_MATCH > end {
+            return literal
+        }
+
+        let max_length = (end - position).min(MAX_MATCH);
+        let current = &self.buffer[position - self.offset..];
+
+        let mut best_length = MIN_MATCH - 1;
+        let mut best_distance = 0;
+        let mut candidate = self.head[self.hash(position)];
+
+        for _ in 0..Self::MAX_CHAIN {
+            // Positions are stored plus one, so zero ends the chain
+            if candidate == 0 || position - (candidate - 1) > WINDOW_SIZE || candidate - 1 < self.offset {
+                break
+            }
+
+            let start = candidate - 1;
+            let earlier = &self.buffer[start - self.offset..];
+
+            let length = current.iter()
+                .zip(earlier.iter())
+                .take(max_length)
+                .take_while(|(a, b)| a == b)
+                .count()
+            ;
+
+            if length > best_length {
+                best_length = length;
+                best_distance = position - start;
+
+                if length >= Self::GOOD_LENGTH.min(max_length) {
+                    break
+                }
+            }
+
+            let next = self.previous[start % WINDOW_SIZE];
+
+            // Older entries in the ring buffer may have been overwritten
+            if next >= candidate {
+                break
+            }
+
+            candidate = next;
+        }
+
+        match best_length >= MIN_MATCH {
+            true => Symbol::Match { length: best_length as u16, distance: best_distance as u16 },
+            false => literal,
+        }
+    }
+
+    /// Write the collected symbols as one block, with the smallest encoding
+    fn write_block(&mut self, is_last: bool) {
+        let mut literal_frequencies = [0u32; 286];
+        let mut distance_frequencies = [0u32; 30];
+
+        for symbol in &self.symbols {
+            match *symbol {
+                Symbol::Literal(octet) => literal_frequencies[octet as usize] += 1,
+                Symbol::Match { length, distance } => {
+                    literal_frequencies[257 + length_index(length)] += 1;
+                    distance_frequencies[distance_index(distance)] += 1;
+                },
+            }
+        }
+
+        literal_frequencies[END_OF_BLOCK as usize] = 1;
+
+        let literal_lengths = code_lengths(&literal_frequencies, MAX_CODE_LENGTH);
+        let distance_lengths = code_lengths(&distance_frequencies, MAX_CODE_LENGTH);
+        let header = DynamicHeader::new(&literal_lengths, &distance_lengths);
+
+        let fixed_literal_lengths = fixed_literal_lengths();
+        let fixed_distance_lengths = fixed_distance_lengths();
+
+        let data_length = self.position - self.block_start;
+        let dynamic_size = header.size() + self.symbols_size(&literal_lengths, &distance_lengths);
+        let fixed_size = self.symbols_size(&fixed_literal_lengths, &fixed_distance_lengths);
+
+        // Stored blocks hold at most 65535 octets each, and are aligned
+        let stored_size = 8 * (data_length + 5 * (data_length / 0xFFFF + 1)) + 7;
+
+        if stored_size <= dynamic_size.min(fixed_size) + 3 {
+            self.write_stored(is_last);
+        } else if dynamic_size < fixed_size {
+            self.bits.write_bits(is_last as u32, 1);
+            self.bits.write_bits(2, 2);
+            header.write(&mut self.bits);
+            self.write_symbols(&literal_lengths, &distance_lengths);
+        } else {
+            self.bits.write_bits(is_last as u32, 1);
+            self.bits.write_bits(1, 2);
+            self.write_symbols(&fixed_literal_lengths, &fixed_distance_lengths);
+        }
+
+        self.symbols.clear();
+        self.block_start = self.position;
+    }
+
+    /// Write the data of the block as stored blocks of at most 65535 octets
+    fn write_stored(&mut self, is_last: bool) {
+        let data = &self.buffer[self.block_start - self.offset..self.position - self.offset];
+        let count = 1.max(data.len().div_ceil(0xFFFF));
+
+        for i in 0..count {
+            let chunk = &data[(i * 0xFFFF).min(data.len())..((i + 1) * 0xFFFF).min(data.len())];
+
+            self.bits.write_bits((is_last && i == count - 1) as u32, 1);
+            self.bits.write_bits(0, 2);
+            self.bits.align();
+            self.bits.write_bits(chunk.len() as u32, 16);
+            self.bits.write_bits(!chunk.len() as u32 & 0xFFFF, 16);
+            self.bits.output.extend(chunk);
+        }
+    }
+
+    fn symbols_size(&self, literal_lengths: &[u8], distance_lengths: &[u8]) -> usize {
+        let mut size = literal_lengths[END_OF_BLOCK as usize] as usize;
+
+        for symbol in &self.symbols {
+            size += match *symbol {
+                Symbol::Literal(octet) => literal_lengths[octet as usize] as usize,
+                Symbol::Match { length, distance } => {
+                    let length_index = length_index(length);
+                    let distance_index = distance_index(distance);
+
+                    literal_lengths[257 + length_index] as usize
+                        + LENGTH_EXTRA[length_index] as usize
+                        + distance_lengths[distance_index] as usize
+                        + DISTANCE_EXTRA[distance_index] as usize
+                },
+            };
+        }
+
+        size
+    }
+
+    fn write_symbols(&mut self, literal_lengths: &[u8], distance_lengths: &[u8]) {
+        let literal_codes = canonical_codes(literal_lengths);
+        let distance_codes = canonical_codes(distance_lengths);
+
+        for symbol in &self.symbols {
+            match *symbol {
+                Symbol::Literal(octet) => {
+                    self.bits.write_code(literal_codes[octet as usize], literal_lengths[octet as usize]);
+                },
+                Symbol::Match { length, distance } => {
+                    let index = length_index(length);
+                    self.bits.write_code(literal_codes[257 + index], literal_lengths[257 + index]);
+                    self.bits.write_bits((length - LENGTH_BASE[index]) as u32, LENGTH_EXTRA[index] as u32);
+
+                    let index = distance_index(distance);
+                    self.bits.write_code(distance_codes[index], distance_lengths[index]);
+                    self.bits.write_bits((distance - DISTANCE_BASE[index]) as u32, DISTANCE_EXTRA[index] as u32);
+                },
+            }
+        }
+
+        let end = END_OF_BLOCK as usize;
+        self.bits.write_code(literal_codes[end], literal_lengths[end]);
+    }
+}
+
+impl<W: Write> Write for DeflateEncoder<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend(data);
+
+        if self.offset + self.buffer.len() - self.position >= Self::CHUNK_SIZE {
+            self.compress(false)?;
+        }
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// The code lengths of a dynamic block, run-length encoded
+///
+/// RFC 1951, Section 3.2.7.
+struct DynamicHeader {
+    literals: usize,
+    distances: usize,
+    code_length_lengths: Vec<u8>,
+    code_lengths_used: usize,
+
+    /// Code length symbols, with their extra bits
+    runs: Vec<(u8, u8)>,
+}
+
+impl DynamicHeader {
+    fn new(literal_lengths: &[u8], distance_lengths: &[u8]) -> Self {
+        let literals = 257.max(literal_lengths.iter().rposition(|&length| length > 0).unwrap_or(0) + 1);
+        let distances = 1.max(distance_lengths.iter().rposition(|&length| length > 0).unwrap_or(0) + 1);
+
+        let lengths = [&literal_lengths[..literals], &distance_lengths[..distances]].concat();
+        let runs = run_lengths(&lengths);
+
+        let mut frequencies = [0u32; 19];
+
+        for (symbol, _) in &runs {
+            frequencies[*symbol as usize] += 1;
+        }
+
+        let code_length_lengths = code_lengths(&frequencies, 7);
+        let code_lengths_used = 4.max(
+            CODE_LENGTH_ORDER.iter().rposition(|&symbol| code_length_lengths[symbol] > 0).unwrap_or(0) + 1
+        );
+
+        Self {
+            literals,
+            distances,
+            code_length_lengths,
+            code_lengths_used,
+            runs,
+        }
+    }
+
+    /// The size in bits, including the block type
+    fn size(&self) -> usize {
+        let runs: usize = self.runs.iter()
+            .map(|&(symbol, _)| self.code_length_lengths[symbol as usize] as usize + extra_bits(symbol) as usize)
+            .sum()
+        ;
+
+        2 + 5 + 5 + 4 + 3 * self.code_lengths_used + runs
+    }
+
+    fn write(&self, bits: &mut BitWriter) {
+        bits.write_bits(self.literals as u32 - 257, 5);
+        bits.write_bits(self.distances as u32 - 1, 5);
+        bits.write_bits(self.code_lengths_used as u32 - 4, 4);
+
+        for &symbol in &CODE_LENGTH_ORDER[..self.code_lengths_used] {
+            bits.write_bits(self.code_length_lengths[symbol] as u32, 3);
+        }
+
+        let codes = canonical_codes(&self.code_length_lengths);
+
+        for &(symbol, extra) in &self.runs {
+            bits.write_code(codes[symbol as usize], self.code_length_lengths[symbol as usize]);
+            bits.write_bits(extra as u32, extra_bits(symbol));
+        }
+    }
+}
+
+/// Run-length encode code lengths with code length symbols 16, 17 and 18
+fn run_lengths(lengths: &[u8]) -> Vec<(u8, u8)> {
+    let mut runs = vec![];
+    let mut i = 0;
+
+    while i < lengths.len() {
+        let length = lengths[i];
+        let run = lengths[i..].iter().take_while(|&&l| l == length).count();
+
+        if length == 0 && run >= 11 {
+            let n = run.min(138);
+            runs.push((18, (n - 11) as u8));
+            i += n;
+        } else if length == 0 && run >= 3 {
+            runs.push((17, (run - 3) as u8));
+            i += run;
+        } else if length != 0 && run >= 4 {
+            runs.push((length, 0));
+            let n = (run - 1).min(6);
+            runs.push((16, (n - 3) as u8));
+            i += 1 + n;
+        } else {
+            runs.push((length, 0));
+            i += 1;
+        }
+    }
+
+    runs
+}
+
+fn extra_bits(code_length_symbol: u8) -> u32 {
+    match code_length_symbol {
+        16 => 2,
+        17 => 3,
+        18 => 7,
+        _ => 0,
+    }
+}
+
+fn length_index(length: u16) -> usize {
+    LENGTH_BASE.iter().rposition(|&base| base <= length).unwrap_or(0)
+}
+
+fn distance_index(distance: u16) -> usize {
+    DISTANCE_BASE.iter().rposition(|&base| base <= distance).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::DeflateDecoder;
+
+    fn round_trip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(Vec::new());
+
+        for chunk in data.chunks(10_000) {
+            encoder.write_all(chunk).unwrap();
+        }
+
+        let compressed = encoder.finish().unwrap();
+
+        let mut decompressed = vec![];
+        DeflateDecoder::new(&compressed[..]).read_to_end(&mut decompressed).unwrap();
+        assert_eq!(decompressed, data);
+
+        compressed
+    }
+
+    #[test]
+    fn empty_input() {
+        round_trip(b"");
+    }
+
+    #[test]
+    fn repetitive_input_compresses() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(10_000);
+
+        assert!(round_trip(&data).len() < data.len() / 100);
+    }
+
+    #[test]
+    fn random_input_does_not_grow_much() {
+        // A simple generator is enough to defeat matching
+        let mut state = 0x2545F491u32;
+        let data: Vec<u8> = (0..300_000).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        }).collect();
+
+        assert!(round_trip(&data).len() < data.len() + data.len() / 1000 + 64);
+    }
+
+    #[test]
+    fn long_runs_and_mixed_input() {
+        let mut data = vec![0; 100_000];
+        data.extend((0..200_000u64).map(|i| (i * i % 251) as u8));
+        data.extend(vec![0xFF; 70_000]);
+
+        round_trip(&data);
+    }
+
+    #[test]
+    fn run_lengths_of_code_lengths() {
+        assert_eq!(run_lengths(&[0; 20]), vec![(18, 9)]);
+        assert_eq!(run_lengths(&[0; 5]), vec![(17, 2)]);
+        assert_eq!(run_lengths(&[8; 8]), vec![(8, 0), (16, 3), (8, 0)]);
+        assert_eq!(run_lengths(&[7, 7, 0]), vec![(7, 0), (7, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn length_and_distance_indices() {
+        assert_eq!(length_index(3), 0);
+        assert_eq!(length_index(12), 8);
+        assert_eq!(length_index(258), 28);
+        assert_eq!(distance_index(1), 0);
+        assert_eq!(distance_index(32768), 29);
+    }
+}
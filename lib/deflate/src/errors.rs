@@ -0,0 +1,47 @@
+use std::io;
+
+/// Errors found while decompressing
+#[derive(Debug, Clone, PartialEq)]
+pub enum InflateError {
+    InvalidBlockType,
+    StoredLengthMismatch,
+    InvalidCodeLengths,
+    InvalidSymbol,
+    InvalidDistance(usize),
+    InvalidZlibHeader,
+    PresetDictionary,
+    ChecksumMismatch,
+}
+
+impl std::fmt::Display for InflateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InflateError::InvalidBlockType => write!(f, "Invalid deflate block type."),
+            InflateError::StoredLengthMismatch => write!(f, "Stored block length does not match its complement."),
+            InflateError::InvalidCodeLengths => write!(f, "Invalid Huffman code lengths."),
+            InflateError::InvalidSymbol => write!(f, "Invalid Huffman code."),
+            InflateError::InvalidDistance(distance) => write!(f, "Distance `{}` is too far back.", distance),
+            InflateError::InvalidZlibHeader => write!(f, "Invalid zlib header."),
+            InflateError::PresetDictionary => write!(f, "Preset dictionaries are not supported."),
+            InflateError::ChecksumMismatch => write!(f, "Adler-32 checksum mismatch."),
+        }
+    }
+}
+
+impl std::error::Error for InflateError {}
+
+impl From<InflateError> for io::Error {
+    fn from(error: InflateError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InflateError;
+
+    #[test]
+    fn invalid_distance_error_formats_correctly() {
+        assert_eq!(InflateError::InvalidDistance(42).to_string(), "Distance `42` is too far back.");
+    }
+}
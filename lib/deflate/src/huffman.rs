@@ -0,0 +1,280 @@
+use std::io;
+use std::io::Read;
+
+use super::bits::BitReader;
+use super::errors::InflateError;
+
+/// The longest code allowed in deflate data
+pub const MAX_CODE_LENGTH: usize = 15;
+
+/// Canonical Huffman code for decoding
+///
+/// RFC 1951, Section 3.2.2.
+pub struct Huffman {
+    counts: [u16; MAX_CODE_LENGTH + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Build the code from the code length of each symbol
+    ///
+    /// Fails for lengths that describe more codes than there is room for.
+    /// Incomplete codes are accepted.
+    pub fn new(lengths: &[u8]) -> Result<Self, InflateError> {
+        let mut counts = [0u16; MAX_CODE_LENGTH + 1];
+
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+
+        let mut left: i32 = 1;
+
+        for count in &counts[1..] {
+            left = (left << 1) - *count as i32;
+
+            if left < 0 {
+                return Err(InflateError::InvalidCodeLengths)
+            }
+        }
+
+        let mut offsets = [0u16; MAX_CODE_LENGTH + 2];
+
+        for length in 1..=MAX_CODE_LENGTH {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+
+        let mut symbols = vec![0; offsets[MAX_CODE_LENGTH + 1] as usize];
+
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    /// Decode one symbol, reading one bit at a time
+    pub fn decode<R: Read>(&self, input: &mut BitReader<R>) -> io::Result<u16> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+
+        for count in &self.counts[1..] {
+            code |= input.bits(1)? as i32;
+            let count = *count as i32;
+
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize])
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(InflateError::InvalidSymbol.into())
+    }
+}
+
+/// Compute code lengths for symbol frequencies, limited to `max_length`
+///
+/// Symbols with a frequency of zero get no code. A single used symbol gets
+/// a code of length one.
+pub fn code_lengths(frequencies: &[u32], max_length: usize) -> Vec<u8> {
+    let mut lengths = vec![0; frequencies.len()];
+
+    let mut used: Vec<usize> = (0..frequencies.len()).filter(|&i| frequencies[i] > 0).collect();
+    used.sort_by_key(|&i| frequencies[i]);
+
+    match used.len() {
+        0 => return lengths,
+        1 => {
+            lengths[used[0]] = 1;
+            return lengths
+        },
+        _ => {},
+    }
+
+    // Two-queue Huffman construction over leaves sorted by frequency
+    let mut weights: Vec<u64> = used.iter().map(|&i| frequencies[i] as u64).collect();
+    let mut parents = vec![0; 2 * used.len() - 1];
+    let (mut next_leaf, mut next_node) = (0, used.len());
+
+    for node in used.len()..parents.len() {
+        let mut pick = || {
+            let take_leaf = next_leaf < used.len() && (next_node >= node || weights[next_leaf] <= weights[next_node]);
+
+            if take_leaf {
+                next_leaf += 1;
+                next_leaf - 1
+            } else {
+                next_node += 1;
+                next_node - 1
+            }
+        };
+
+        let (left, right) = (pick(), pick());
+        weights.push(weights[left] + weights[right]);
+        parents[left] = node;
+        parents[right] = node;
+    }
+
+    // Depths, from the root down, counted per length
+    let root = parents.len() - 1;
+    let mut depths = vec![0; parents.len()];
+    let mut counts = vec![0usize; used.len() + 1];
+
+    for node in (0..root).rev() {
+        depths[node] = depths[parents[node]] + 1;
+
+        if node < used.len() {
+            counts[depths[node]] += 1;
+        }
+    }
+
+    limit_lengths(&mut counts, max_length);
+
+    // The most frequent symbols get the shortest codes
+    let mut symbols = used.iter().rev();
+
+    for (length, &count) in counts.iter().enumerate().take(max_length + 1) {
+        for symbol in symbols.by_ref().take(count) {
+            lengths[*symbol] = length as u8;
+        }
+    }
+
+    lengths
+}
+
+/// Move codes longer than `max_length` up, and lengthen shorter ones until
+/// the code is complete again
+fn limit_lengths(counts: &mut Vec<usize>, max_length: usize) {
+    if counts.len() <= max_length + 1 {
+        counts.resize(max_length + 1, 0);
+        return
+    }
+
+    let overflow: usize = counts.drain(max_length + 1..).sum();
+    counts[max_length] += overflow;
+
+    let mut total: usize = (1..=max_length).map(|length| counts[length] << (max_length - length)).sum();
+
+    while total > 1 << max_length {
+        counts[max_length] -= 1;
+
+        for length in (1..max_length).rev() {
+            if counts[length] != 0 {
+                counts[length] -= 1;
+                counts[length + 1] += 2;
+                break
+            }
+        }
+
+        total -= 1;
+    }
+}
+
+/// Assign canonical codes to code lengths
+///
+/// RFC 1951, Section 3.2.2.
+pub fn canonical_codes(lengths: &[u8]) -> Vec<u16> {
+    let mut counts = [0u16; MAX_CODE_LENGTH + 1];
+
+    for &length in lengths {
+        counts[length as usize] += 1;
+    }
+
+    counts[0] = 0;
+
+    let mut next_code = [0u16; MAX_CODE_LENGTH + 1];
+    let mut code = 0;
+
+    for length in 1..=MAX_CODE_LENGTH {
+        code = (code + counts[length - 1]) << 1;
+        next_code[length] = code;
+    }
+
+    lengths.iter()
+        .map(|&length| {
+            let code = next_code[length as usize];
+            next_code[length as usize] += 1;
+            code
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_complete(lengths: &[u8]) -> bool {
+        let kraft: u64 = lengths.iter()
+            .filter(|&&length| length > 0)
+            .map(|&length| 1 << (MAX_CODE_LENGTH - length as usize))
+            .sum()
+        ;
+
+        kraft == 1 << MAX_CODE_LENGTH
+    }
+
+    #[test]
+    fn canonical_codes_from_rfc_example() {
+        // RFC 1951, Section 3.2.2: ABCDEFGH with lengths (3, 3, 3, 3, 3, 2, 4, 4)
+        let codes = canonical_codes(&[3, 3, 3, 3, 3, 2, 4, 4]);
+
+        assert_eq!(codes, vec![0b010, 0b011, 0b100, 0b101, 0b110, 0b00, 0b1110, 0b1111]);
+    }
+
+    #[test]
+    fn code_lengths_are_optimal() {
+        let lengths = code_lengths(&[10, 1, 1, 2, 0], 15);
+
+        assert_eq!(lengths, vec![1, 3, 3, 2, 0]);
+        assert!(is_complete(&lengths));
+    }
+
+    #[test]
+    fn code_lengths_are_limited() {
+        // Fibonacci frequencies give the deepest possible tree
+        let mut frequencies = vec![1u32, 1];
+
+        while frequencies.len() < 30 {
+            let n = frequencies.len();
+            frequencies.push(frequencies[n - 1] + frequencies[n - 2]);
+        }
+
+        let lengths = code_lengths(&frequencies, 15);
+
+        assert!(lengths.iter().all(|&length| (1..=15).contains(&length)));
+        assert!(is_complete(&lengths));
+        assert!(lengths[29] <= lengths[0]);
+    }
+
+    #[test]
+    fn new_fails_for_oversubscribed_lengths() {
+        assert!(Huffman::new(&[1, 1, 1]).is_err());
+        assert!(Huffman::new(&[1, 2, 0]).is_ok());
+    }
+
+    #[test]
+    fn decode_canonical_codes() {
+        let lengths = [3, 3, 3, 3, 3, 2, 4, 4];
+        let huffman = Huffman::new(&lengths).unwrap();
+
+        let mut writer = super::super::bits::BitWriter::default();
+
+        for (symbol, code) in canonical_codes(&lengths).into_iter().enumerate().rev() {
+            writer.write_code(code, lengths[symbol]);
+        }
+
+        writer.align();
+
+        let mut reader = BitReader::new(&writer.output[..]);
+
+        for symbol in (0..8).rev() {
+            assert_eq!(huffman.decode(&mut reader).unwrap(), symbol);
+        }
+    }
+}
@@ -0,0 +1,14 @@
+mod bits;
+mod huffman;
+mod tables;
+
+pub mod adler32;
+pub mod decoder;
+pub mod encoder;
+pub mod errors;
+pub mod zlib;
+
+pub use self::decoder::DeflateDecoder;
+pub use self::encoder::DeflateEncoder;
+pub use self::errors::InflateError;
+pub use self::zlib::{ZlibDecoder, ZlibEncoder};
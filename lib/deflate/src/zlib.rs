@@ -0,0 +1,213 @@
+use std::io;
+use std::io::{Read, Write};
+
+use super::adler32::Adler32;
+use super::bits::BitReader;
+use super::decoder::DeflateDecoder;
+use super::encoder::DeflateEncoder;
+use super::errors::InflateError;
+
+/// Compression method 8 (deflate) with a 32 KiB window, default level
+const HEADER: [u8; 2] = [0x78, 0x9C];
+
+/// Compresses data written to it into a zlib stream
+///
+/// # Links
+/// - [RFC 1950](https://tools.ietf.org/html/rfc1950)
+pub struct ZlibEncoder<W: Write> {
+    inner: DeflateEncoder<W>,
+    checksum: Adler32,
+    header_written: bool,
+}
+
+impl<W: Write> ZlibEncoder<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            inner: DeflateEncoder::new(output),
+            checksum: Adler32::new(),
+            header_written: false,
+        }
+    }
+
+    /// Write the remaining data and the checksum, and return the underlying
+    /// writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_header()?;
+
+        let mut output = self.inner.finish()?;
+        output.write_all(&self.checksum.value().to_be_bytes())?;
+        output.flush()?;
+
+        Ok(output)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if !self.header_written {
+            self.inner.get_mut().write_all(&HEADER)?;
+            self.header_written = true;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for ZlibEncoder<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.write_header()?;
+        self.checksum.update(data);
+        self.inner.write_all(data)?;
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decompresses a zlib stream while reading, and verifies its checksum at
+/// the end
+///
+/// # Examples
+/// ```rust
+/// use std::io::Read;
+///
+/// use deflate::ZlibDecoder;
+///
+/// // Python: zlib.compress(b"hello hello hello hello")
+/// let compressed = [
+///     0x78, 0x9C, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57,
+///     0xC8, 0x40, 0x27, 0x01, 0x68, 0x03, 0x08, 0xB1,
+/// ];
+///
+/// let mut decompressed = String::new();
+/// ZlibDecoder::new(&compressed[..]).read_to_string(&mut decompressed).unwrap();
+///
+/// assert_eq!(decompressed, "hello hello hello hello");
+/// ```
+///
+/// # Links
+/// - [RFC 1950](https://tools.ietf.org/html/rfc1950)
+pub struct ZlibDecoder<R: Read> {
+    inner: DeflateDecoder<R>,
+    checksum: Adler32,
+    header_read: bool,
+    verified: bool,
+}
+
+impl<R: Read> ZlibDecoder<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            inner: DeflateDecoder::with_bit_reader(BitReader::new(input)),
+            checksum: Adler32::new(),
+            header_read: false,
+            verified: false,
+        }
+    }
+
+    fn read_header(&mut self) -> io::Result<()> {
+        let method = self.inner.input.bits(8)?;
+        let flags = self.inner.input.bits(8)?;
+
+        if method & 0x0F != 8 || method >> 4 > 7 || (method << 8 | flags) % 31 != 0 {
+            return Err(InflateError::InvalidZlibHeader.into())
+        }
+
+        if flags & 0x20 != 0 {
+            return Err(InflateError::PresetDictionary.into())
+        }
+
+        self.header_read = true;
+
+        Ok(())
+    }
+
+    fn verify_checksum(&mut self) -> io::Result<()> {
+        self.inner.input.align();
+
+        let mut expected = 0;
+
+        for _ in 0..4 {
+            expected = expected << 8 | self.inner.input.bits(8)?;
+        }
+
+        if expected != self.checksum.value() {
+            return Err(InflateError::ChecksumMismatch.into())
+        }
+
+        self.verified = true;
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for ZlibDecoder<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if !self.header_read {
+            self.read_header()?;
+        }
+
+        let n = self.inner.read(buffer)?;
+        self.checksum.update(&buffer[..n]);
+
+        if n == 0 && !buffer.is_empty() && !self.verified {
+            self.verify_checksum()?;
+        }
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+
+    const HELLO: [u8; 16] = hex!("78 9C CB 48 CD C9 C9 57 C8 40 27 01 68 03 08 B1");
+
+    fn inflate(input: &[u8]) -> io::Result<Vec<u8>> {
+        let mut output = vec![];
+        ZlibDecoder::new(input).read_to_end(&mut output)?;
+
+        Ok(output)
+    }
+
+    #[test]
+    fn decode_python_output() {
+        assert_eq!(inflate(&HELLO).unwrap(), b"hello hello hello hello");
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = b"zlib round trip ".repeat(1000);
+
+        let mut encoder = ZlibEncoder::new(Vec::new());
+        encoder.write_all(&data).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(compressed[..2], HEADER);
+        assert_eq!(inflate(&compressed).unwrap(), data);
+    }
+
+    #[test]
+    fn fails_for_checksum_mismatch() {
+        let mut input = HELLO;
+        input[15] ^= 1;
+
+        let error = inflate(&input).unwrap_err();
+        assert_eq!(error.to_string(), InflateError::ChecksumMismatch.to_string());
+    }
+
+    #[test]
+    fn fails_for_invalid_header() {
+        assert!(inflate(&hex!("78 9D 03 00 00 00 00 01")).is_err());
+        assert!(inflate(&hex!("79 9C 03 00 00 00 00 01")).is_err());
+    }
+
+    #[test]
+    fn fails_for_preset_dictionary() {
+        let error = inflate(&hex!("78 BB 03 00 00 00 00 01")).unwrap_err();
+        assert_eq!(error.to_string(), InflateError::PresetDictionary.to_string());
+    }
+}
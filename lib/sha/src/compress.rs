@@ -0,0 +1,160 @@
+use super::constants::{SHA1_K, SHA256_K, SHA512_K};
+
+/// SHA-1 hash computation for a single 512-bit block
+///
+/// FIPS 180-4, Section 6.1.2.
+pub fn sha1(state: &mut [u32; 5], block: &[u8]) {
+    let mut w = [0u32; 80];
+
+    for (w, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *w = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+
+    for t in 16..80 {
+        w[t] = (w[t-3] ^ w[t-8] ^ w[t-14] ^ w[t-16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+
+    for (t, w) in w.iter().enumerate() {
+        let f = match t {
+            0..=19 => (b & c) ^ (!b & d),
+            40..=59 => (b & c) ^ (b & d) ^ (c & d),
+            _ => b ^ c ^ d,
+        };
+
+        let temp = a.rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(SHA1_K[t / 20])
+            .wrapping_add(*w)
+        ;
+
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+
+    for (state, value) in state.iter_mut().zip([a, b, c, d, e].iter()) {
+        *state = state.wrapping_add(*value);
+    }
+}
+
+/// SHA-224 and SHA-256 hash computation for a single 512-bit block
+///
+/// FIPS 180-4, Section 6.2.2.
+pub fn sha256(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+
+    for (w, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *w = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+
+    for t in 16..64 {
+        let s0 = w[t-15].rotate_right(7) ^ w[t-15].rotate_right(18) ^ (w[t-15] >> 3);
+        let s1 = w[t-2].rotate_right(17) ^ w[t-2].rotate_right(19) ^ (w[t-2] >> 10);
+        w[t] = w[t-16].wrapping_add(s0).wrapping_add(w[t-7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    for (k, w) in SHA256_K.iter().zip(w.iter()) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(*k).wrapping_add(*w);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (state, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+        *state = state.wrapping_add(*value);
+    }
+}
+
+/// SHA-384 and SHA-512 hash computation for a single 1024-bit block
+///
+/// FIPS 180-4, Section 6.4.2.
+pub fn sha512(state: &mut [u64; 8], block: &[u8]) {
+    let mut w = [0u64; 80];
+
+    for (w, chunk) in w.iter_mut().zip(block.chunks_exact(8)) {
+        let mut octets = [0; 8];
+        octets.copy_from_slice(chunk);
+        *w = u64::from_be_bytes(octets);
+    }
+
+    for t in 16..80 {
+        let s0 = w[t-15].rotate_right(1) ^ w[t-15].rotate_right(8) ^ (w[t-15] >> 7);
+        let s1 = w[t-2].rotate_right(19) ^ w[t-2].rotate_right(61) ^ (w[t-2] >> 6);
+        w[t] = w[t-16].wrapping_add(s0).wrapping_add(w[t-7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    for (k, w) in SHA512_K.iter().zip(w.iter()) {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(*k).wrapping_add(*w);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (state, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+        *state = state.wrapping_add(*value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::constants::{SHA1_H0, SHA256_H0};
+
+    /// The message "abc", padded to a single 512-bit block
+    fn padded_abc() -> [u8; 64] {
+        let mut block = [0; 64];
+        block[0..3].copy_from_slice(b"abc");
+        block[3] = 0x80;
+        block[63] = 24;
+        block
+    }
+
+    #[test]
+    fn sha1_single_block() {
+        let mut state = SHA1_H0;
+        super::sha1(&mut state, &padded_abc());
+
+        assert_eq!(state, [0xA9993E36, 0x4706816A, 0xBA3E2571, 0x7850C26C, 0x9CD0D89D]);
+    }
+
+    #[test]
+    fn sha256_single_block() {
+        let mut state = SHA256_H0;
+        super::sha256(&mut state, &padded_abc());
+
+        assert_eq!(state, [
+            0xBA7816BF, 0x8F01CFEA, 0x414140DE, 0x5DAE2223,
+            0xB00361A3, 0x96177A9C, 0xB410FF61, 0xF20015AD,
+        ]);
+    }
+}
@@ -0,0 +1,317 @@
+pub mod constants;
+pub mod compress;
+
+use constants::{SHA1_H0, SHA224_H0, SHA256_H0, SHA384_H0, SHA512_H0};
+
+macro_rules! define_sha_hasher {
+    (
+        $mod:ident,
+        $hasher:ident,
+        $word:ty,
+        $h0:expr,
+        $compress:path,
+        $block_size:expr,
+        $output_size:expr,
+        $doc:expr
+    ) => {
+        pub mod $mod {
+            use super::*;
+
+            pub type Digest = [u8; $output_size];
+
+            #[doc=$doc]
+            #[derive(Debug, Clone)]
+            pub struct $hasher {
+                state: [$word; $h0.len()],
+                buffer: Vec<u8>,
+                length: u128,
+            }
+
+            impl $hasher {
+                /// The block size in bytes
+                pub const BS: usize = $block_size;
+
+                /// The digest size in bytes
+                pub const OUTPUT_SIZE: usize = $output_size;
+
+                /// The size of the message length field appended in padding
+                ///
+                /// 64 bits for 512-bit blocks, 128 bits for 1024-bit blocks.
+                const LENGTH_SIZE: usize = Self::BS / 8;
+
+                pub fn new() -> Self {
+                    Self {
+                        state: $h0,
+                        buffer: Vec::with_capacity(Self::BS),
+                        length: 0,
+                    }
+                }
+
+                /// Feed more of the message into the hasher
+                pub fn update(&mut self, data: &[u8]) {
+                    self.length = self.length.wrapping_add(data.len() as u128);
+
+                    self.absorb(data);
+                }
+
+                /// Pad the message and return its digest
+                pub fn finalize(mut self) -> Digest {
+                    let bit_length = self.length.wrapping_mul(8).to_be_bytes();
+
+                    let unpadded = (self.length % Self::BS as u128) as usize;
+                    let zeros = (2 * Self::BS - Self::LENGTH_SIZE - unpadded - 1) % Self::BS;
+
+                    let mut padding = vec![0x80];
+                    padding.extend(vec![0; zeros]);
+                    padding.extend(&bit_length[bit_length.len() - Self::LENGTH_SIZE..]);
+
+                    self.absorb(&padding);
+
+                    let words: Vec<u8> = self.state.iter()
+                        .flat_map(|word| word.to_be_bytes())
+                        .collect()
+                    ;
+
+                    let mut output = [0; Self::OUTPUT_SIZE];
+                    output.copy_from_slice(&words[..Self::OUTPUT_SIZE]);
+
+                    output
+                }
+
+                /// Compute the digest of a complete message
+                pub fn digest(data: &[u8]) -> Digest {
+                    let mut hasher = Self::new();
+                    hasher.update(data);
+                    hasher.finalize()
+                }
+
+                fn absorb(&mut self, data: &[u8]) {
+                    let mut data = data;
+
+                    if !self.buffer.is_empty() {
+                        let missing = (Self::BS - self.buffer.len()).min(data.len());
+                        self.buffer.extend(&data[..missing]);
+                        data = &data[missing..];
+
+                        if self.buffer.len() < Self::BS {
+                            return
+                        }
+
+                        $compress(&mut self.state, &self.buffer);
+                        self.buffer.clear();
+                    }
+
+                    let mut blocks = data.chunks_exact(Self::BS);
+
+                    for block in &mut blocks {
+                        $compress(&mut self.state, block);
+                    }
+
+                    self.buffer.extend(blocks.remainder());
+                }
+            }
+
+            impl Default for $hasher {
+                fn default() -> Self {
+                    Self::new()
+                }
+            }
+        }
+    }
+}
+
+define_sha_hasher!(sha1, Sha1, u32, SHA1_H0, compress::sha1, 64, 20, "SHA-1 message digest");
+define_sha_hasher!(sha224, Sha224, u32, SHA224_H0, compress::sha256, 64, 28, "SHA-224 message digest");
+define_sha_hasher!(sha256, Sha256, u32, SHA256_H0, compress::sha256, 64, 32, "SHA-256 message digest");
+define_sha_hasher!(sha384, Sha384, u64, SHA384_H0, compress::sha512, 128, 48, "SHA-384 message digest");
+define_sha_hasher!(sha512, Sha512, u64, SHA512_H0, compress::sha512, 128, 64, "SHA-512 message digest");
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::sha1::Sha1;
+    use super::sha224::Sha224;
+    use super::sha256::Sha256;
+    use super::sha384::Sha384;
+    use super::sha512::Sha512;
+
+    /// 448-bit message from the FIPS 180-4 examples
+    const TWO_BLOCK_MESSAGE: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+    /// 896-bit message from the FIPS 180-4 examples
+    const LONG_MESSAGE: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+                                  hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    #[test]
+    fn sha1_empty_message() {
+        assert_eq!(Sha1::digest(b""), hex!("DA39A3EE 5E6B4B0D 3255BFEF 95601890 AFD80709"));
+    }
+
+    #[test]
+    fn sha1_one_block_message() {
+        assert_eq!(Sha1::digest(b"abc"), hex!("A9993E36 4706816A BA3E2571 7850C26C 9CD0D89D"));
+    }
+
+    #[test]
+    fn sha1_two_block_message() {
+        assert_eq!(
+            Sha1::digest(TWO_BLOCK_MESSAGE),
+            hex!("84983E44 1C3BD26E BAAE4AA1 F95129E5 E54670F1")
+        );
+    }
+
+    #[test]
+    fn sha1_long_message() {
+        assert_eq!(
+            Sha1::digest(LONG_MESSAGE),
+            hex!("A49B2446 A02C645B F419F995 B6709125 3A04A259")
+        );
+    }
+
+    #[test]
+    fn sha1_million_a() {
+        assert_eq!(
+            Sha1::digest(&b"a".repeat(1_000_000)),
+            hex!("34AA973C D4C4DAA4 F61EEB2B DBAD2731 6534016F")
+        );
+    }
+
+    #[test]
+    fn sha224_empty_message() {
+        assert_eq!(
+            Sha224::digest(b""),
+            hex!("D14A028C 2A3A2BC9 476102BB 288234C4 15A2B01F 828EA62A C5B3E42F")
+        );
+    }
+
+    #[test]
+    fn sha224_one_block_message() {
+        assert_eq!(
+            Sha224::digest(b"abc"),
+            hex!("23097D22 3405D822 8642A477 BDA255B3 2AADBCE4 BDA0B3F7 E36C9DA7")
+        );
+    }
+
+    #[test]
+    fn sha224_two_block_message() {
+        assert_eq!(
+            Sha224::digest(TWO_BLOCK_MESSAGE),
+            hex!("75388B16 512776CC 5DBA5DA1 FD890150 B0C6455C B4F58B19 52522525")
+        );
+    }
+
+    #[test]
+    fn sha256_empty_message() {
+        assert_eq!(
+            Sha256::digest(b""),
+            hex!("E3B0C442 98FC1C14 9AFBF4C8 996FB924 27AE41E4 649B934C A495991B 7852B855")
+        );
+    }
+
+    #[test]
+    fn sha256_one_block_message() {
+        assert_eq!(
+            Sha256::digest(b"abc"),
+            hex!("BA7816BF 8F01CFEA 414140DE 5DAE2223 B00361A3 96177A9C B410FF61 F20015AD")
+        );
+    }
+
+    #[test]
+    fn sha256_two_block_message() {
+        assert_eq!(
+            Sha256::digest(TWO_BLOCK_MESSAGE),
+            hex!("248D6A61 D20638B8 E5C02693 0C3E6039 A33CE459 64FF2167 F6ECEDD4 19DB06C1")
+        );
+    }
+
+    #[test]
+    fn sha256_million_a() {
+        assert_eq!(
+            Sha256::digest(&b"a".repeat(1_000_000)),
+            hex!("CDC76E5C 9914FB92 81A1C7E2 84D73E67 F1809A48 A497200E 046D39CC C7112CD0")
+        );
+    }
+
+    #[test]
+    fn sha384_empty_message() {
+        assert_eq!(
+            Sha384::digest(b""),
+            hex!("
+                38B060A7 51AC9638 4CD9327E B1B1E36A 21FDB711 14BE0743
+                4C0CC7BF 63F6E1DA 274EDEBF E76F65FB D51AD2F1 4898B95B
+            ")
+        );
+    }
+
+    #[test]
+    fn sha384_one_block_message() {
+        assert_eq!(
+            Sha384::digest(b"abc"),
+            hex!("
+                CB00753F 45A35E8B B5A03D69 9AC65007 272C32AB 0EDED163
+                1A8B605A 43FF5BED 8086072B A1E7CC23 58BAECA1 34C825A7
+            ")
+        );
+    }
+
+    #[test]
+    fn sha384_two_block_message() {
+        assert_eq!(
+            Sha384::digest(LONG_MESSAGE),
+            hex!("
+                09330C33 F71147E8 3D192FC7 82CD1B47 53111B17 3B3B05D2
+                2FA08086 E3B0F712 FCC7C71A 557E2DB9 66C3E9FA 91746039
+            ")
+        );
+    }
+
+    #[test]
+    fn sha512_empty_message() {
+        assert_eq!(
+            Sha512::digest(b""),
+            hex!("
+                CF83E135 7EEFB8BD F1542850 D66D8007 D620E405 0B5715DC 83F4A921 D36CE9CE
+                47D0D13C 5D85F2B0 FF8318D2 877EEC2F 63B931BD 47417A81 A538327A F927DA3E
+            ")
+        );
+    }
+
+    #[test]
+    fn sha512_one_block_message() {
+        assert_eq!(
+            Sha512::digest(b"abc"),
+            hex!("
+                DDAF35A1 93617ABA CC417349 AE204131 12E6FA4E 89A97EA2 0A9EEEE6 4B55D39A
+                2192992A 274FC1A8 36BA3C23 A3FEEBBD 454D4423 643CE80E 2A9AC94F A54CA49F
+            ")
+        );
+    }
+
+    #[test]
+    fn sha512_two_block_message() {
+        assert_eq!(
+            Sha512::digest(LONG_MESSAGE),
+            hex!("
+                8E959B75 DAE313DA 8CF4F728 14FC143F 8F7779C6 EB9F7FA1 7299AEAD B6889018
+                501D289E 4900F7E4 331B99DE C4B5433A C7D329EE B6DD2654 5E96E55B 874BE909
+            ")
+        );
+    }
+
+    #[test]
+    fn incremental_updates() {
+        let data = b"A".repeat(1000);
+
+        let mut sha256 = Sha256::new();
+        let mut sha512 = Sha512::new();
+
+        for chunk in data.chunks(7) {
+            sha256.update(chunk);
+            sha512.update(chunk);
+        }
+
+        assert_eq!(sha256.finalize(), Sha256::digest(&data));
+        assert_eq!(sha512.finalize(), Sha512::digest(&data));
+    }
+}
@@ -4,3 +4,6 @@ pub mod mode_of_operations;
 pub mod openpgp_cfb;
 
 pub mod aes;
+
+pub mod hash;
+pub mod s2k;
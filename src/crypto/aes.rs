@@ -1,16 +1,52 @@
 extern crate aes;
 
+use std::convert::TryInto;
+
 use aes::aes128 as aes128_backend;
+use aes::aes192 as aes192_backend;
+use aes::aes256 as aes256_backend;
 
 use super::openpgp_cfb::OpenPgpCfbAes128;
 use super::symmetric_cipher::{SymmetricEncryption, SymmetricDecryption};
 use super::symmetric_cipher::SymmetricKey;
 use super::symmetric_cipher::BlockCipher;
+use super::symmetric_cipher::BlockCipherBackend;
 use super::symmetric_cipher::{PlainTextOut, CipherTextOut};
 use super::mode_of_operations::Mode;
 
 use crate::Error;
 
+macro_rules! impl_block_cipher_backend {
+    ($backend:ty, $name:expr, $key_size:expr) => {
+        impl BlockCipherBackend for $backend {
+            const NAME: &'static str = $name;
+            const BLOCK_SIZE: usize = 16;
+            const KEY_SIZE: usize = $key_size;
+
+            type Block = [u8; 16];
+
+            fn from_key(key: &[u8]) -> Result<Self, Error> {
+                let cipher_key = key.try_into().map_err(|_| format!(
+                    "{} requires a {}-bit key, but the key is {} bits long.",
+                    Self::NAME,
+                    Self::KEY_SIZE * 8,
+                    key.len() * 8,
+                ))?;
+
+                Ok(<$backend>::with_key(cipher_key))
+            }
+
+            fn encrypt_block(&self, block: &Self::Block) -> Self::Block {
+                <$backend>::encrypt_block(self, *block)
+            }
+        }
+    }
+}
+
+impl_block_cipher_backend!(aes128_backend::Aes128, "AES-128", 16);
+impl_block_cipher_backend!(aes192_backend::Aes192, "AES-192", 24);
+impl_block_cipher_backend!(aes256_backend::Aes256, "AES-256", 32);
+
 pub struct Aes128 {
     mode: Option<Mode>,
     key: Option<aes128_backend::CipherKey>,
@@ -52,7 +88,7 @@ impl SymmetricEncryption for Aes128 {
         }
 
         match self.mode.unwrap() {
-            Mode::OpenPgpCfb => OpenPgpCfbAes128::encrypt(plaintext, &self.key.unwrap()),
+            Mode::OpenPgpCfb => OpenPgpCfbAes128::with_key(&self.key.unwrap())?.encrypt(plaintext),
         }
     }
 }
@@ -68,7 +104,7 @@ impl SymmetricDecryption for Aes128 {
         }
 
         match self.mode.unwrap() {
-            Mode::OpenPgpCfb => OpenPgpCfbAes128::decrypt(ciphertext, &self.key.unwrap()),
+            Mode::OpenPgpCfb => OpenPgpCfbAes128::with_key(&self.key.unwrap())?.decrypt(ciphertext),
         }
     }
 }
@@ -98,4 +134,24 @@ mod tests {
 
         assert_eq!(decrypted, plaintext);
     }
+
+    #[test]
+    fn backend_rejects_wrong_key_length() {
+        let error = aes256_backend::Aes256::from_key(&[0; 16]).err().unwrap();
+
+        assert_eq!(error.to_string(), "AES-256 requires a 256-bit key, but the key is 128 bits long.");
+    }
+
+    #[test]
+    fn backend_encrypts_like_aes() {
+        let key = [0x42; 24];
+        let block = [0x17; 16];
+
+        let backend = aes192_backend::Aes192::from_key(&key).unwrap();
+
+        assert_eq!(
+            BlockCipherBackend::encrypt_block(&backend, &block),
+            aes192_backend::Aes192::with_key(key).encrypt_block(block)
+        );
+    }
 }
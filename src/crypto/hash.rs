@@ -0,0 +1,136 @@
+use sha::sha1::Sha1;
+use sha::sha224::Sha224;
+use sha::sha256::Sha256;
+use sha::sha384::Sha384;
+use sha::sha512::Sha512;
+
+use crate::Error;
+
+/// Incremental message digest
+pub trait Hasher {
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self: Box<Self>) -> Vec<u8>;
+}
+
+macro_rules! impl_hasher {
+    ($hasher:ident) => {
+        impl Hasher for $hasher {
+            fn update(&mut self, data: &[u8]) {
+                $hasher::update(self, data)
+            }
+
+            fn finalize(self: Box<Self>) -> Vec<u8> {
+                $hasher::finalize(*self).to_vec()
+            }
+        }
+    }
+}
+
+impl_hasher!(Sha1);
+impl_hasher!(Sha224);
+impl_hasher!(Sha256);
+impl_hasher!(Sha384);
+impl_hasher!(Sha512);
+
+/// OpenPGP hash algorithms
+///
+/// # Links
+/// - [RFC 4880, Section 9.4](https://tools.ietf.org/html/rfc4880#section-9.4)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Ripemd160,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha224,
+}
+
+impl HashAlgorithm {
+    /// Get the hash algorithm from its OpenPGP algorithm ID
+    pub fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            1 => Ok(Self::Md5),
+            2 => Ok(Self::Sha1),
+            3 => Ok(Self::Ripemd160),
+            8 => Ok(Self::Sha256),
+            9 => Ok(Self::Sha384),
+            10 => Ok(Self::Sha512),
+            11 => Ok(Self::Sha224),
+            x => Err(format!("Unknown hash algorithm ID `{}`.", x).into()),
+        }
+    }
+
+    /// Get the OpenPGP algorithm ID
+    pub fn to_id(&self) -> u8 {
+        match self {
+            Self::Md5 => 1,
+            Self::Sha1 => 2,
+            Self::Ripemd160 => 3,
+            Self::Sha256 => 8,
+            Self::Sha384 => 9,
+            Self::Sha512 => 10,
+            Self::Sha224 => 11,
+        }
+    }
+
+    /// Create a hasher for the algorithm
+    pub fn new_hasher(&self) -> Result<Box<dyn Hasher>, Error> {
+        match self {
+            Self::Sha1 => Ok(Box::new(Sha1::new())),
+            Self::Sha224 => Ok(Box::new(Sha224::new())),
+            Self::Sha256 => Ok(Box::new(Sha256::new())),
+            Self::Sha384 => Ok(Box::new(Sha384::new())),
+            Self::Sha512 => Ok(Box::new(Sha512::new())),
+            x => Err(format!("Hash algorithm {} is not supported.", x.to_str()).into()),
+        }
+    }
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Ripemd160 => "RIPEMD-160",
+            Self::Sha256 => "SHA-256",
+            Self::Sha384 => "SHA-384",
+            Self::Sha512 => "SHA-512",
+            Self::Sha224 => "SHA-224",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::HashAlgorithm;
+
+    #[test]
+    fn id_to_enum_for_sha256() {
+        assert_eq!(HashAlgorithm::from_id(8).unwrap(), HashAlgorithm::Sha256);
+    }
+
+    #[test]
+    fn id_to_enum_fails_for_unknown_id() {
+        assert!(HashAlgorithm::from_id(4).is_err());
+    }
+
+    #[test]
+    fn unsupported_hasher() {
+        assert!(HashAlgorithm::Md5.new_hasher().is_err());
+    }
+
+    #[test]
+    fn sha1_hasher() {
+        let mut hasher = HashAlgorithm::Sha1.new_hasher().unwrap();
+        hasher.update(b"abc");
+
+        assert_eq!(hasher.finalize(), hex!("A9993E36 4706816A BA3E2571 7850C26C 9CD0D89D"));
+    }
+
+    #[test]
+    fn enum_to_id_for_sha1() {
+        assert_eq!(HashAlgorithm::Sha1.to_id(), 2);
+    }
+}
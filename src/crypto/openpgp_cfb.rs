@@ -1,36 +1,74 @@
-use std::convert::TryInto;
+use std::io;
+use std::io::{Read, Write};
 
 use rand::Rng;
 use aes::aes128 as aes128_backend;
+use aes::aes192 as aes192_backend;
+use aes::aes256 as aes256_backend;
 
+use super::symmetric_cipher::BlockCipherBackend;
 use super::symmetric_cipher::CipherTextOut;
 use super::symmetric_cipher::PlainTextOut;
 
 use crate::Error;
 
-type Block = [u8; OpenPgpCfbAes128::BS];
+pub type OpenPgpCfbAes128 = OpenPgpCfb<aes128_backend::Aes128>;
+pub type OpenPgpCfbAes192 = OpenPgpCfb<aes192_backend::Aes192>;
+pub type OpenPgpCfbAes256 = OpenPgpCfb<aes256_backend::Aes256>;
+
+/// The quick check of a decrypted random prefix failed
+///
+/// The last two octets of the prefix do not repeat the two before them, so
+/// the session key, and with it the key or passphrase, is wrong. A correct
+/// key always passes; a wrong key passes by chance once in 65536 times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WrongKeyError;
+
+impl std::fmt::Display for WrongKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Wrong key or passphrase.")
+    }
+}
+
+impl std::error::Error for WrongKeyError {}
+
+/// OpenPGP CFB mode over any block cipher backend
+///
+/// Holds the cipher with its key schedule, so the key is only expanded once
+/// per message.
+///
+/// # Links
+/// - [RFC 4880, Section 13.9](https://tools.ietf.org/html/rfc4880#section-13.9)
+pub struct OpenPgpCfb<C> {
+    cipher: C,
+}
+
+impl<C: BlockCipherBackend> OpenPgpCfb<C> {
+    const BS: usize = C::BLOCK_SIZE;
 
-pub struct OpenPgpCfbAes128;
+    /// The length of the random prefix, one block plus two repeated octets
+    pub const PREFIX_LENGTH: usize = Self::BS + 2;
 
-impl OpenPgpCfbAes128 {
-    const BS: usize = 16;
-    const PREFIX_LENGTH: usize = Self::BS + 2;
+    /// Expand the key, failing if it does not fit the cipher
+    pub fn with_key(key: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            cipher: C::from_key(key)?,
+        })
+    }
 
     #[allow(non_snake_case)]
-    pub fn encrypt(plaintext: &[u8], key: &[u8]) -> Result<CipherTextOut, Error> {
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<CipherTextOut, Error> {
         let mut C: Vec<u8> = Vec::with_capacity(Self::PREFIX_LENGTH + plaintext.len());
 
         let prefix = generate_random_prefix(Self::PREFIX_LENGTH);
 
-        let IV: [u8; Self::BS] = [0; Self::BS];
-        let mut FR = IV;
-        let mut FRE = Self::encrypt_block(&FR, key)?;
-        C.append(&mut xor_block(&FRE, &prefix[0..Self::BS]));
+        let IV = C::Block::default();
+        let mut FRE = self.cipher.encrypt_block(&IV);
+        C.extend(xor_block(FRE.as_ref(), &prefix[0..Self::BS]));
 
-        FR = C[0..Self::BS].try_into()?;
-        FRE = Self::encrypt_block(&FR, key)?;
-        C.push(FRE[0] ^ prefix[Self::BS + 0]);
-        C.push(FRE[1] ^ prefix[Self::BS + 1]);
+        FRE = self.encrypt_block(&C[0..Self::BS]);
+        C.push(FRE.as_ref()[0] ^ prefix[Self::BS + 0]);
+        C.push(FRE.as_ref()[1] ^ prefix[Self::BS + 1]);
 
         if plaintext.len() == 0 {
             // NOTE: Could also return error. In that case, return early at fn start
@@ -41,37 +79,124 @@ impl OpenPgpCfbAes128 {
         let mut plaintext_blocks = plaintext.chunks(Self::BS);
 
         // The resync step
-        FR = C[2..prefix.len()].try_into()?;
-        FRE = Self::encrypt_block(&FR, key)?;
-        C.append(&mut xor_block(&FRE, &plaintext_blocks.next().unwrap()));
+        FRE = self.encrypt_block(&C[2..prefix.len()]);
+        C.extend(xor_block(FRE.as_ref(), &plaintext_blocks.next().unwrap()));
 
         while let Some(plaintext_block) = plaintext_blocks.next() {
-            {
-                let current = C.len();
-                let total = C.capacity();
-                let percentage = current as f32 / total as f32 * 100.0;
+            let range = (C.len() - Self::BS)..C.len();
+            FRE = self.encrypt_block(&C[range]);
+            C.extend(xor_block(FRE.as_ref(), &plaintext_block));
+        }
 
-                println!("{:.1}% encrypted ({}/{} KB).", percentage, current / 1000, total / 1000);
-            }
+        Ok(C)
+    }
 
-            let range = (C.len() - Self::BS)..C.len();
-            FR = C[range].try_into()?;
-            FRE = Self::encrypt_block(&FR, key)?;
-            C.append(&mut xor_block(&FRE, &plaintext_block));
+    /// Encrypt without the resync step, as used for integrity protected data
+    ///
+    /// This is plain CFB mode with an all-zero IV. Unlike `encrypt`, no
+    /// prefix is added; the caller includes it in the plaintext, since the
+    /// modification detection code covers it.
+    ///
+    /// # Links
+    /// - [RFC 4880, Section 5.13](https://tools.ietf.org/html/rfc4880#section-5.13)
+    #[allow(non_snake_case)]
+    pub fn encrypt_without_resync(&self, plaintext: &[u8]) -> Result<CipherTextOut, Error> {
+        let mut C: Vec<u8> = Vec::with_capacity(plaintext.len());
+        let mut FR = C::Block::default();
+
+        for plaintext_block in plaintext.chunks(Self::BS) {
+            let FRE = self.cipher.encrypt_block(&FR);
+            let start = C.len();
+            C.extend(xor_block(FRE.as_ref(), plaintext_block));
+
+            FR.as_mut()[..plaintext_block.len()].copy_from_slice(&C[start..]);
         }
 
         Ok(C)
     }
 
-    fn encrypt_block(plaintext_block: &[u8], key: &[u8]) -> Result<Block, Error> {
-        let key = key.try_into()?;
-        let plaintext = plaintext_block.try_into()?;
+    /// Decrypt data encrypted with `encrypt_without_resync`
+    ///
+    /// The prefix is returned as part of the plaintext.
+    #[allow(non_snake_case)]
+    pub fn decrypt_without_resync(&self, ciphertext: &[u8]) -> Result<PlainTextOut, Error> {
+        let mut P: Vec<u8> = Vec::with_capacity(ciphertext.len());
+        let mut FR = C::Block::default();
+
+        for ciphertext_block in ciphertext.chunks(Self::BS) {
+            let FRE = self.cipher.encrypt_block(&FR);
+            P.extend(xor_block(FRE.as_ref(), ciphertext_block));
 
-        Ok(aes128_backend::Aes128::with_key(key).encrypt_block(plaintext))
+            FR.as_mut()[..ciphertext_block.len()].copy_from_slice(ciphertext_block);
+        }
+
+        Ok(P)
+    }
+
+    /// Generate the random prefix for encrypting without the resync step
+    ///
+    /// One block of random octets, followed by a repetition of the last two.
+    pub fn random_prefix() -> Vec<u8> {
+        generate_random_prefix(Self::PREFIX_LENGTH)
+    }
+
+    /// Check the quick check octets at the end of a decrypted random prefix
+    ///
+    /// Fails with `WrongKeyError` when they do not repeat the two octets
+    /// before them.
+    ///
+    /// # Links
+    /// - [RFC 4880, Section 5.7](https://tools.ietf.org/html/rfc4880#section-5.7)
+    pub fn check_prefix(prefix: &[u8]) -> Result<(), Error> {
+        if prefix.len() != Self::PREFIX_LENGTH {
+            return Err(format!(
+                "Random prefix must be {} octets, found {}.",
+                Self::PREFIX_LENGTH,
+                prefix.len()
+            ).into())
+        }
+
+        if prefix[Self::BS - 2..Self::BS] != prefix[Self::BS..] {
+            return Err(Box::new(WrongKeyError))
+        }
+
+        Ok(())
+    }
+
+    /// Decrypt only the random prefix of data encrypted with `encrypt`
+    ///
+    /// Allows checking the key with `check_prefix` before decrypting the
+    /// rest.
+    pub fn decrypt_prefix(&self, ciphertext: &[u8]) -> Result<PlainTextOut, Error> {
+        if ciphertext.len() < Self::PREFIX_LENGTH {
+            return Err("Ciphertext is shorter than the random prefix.".into())
+        }
+
+        let iv = C::Block::default();
+        let fre = self.cipher.encrypt_block(&iv);
+        let mut prefix: Vec<u8> = xor_block(fre.as_ref(), &ciphertext[..Self::BS]).collect();
+
+        // The last two octets are encrypted before the resync step
+        let fre = self.encrypt_block(&ciphertext[..Self::BS]);
+        prefix.extend(xor_block(fre.as_ref(), &ciphertext[Self::BS..Self::PREFIX_LENGTH]));
+
+        Ok(prefix)
+    }
+
+    /// Encrypt a whole block of ciphertext taken from a slice
+    fn encrypt_block(&self, plaintext_block: &[u8]) -> C::Block {
+        let mut block = C::Block::default();
+        block.as_mut().copy_from_slice(plaintext_block);
+
+        self.cipher.encrypt_block(&block)
     }
 
     #[allow(non_snake_case)]
-    pub fn decrypt(ciphertext: &[u8], key: &[u8]) -> Result<PlainTextOut, Error> {
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<PlainTextOut, Error> {
+        if ciphertext.len() < Self::PREFIX_LENGTH {
+            return Err("Ciphertext is shorter than the random prefix.".into())
+        }
+
         let offset = 2;
 
         let mut ciphertext_blocks = ciphertext[offset..]
@@ -81,27 +206,18 @@ impl OpenPgpCfbAes128 {
 
         let mut decrypted: Vec<u8> = Vec::with_capacity(ciphertext.len());
 
-        let IV: [u8; Self::BS] = [0; Self::BS];
-        let FRE = Self::encrypt_block(&IV, key)?;
+        let IV = C::Block::default();
+        let FRE = self.cipher.encrypt_block(&IV);
 
         if let Some(ciphertext_block) = ciphertext_blocks.peek() {
-            decrypted.append(&mut xor_block(&FRE, ciphertext_block));
+            decrypted.extend(xor_block(FRE.as_ref(), ciphertext_block));
         }
 
         while let Some(FR) = ciphertext_blocks.next() {
-            {
-                let current = decrypted.len();
-                let total = decrypted.capacity();
-                let percentage = current as f32 / total as f32 * 100.0;
-
-                println!("{:.1}% decrypted ({}/{} KB).", percentage, current / 1000, total / 1000);
-            }
-
             if let Some(ciphertext_block) = ciphertext_blocks.peek() {
-                let FRE = Self::encrypt_block(&FR, key)?;
+                let FRE = self.encrypt_block(&FR);
 
-                let plaintext_block = &mut xor_block(&FRE, ciphertext_block);
-                decrypted.append(plaintext_block);
+                decrypted.extend(xor_block(FRE.as_ref(), ciphertext_block));
             }
         }
 
@@ -111,12 +227,170 @@ impl OpenPgpCfbAes128 {
     }
 }
 
-fn xor_block(input1: &[u8], input2: &[u8]) -> Vec<u8> {
+/// CFB state without the resync step, carried across stream chunks
+///
+/// Each keystream block is the encryption of the previous ciphertext block,
+/// so data can be processed one octet at a time.
+struct CfbStream<C: BlockCipherBackend> {
+    cipher: C,
+    feedback: C::Block,
+    keystream: C::Block,
+    position: usize,
+}
+
+impl<C: BlockCipherBackend> CfbStream<C> {
+    fn new(key: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            cipher: C::from_key(key)?,
+            feedback: C::Block::default(),
+            keystream: C::Block::default(),
+            position: 0,
+        })
+    }
+
+    fn apply(&mut self, data: &mut [u8], encrypting: bool) {
+        for octet in data.iter_mut() {
+            if self.position == 0 {
+                self.keystream = self.cipher.encrypt_block(&self.feedback);
+            }
+
+            let input = *octet;
+            *octet ^= self.keystream.as_ref()[self.position];
+
+            self.feedback.as_mut()[self.position] = if encrypting { *octet } else { input };
+            self.position = (self.position + 1) % C::BLOCK_SIZE;
+        }
+    }
+
+    /// Start a new block with the last block of ciphertext as feedback,
+    /// even in the middle of a block
+    ///
+    /// The feedback holds the octets of the current block before those
+    /// left over from the previous one, so rotating puts them in order.
+    fn resync(&mut self) {
+        self.feedback.as_mut().rotate_left(self.position);
+        self.position = 0;
+    }
+}
+
+/// Encrypts everything written to it without the resync step, and writes
+/// the ciphertext to the underlying writer
+///
+/// Equivalent to `OpenPgpCfb::encrypt_without_resync`, but in bounded
+/// memory. The random prefix must be written first.
+pub struct OpenPgpCfbEncryptor<W: Write, C: BlockCipherBackend> {
+    output: W,
+    cfb: CfbStream<C>,
+}
+
+impl<W: Write, C: BlockCipherBackend> OpenPgpCfbEncryptor<W, C> {
+    pub fn new(output: W, key: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            output,
+            cfb: CfbStream::new(key)?,
+        })
+    }
+
+    /// Flush and return the underlying writer
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.output.flush()?;
+
+        Ok(self.output)
+    }
+}
+
+impl<W: Write, C: BlockCipherBackend> Write for OpenPgpCfbEncryptor<W, C> {
+    fn write(&mut self, plaintext: &[u8]) -> io::Result<usize> {
+        let mut ciphertext = plaintext.to_vec();
+        self.cfb.apply(&mut ciphertext, true);
+        self.output.write_all(&ciphertext)?;
+
+        Ok(plaintext.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// Decrypts data read from the underlying reader without the resync step
+///
+/// Equivalent to `OpenPgpCfb::decrypt_without_resync`, but in bounded
+/// memory. The random prefix is part of the decrypted data.
+pub struct OpenPgpCfbDecryptor<R: Read, C: BlockCipherBackend> {
+    input: R,
+    cfb: CfbStream<C>,
+}
+
+impl<R: Read, C: BlockCipherBackend> OpenPgpCfbDecryptor<R, C> {
+    pub fn new(input: R, key: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            input,
+            cfb: CfbStream::new(key)?,
+        })
+    }
+}
+
+impl<R: Read, C: BlockCipherBackend> Read for OpenPgpCfbDecryptor<R, C> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let n = self.input.read(buffer)?;
+        self.cfb.apply(&mut buffer[..n], false);
+
+        Ok(n)
+    }
+}
+
+/// Decrypts data read from the underlying reader with the resync step, as
+/// used for data without integrity protection
+///
+/// Equivalent to `OpenPgpCfb::decrypt`, but in bounded memory. Unlike it,
+/// the random prefix is part of the decrypted data, so that it can be
+/// checked with `OpenPgpCfb::check_prefix`.
+///
+/// # Links
+/// - [RFC 4880, Section 13.9](https://tools.ietf.org/html/rfc4880#section-13.9)
+pub struct OpenPgpCfbResyncDecryptor<R: Read, C: BlockCipherBackend> {
+    input: R,
+    cfb: CfbStream<C>,
+
+    /// Octets of the random prefix left to decrypt before the resync step
+    prefix_remaining: usize,
+}
+
+impl<R: Read, C: BlockCipherBackend> OpenPgpCfbResyncDecryptor<R, C> {
+    pub fn new(input: R, key: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            input,
+            cfb: CfbStream::new(key)?,
+            prefix_remaining: OpenPgpCfb::<C>::PREFIX_LENGTH,
+        })
+    }
+}
+
+impl<R: Read, C: BlockCipherBackend> Read for OpenPgpCfbResyncDecryptor<R, C> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let n = self.input.read(buffer)?;
+        let prefix_length = n.min(self.prefix_remaining);
+
+        self.cfb.apply(&mut buffer[..prefix_length], false);
+        self.prefix_remaining -= prefix_length;
+
+        // The resync step follows the two quick check octets
+        if prefix_length > 0 && self.prefix_remaining == 0 {
+            self.cfb.resync();
+        }
+
+        self.cfb.apply(&mut buffer[prefix_length..n], false);
+
+        Ok(n)
+    }
+}
+
+fn xor_block<'a>(input1: &'a [u8], input2: &'a [u8]) -> impl Iterator<Item = u8> + 'a {
     input1
         .iter()
         .zip(input2)
         .map(|(i, j)| i ^ j)
-        .collect()
 }
 
 // More efficient in-place XOR
@@ -138,6 +412,8 @@ fn generate_random_prefix(length: usize) -> Vec<u8> {
 
 #[cfg(test)]
 mod tests {
+    use hex_literal::hex;
+
     use super::*;
 
     #[test]
@@ -146,8 +422,10 @@ mod tests {
         let plaintext = b"";
         assert_eq!(plaintext.len(), 0);
 
-        let ciphertext = OpenPgpCfbAes128::encrypt(plaintext, &key).expect("Failed to encrypt.");
-        let decrypted_text = OpenPgpCfbAes128::decrypt(&ciphertext, &key).expect("Failed to decrypt.");
+        let cfb = OpenPgpCfbAes128::with_key(&key).unwrap();
+
+        let ciphertext = cfb.encrypt(plaintext).expect("Failed to encrypt.");
+        let decrypted_text = cfb.decrypt(&ciphertext).expect("Failed to decrypt.");
 
         assert_eq!(decrypted_text.to_vec(), plaintext.to_vec());
     }
@@ -158,8 +436,10 @@ mod tests {
         let plaintext = b"This secret message uses exactly three blocks...";
         assert_eq!(plaintext.len(), 3 * 16);
 
-        let ciphertext = OpenPgpCfbAes128::encrypt(plaintext, &key).expect("Failed to encrypt.");
-        let decrypted_text = OpenPgpCfbAes128::decrypt(&ciphertext, &key).expect("Failed to decrypt.");
+        let cfb = OpenPgpCfbAes128::with_key(&key).unwrap();
+
+        let ciphertext = cfb.encrypt(plaintext).expect("Failed to encrypt.");
+        let decrypted_text = cfb.decrypt(&ciphertext).expect("Failed to decrypt.");
 
         assert_eq!(decrypted_text.to_vec(), plaintext.to_vec());
     }
@@ -170,9 +450,176 @@ mod tests {
         let plaintext = b"This secret message uses less than 3 blocks.";
         assert_ne!(plaintext.len(), 3 * 16);
 
-        let ciphertext = OpenPgpCfbAes128::encrypt(plaintext, &key).expect("Failed to encrypt.");
-        let decrypted_text = OpenPgpCfbAes128::decrypt(&ciphertext, &key).expect("Failed to decrypt.");
+        let cfb = OpenPgpCfbAes128::with_key(&key).unwrap();
+
+        let ciphertext = cfb.encrypt(plaintext).expect("Failed to encrypt.");
+        let decrypted_text = cfb.decrypt(&ciphertext).expect("Failed to decrypt.");
 
         assert_eq!(decrypted_text.to_vec(), plaintext.to_vec());
     }
+
+    #[test]
+    fn encrypt_three_blocks_aes256() {
+        let key = [0x42; 32];
+        let plaintext = b"This secret message uses less than 3 blocks.";
+
+        let cfb = OpenPgpCfbAes256::with_key(&key).unwrap();
+
+        let ciphertext = cfb.encrypt(plaintext).expect("Failed to encrypt.");
+        let decrypted_text = cfb.decrypt(&ciphertext).expect("Failed to decrypt.");
+
+        assert_eq!(decrypted_text, plaintext.to_vec());
+    }
+
+    #[test]
+    fn with_key_fails_for_wrong_key_length() {
+        let error = OpenPgpCfbAes192::with_key(&[0; 16]).err().unwrap();
+
+        assert_eq!(error.to_string(), "AES-192 requires a 192-bit key, but the key is 128 bits long.");
+    }
+
+    #[test]
+    fn decrypt_fails_for_short_ciphertext() {
+        let cfb = OpenPgpCfbAes128::with_key(&[0; 16]).unwrap();
+
+        assert!(cfb.decrypt(&[0; 17]).is_err());
+    }
+
+    #[test]
+    fn encrypt_without_resync_round_trip() {
+        let key = (0x112233445566778899AABBCCDDEEFF_u128).to_be_bytes();
+        let plaintext = b"This secret message uses less than 3 blocks.";
+
+        let cfb = OpenPgpCfbAes128::with_key(&key).unwrap();
+
+        let ciphertext = cfb.encrypt_without_resync(plaintext).unwrap();
+        assert_eq!(ciphertext.len(), plaintext.len());
+
+        let decrypted_text = cfb.decrypt_without_resync(&ciphertext).unwrap();
+        assert_eq!(decrypted_text, plaintext.to_vec());
+    }
+
+    #[test]
+    fn encrypt_without_resync_is_plain_cfb() {
+        let key = hex!("2B7E1516 28AED2A6 ABF71588 09CF4F3C");
+        let plaintext = hex!("
+            6BC1BEE2 2E409F96 E93D7E11 7393172A
+            AE2D8A57 1E03AC9C 9EB76FAC 45AF8E51
+            30C81C46 A35CE411
+        ");
+
+        let cfb = OpenPgpCfbAes128::with_key(&key).unwrap();
+
+        let ciphertext = cfb.encrypt_without_resync(&plaintext).unwrap();
+
+        assert_eq!(ciphertext, hex!("
+            1636D5EE 34F80625 D77F8E56 CA884345
+            86360140 D4086CFD 51DE95EA 001F81DC
+            E3600D8B C70A66D4
+        "));
+    }
+
+    #[test]
+    fn encryptor_matches_encrypt_without_resync() {
+        let key = [0x42; 24];
+        let plaintext: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+
+        let mut encryptor = OpenPgpCfbEncryptor::<_, aes192_backend::Aes192>::new(Vec::new(), &key).unwrap();
+
+        // Chunks that do not line up with the block size
+        for chunk in plaintext.chunks(7) {
+            encryptor.write_all(chunk).unwrap();
+        }
+
+        let ciphertext = encryptor.finish().unwrap();
+        let cfb = OpenPgpCfbAes192::with_key(&key).unwrap();
+
+        assert_eq!(ciphertext, cfb.encrypt_without_resync(&plaintext).unwrap());
+    }
+
+    #[test]
+    fn decryptor_matches_decrypt_without_resync() {
+        let key = [0x42; 16];
+        let plaintext: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let ciphertext = OpenPgpCfbAes128::with_key(&key).unwrap().encrypt_without_resync(&plaintext).unwrap();
+
+        let mut decryptor = OpenPgpCfbDecryptor::<_, aes128_backend::Aes128>::new(&ciphertext[..], &key).unwrap();
+        let mut decrypted: Vec<u8> = vec![];
+        let mut chunk = [0; 13];
+
+        loop {
+            match decryptor.read(&mut chunk).unwrap() {
+                0 => break,
+                n => decrypted.extend(&chunk[..n]),
+            }
+        }
+
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn resync_decryptor_matches_decrypt() {
+        let key = [0x42; 16];
+        let cfb = OpenPgpCfbAes128::with_key(&key).unwrap();
+
+        for length in [0, 1, 15, 16, 17, 1000].iter() {
+            let plaintext: Vec<u8> = (0..*length).map(|i| i as u8).collect();
+            let ciphertext = cfb.encrypt(&plaintext).unwrap();
+
+            for chunk_size in [1, 13, 18, 4096].iter() {
+                let mut decryptor = OpenPgpCfbResyncDecryptor::<_, aes128_backend::Aes128>::new(&ciphertext[..], &key).unwrap();
+                let mut decrypted: Vec<u8> = vec![];
+                let mut chunk = vec![0; *chunk_size];
+
+                loop {
+                    match decryptor.read(&mut chunk).unwrap() {
+                        0 => break,
+                        n => decrypted.extend(&chunk[..n]),
+                    }
+                }
+
+                let (prefix, data) = decrypted.split_at(OpenPgpCfbAes128::PREFIX_LENGTH);
+
+                assert_eq!(prefix, &cfb.decrypt_prefix(&ciphertext).unwrap()[..]);
+                assert!(OpenPgpCfbAes128::check_prefix(prefix).is_ok());
+                assert_eq!(data, &plaintext[..], "length {}, chunks of {}", length, chunk_size);
+            }
+        }
+    }
+
+    #[test]
+    fn decrypted_prefix_passes_quick_check() {
+        let key = [0x42; 16];
+        let cfb = OpenPgpCfbAes128::with_key(&key).unwrap();
+        let ciphertext = cfb.encrypt(b"Hello world!").unwrap();
+
+        let prefix = cfb.decrypt_prefix(&ciphertext).unwrap();
+
+        assert_eq!(prefix.len(), OpenPgpCfbAes128::PREFIX_LENGTH);
+        assert!(OpenPgpCfbAes128::check_prefix(&prefix).is_ok());
+    }
+
+    #[test]
+    fn quick_check_fails_for_wrong_key() {
+        // A fixed prefix, since a random one passes by chance once in 65536
+        // times. Before the resync step, both variants encrypt alike.
+        let prefix = hex!("00010203 04050607 08090A0B 0C0D0E0F 0E0F");
+        let ciphertext = OpenPgpCfbAes128::with_key(&[0x42; 16]).unwrap().encrypt_without_resync(&prefix).unwrap();
+        let cfb = OpenPgpCfbAes128::with_key(&[0x43; 16]).unwrap();
+
+        assert_eq!(OpenPgpCfbAes128::with_key(&[0x42; 16]).unwrap().decrypt_prefix(&ciphertext).unwrap(), prefix);
+
+        let prefix = cfb.decrypt_prefix(&ciphertext).unwrap();
+        let error = OpenPgpCfbAes128::check_prefix(&prefix).unwrap_err();
+
+        assert_eq!(error.downcast_ref::<WrongKeyError>(), Some(&WrongKeyError));
+    }
+
+    #[test]
+    fn random_prefix_repeats_last_two_octets() {
+        let prefix = OpenPgpCfbAes128::random_prefix();
+
+        assert_eq!(prefix.len(), 18);
+        assert_eq!(prefix[14..16], prefix[16..18]);
+    }
 }
@@ -0,0 +1,284 @@
+use std::convert::TryInto;
+
+use rand::Rng;
+
+use super::hash::HashAlgorithm;
+
+use crate::Error;
+
+pub type Salt = [u8; 8];
+
+/// String-to-key (S2K) specifier
+///
+/// Describes how a passphrase is converted into a cipher key.
+///
+/// # Links
+/// - [RFC 4880, Section 3.7](https://tools.ietf.org/html/rfc4880#section-3.7)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StringToKey {
+    /// Directly hashes the passphrase.
+    Simple {
+        hash: HashAlgorithm,
+    },
+
+    /// Hashes the salt followed by the passphrase.
+    Salted {
+        hash: HashAlgorithm,
+        salt: Salt,
+    },
+
+    /// Hashes the salt and passphrase repeatedly, until the coded count of
+    /// octets has been hashed.
+    IteratedAndSalted {
+        hash: HashAlgorithm,
+        salt: Salt,
+        count: u8,
+    },
+}
+
+impl StringToKey {
+    /// The coded count used for new iterated and salted specifiers
+    ///
+    /// Decodes to 4,194,304 octets hashed per derived key.
+    pub const DEFAULT_COUNT: u8 = 0xC0;
+
+    /// Create an iterated and salted specifier with a random salt
+    pub fn new_iterated_and_salted(hash: HashAlgorithm, count: u8) -> Self {
+        Self::IteratedAndSalted {
+            hash,
+            salt: rand::thread_rng().gen(),
+            count,
+        }
+    }
+
+    /// Decode the coded count of an iterated and salted specifier to the
+    /// amount of octets to hash
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rpg::crypto::s2k::StringToKey;
+    ///
+    /// assert_eq!(StringToKey::decode_count(0x60), 65536);
+    /// assert_eq!(StringToKey::decode_count(0xFF), 65011712);
+    /// ```
+    pub fn decode_count(count: u8) -> usize {
+        (16 + (count as usize & 15)) << ((count >> 4) + 6)
+    }
+
+    /// Derive a key of `key_size` octets from a passphrase
+    ///
+    /// When the hash output is shorter than the key, multiple hash contexts
+    /// are used, each preloaded with one more zero octet than the previous.
+    pub fn derive_key(&self, passphrase: &[u8], key_size: usize) -> Result<Vec<u8>, Error> {
+        let mut key = Vec::with_capacity(key_size);
+        let mut preload = 0;
+
+        while key.len() < key_size {
+            let mut hasher = self.hash_algorithm().new_hasher()?;
+            hasher.update(&vec![0; preload]);
+
+            match self {
+                Self::Simple { .. } => hasher.update(passphrase),
+                Self::Salted { salt, .. } => {
+                    hasher.update(salt);
+                    hasher.update(passphrase);
+                },
+                Self::IteratedAndSalted { salt, count, .. } => {
+                    let data = [&salt[..], passphrase].concat();
+
+                    // The whole salt and passphrase is hashed at least once,
+                    // even if the count is smaller.
+                    let count = Self::decode_count(*count).max(data.len());
+
+                    for _ in 0..count / data.len() {
+                        hasher.update(&data);
+                    }
+
+                    hasher.update(&data[..count % data.len()]);
+                },
+            }
+
+            key.extend(hasher.finalize());
+            preload += 1;
+        }
+
+        key.truncate(key_size);
+
+        Ok(key)
+    }
+
+    /// Parse an S2K specifier
+    ///
+    /// Returns the specifier and the amount of octets it occupies.
+    pub fn parse(input: &[u8]) -> Result<(Self, usize), Error> {
+        let eof = "Unexpected end of input while reading S2K specifier.";
+
+        let s2k_type = *input.first().ok_or(eof)?;
+        let hash = HashAlgorithm::from_id(*input.get(1).ok_or(eof)?)?;
+
+        match s2k_type {
+            0 => Ok((Self::Simple { hash }, 2)),
+            1 => {
+                let salt = input.get(2..10).ok_or(eof)?.try_into()?;
+
+                Ok((Self::Salted { hash, salt }, 10))
+            },
+            3 => {
+                let salt = input.get(2..10).ok_or(eof)?.try_into()?;
+                let count = *input.get(10).ok_or(eof)?;
+
+                Ok((Self::IteratedAndSalted { hash, salt, count }, 11))
+            },
+            x => Err(format!("Unknown S2K specifier type `{}`.", x).into()),
+        }
+    }
+
+    /// Encode the S2K specifier
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Simple { hash } => vec![0, hash.to_id()],
+            Self::Salted { hash, salt } => [&[1, hash.to_id()], &salt[..]].concat(),
+            Self::IteratedAndSalted { hash, salt, count } => {
+                [&[3, hash.to_id()], &salt[..], &[*count]].concat()
+            },
+        }
+    }
+
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        match self {
+            Self::Simple { hash } => *hash,
+            Self::Salted { hash, .. } => *hash,
+            Self::IteratedAndSalted { hash, .. } => *hash,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+
+    #[test]
+    fn parse_simple() {
+        let (s2k, size) = StringToKey::parse(&[0x00, 0x02]).unwrap();
+
+        assert_eq!(s2k, StringToKey::Simple { hash: HashAlgorithm::Sha1 });
+        assert_eq!(size, 2);
+    }
+
+    #[test]
+    fn parse_iterated_and_salted() {
+        let input = [0x03, 0x08, 1, 2, 3, 4, 5, 6, 7, 8, 0x60];
+        let (s2k, size) = StringToKey::parse(&input).unwrap();
+
+        assert_eq!(s2k, StringToKey::IteratedAndSalted {
+            hash: HashAlgorithm::Sha256,
+            salt: [1, 2, 3, 4, 5, 6, 7, 8],
+            count: 0x60,
+        });
+        assert_eq!(size, 11);
+    }
+
+    #[test]
+    fn parse_fails_for_unknown_type() {
+        assert!(StringToKey::parse(&[0x02, 0x02]).is_err());
+    }
+
+    #[test]
+    fn parse_fails_for_truncated_salt() {
+        assert!(StringToKey::parse(&[0x01, 0x02, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn decode_count() {
+        assert_eq!(StringToKey::decode_count(0x00), 1024);
+        assert_eq!(StringToKey::decode_count(0xC0), 4_194_304);
+    }
+
+    #[test]
+    fn derive_key_simple() {
+        let s2k = StringToKey::Simple { hash: HashAlgorithm::Sha256 };
+
+        assert_eq!(
+            s2k.derive_key(b"abc", 16).unwrap(),
+            hex!("BA7816BF 8F01CFEA 414140DE 5DAE2223")
+        );
+    }
+
+    #[test]
+    fn derive_key_longer_than_digest_preloads_zeros() {
+        let s2k = StringToKey::Simple { hash: HashAlgorithm::Sha256 };
+        let key = s2k.derive_key(b"abc", 40).unwrap();
+
+        let first = s2k.derive_key(b"abc", 32).unwrap();
+        let second = StringToKey::Simple { hash: HashAlgorithm::Sha256 }
+            .derive_key(b"\0abc", 8)
+            .unwrap()
+        ;
+
+        assert_eq!(key, [first, second].concat());
+    }
+
+    #[test]
+    fn derive_key_salted_hashes_salt_before_passphrase() {
+        let salted = StringToKey::Salted { hash: HashAlgorithm::Sha256, salt: *b"abcdefgh" };
+        let simple = StringToKey::Simple { hash: HashAlgorithm::Sha256 };
+
+        assert_eq!(
+            salted.derive_key(b"passphrase", 32).unwrap(),
+            simple.derive_key(b"abcdefghpassphrase", 32).unwrap()
+        );
+    }
+
+    #[test]
+    fn derive_key_iterated_and_salted_hashes_count_octets() {
+        let salt = *b"abcdefgh";
+        let iterated = StringToKey::IteratedAndSalted { hash: HashAlgorithm::Sha256, salt, count: 0x00 };
+        let simple = StringToKey::Simple { hash: HashAlgorithm::Sha256 };
+
+        // 1024 octets of repeated salt and passphrase
+        let repeated: Vec<u8> = b"abcdefghpass".iter().copied().cycle().take(1024).collect();
+
+        assert_eq!(
+            iterated.derive_key(b"pass", 32).unwrap(),
+            simple.derive_key(&repeated, 32).unwrap()
+        );
+    }
+
+    #[test]
+    fn derive_key_iterated_and_salted_hashes_everything_at_least_once() {
+        let salt = *b"abcdefgh";
+        let passphrase = b"x".repeat(2000);
+        let iterated = StringToKey::IteratedAndSalted { hash: HashAlgorithm::Sha256, salt, count: 0x00 };
+        let salted = StringToKey::Salted { hash: HashAlgorithm::Sha256, salt };
+
+        assert_eq!(
+            iterated.derive_key(&passphrase, 32).unwrap(),
+            salted.derive_key(&passphrase, 32).unwrap()
+        );
+    }
+
+    #[test]
+    fn new_iterated_and_salted_uses_random_salt() {
+        let first = StringToKey::new_iterated_and_salted(HashAlgorithm::Sha256, 0x60);
+        let second = StringToKey::new_iterated_and_salted(HashAlgorithm::Sha256, 0x60);
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn specifiers_round_trip() {
+        let specifiers = [
+            StringToKey::Simple { hash: HashAlgorithm::Sha256 },
+            StringToKey::Salted { hash: HashAlgorithm::Sha1, salt: [8; 8] },
+            StringToKey::IteratedAndSalted { hash: HashAlgorithm::Sha512, salt: [9; 8], count: 0xFF },
+        ];
+
+        for s2k in specifiers.iter() {
+            let encoded = s2k.to_bytes();
+
+            assert_eq!(StringToKey::parse(&encoded).unwrap(), (*s2k, encoded.len()));
+        }
+    }
+}
@@ -22,3 +22,23 @@ pub trait SymmetricEncryption {
 pub trait SymmetricDecryption {
     fn decrypt(self, ciphertext: &[u8]) -> Result<PlainTextOut, Error>;
 }
+
+/// A block cipher that modes of operation can be built on
+pub trait BlockCipherBackend: Sized {
+    const NAME: &'static str;
+
+    /// The block size in bytes
+    const BLOCK_SIZE: usize;
+
+    /// The key size in bytes
+    const KEY_SIZE: usize;
+
+    /// A block of `BLOCK_SIZE` bytes, kept on the stack
+    type Block: AsRef<[u8]> + AsMut<[u8]> + Default + Copy;
+
+    /// Create the cipher, failing if the key is not `KEY_SIZE` bytes long
+    fn from_key(key: &[u8]) -> Result<Self, Error>;
+
+    /// Encrypt a single block
+    fn encrypt_block(&self, block: &Self::Block) -> Self::Block;
+}
@@ -1,19 +1,44 @@
 pub mod crypto;
+pub mod message;
+pub mod packet;
+pub mod progress;
 
+use std::cell::Cell;
+use std::convert::TryFrom;
 use std::fs;
+use std::io;
+use std::io::{BufRead, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::UNIX_EPOCH;
 
-use ascii_armor::ArmorWriterBuilder;
+use ascii_armor::ArmorChecksumStatus;
 use ascii_armor::ArmorDataHeader;
 use ascii_armor::ArmorDataType;
-use ascii_armor::ArmorReader;
+use ascii_armor::ArmorDecoder;
+use ascii_armor::ArmorEncoder;
+use ascii_armor::InputKind;
+use ascii_armor::multipart;
 
-use crypto::openpgp_cfb::OpenPgpCfbAes128;
+use crypto::hash::HashAlgorithm;
+use crypto::openpgp_cfb::WrongKeyError;
+use crypto::s2k::StringToKey;
+use message::{EncryptedMessage, EncryptedMessageReader};
+use packet::{CompressionAlgorithm, LiteralData, LiteralDataFormat};
+use packet::literal_data::{CanonicalTextReader, NativeTextWriter};
+use progress::ProgressReader;
 
 pub type Error = Box<dyn std::error::Error>;
 
+/// Convert an error for use in `io::Read` and `io::Write` implementations
+pub(crate) fn into_io_error(error: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
 pub const APP_NAME: &str = env!("CARGO_PKG_NAME");
 pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SymmetricCipher {
     Aes128,
     Aes192,
@@ -39,135 +64,552 @@ impl SymmetricCipher {
             Self::Aes256 => "AES-256",
         }
     }
+
+    /// Get the cipher from its OpenPGP algorithm ID
+    ///
+    /// See [RFC 4880, Section 9.2](https://tools.ietf.org/html/rfc4880#section-9.2).
+    pub fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            7 => Ok(Self::Aes128),
+            8 => Ok(Self::Aes192),
+            9 => Ok(Self::Aes256),
+            x => Err(format!("Unsupported cipher algorithm ID `{}`.", x).into()),
+        }
+    }
+
+    /// Get the OpenPGP algorithm ID
+    pub fn to_id(&self) -> u8 {
+        match self {
+            Self::Aes128 => 7,
+            Self::Aes192 => 8,
+            Self::Aes256 => 9,
+        }
+    }
+
+    /// The key size in bytes
+    pub fn key_size(&self) -> usize {
+        match self {
+            Self::Aes128 => 16,
+            Self::Aes192 => 24,
+            Self::Aes256 => 32,
+        }
+    }
+}
+
+/// How much the commands print
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Verbosity {
+    /// Only warnings and errors
+    Quiet,
+
+    /// Status messages, and a progress indicator when on a terminal
+    Normal,
+
+    /// Also details about keys and packets
+    Verbose,
+}
+
+/// Single-line progress indicator, drawn on stderr
+///
+/// Only redraws when the percentage changes, and stays hidden when stderr
+/// is not a terminal. Nothing is drawn before `start`, so status messages
+/// printed before that are not interleaved with it.
+struct ProgressIndicator {
+    label: &'static str,
+    enabled: bool,
+    started: Cell<bool>,
+    last_percentage: Cell<Option<u64>>,
+}
+
+impl ProgressIndicator {
+    fn new(label: &'static str, verbosity: Verbosity) -> Self {
+        Self {
+            label,
+            enabled: verbosity >= Verbosity::Normal && io::stderr().is_terminal(),
+            started: Cell::new(false),
+            last_percentage: Cell::new(None),
+        }
+    }
+
+    fn start(&self) {
+        self.started.set(true);
+    }
+
+    fn update(&self, processed: u64, total: Option<u64>) {
+        let total = match total {
+            Some(total) if total > 0 && self.enabled && self.started.get() => total,
+            _ => return,
+        };
+
+        let percentage = processed.min(total) * 100 / total;
+
+        if self.last_percentage.get() == Some(percentage) {
+            return
+        }
+
+        self.last_percentage.set(Some(percentage));
+
+        eprint!("\r{} {:>3}% ({}/{} KB)", self.label, percentage, processed / 1000, total / 1000);
+    }
+
+    /// End the line, if anything was drawn
+    fn finish(&self) {
+        if self.last_percentage.get().is_some() {
+            eprintln!();
+        }
+    }
 }
 
 pub struct EncryptionCommand {
     pub algo: SymmetricCipher,
     pub input_file: String,
     pub output_file: String,
-    pub cipher_key: Vec<u8>,
+    pub passphrase: Vec<u8>,
     pub with_armor: bool,
+    pub format: LiteralDataFormat,
+
+    /// Compress the data before encrypting, unless `Uncompressed`
+    pub compression: CompressionAlgorithm,
+    pub verbosity: Verbosity,
 }
 
 impl EncryptionCommand {
     pub fn run(self) {
-        match &self.algo {
-            SymmetricCipher::Aes128 => {},
-            SymmetricCipher::Aes192 => unimplemented!(),
-            SymmetricCipher::Aes256 => unimplemented!(),
+        if self.verbosity >= Verbosity::Normal {
+            println!("Encrypting `{}` using {}...", self.input_file, self.algo.to_str());
         }
 
-        println!("Encrypting `{}` using {}...", self.input_file, self.algo.to_str());
-
-        let plaintext = fs::read(&self.input_file)
+        let input = fs::File::open(&self.input_file)
             .expect(&format!("Could not read `{}`.", self.input_file))
         ;
 
-        let ciphertext = OpenPgpCfbAes128::encrypt(&plaintext, &self.cipher_key)
-            .expect("Failed to encrypt.")
-        ;
+        let progress = ProgressIndicator::new("Encrypting", self.verbosity);
+        let input = ProgressReader::new(
+            io::BufReader::new(input),
+            Some(file_size(&self.input_file)),
+            |processed, total| progress.update(processed, total),
+        );
 
+        let mut input: Box<dyn Read> = match self.format.is_text() {
+            true => Box::new(CanonicalTextReader::new(input)),
+            false => Box::new(input),
+        };
 
-        if self.with_armor {
-            let mut buffer = fs::File::create(&self.output_file).unwrap();
+        let s2k = StringToKey::new_iterated_and_salted(HashAlgorithm::Sha256, StringToKey::DEFAULT_COUNT);
+        let literal_header = LiteralData {
+            format: self.format,
+            file_name: literal_file_name(&self.input_file),
+            date: literal_date(&self.input_file),
+            data: vec![],
+        };
+
+        if self.verbosity >= Verbosity::Verbose {
+            println!(
+                "Deriving the key from the passphrase using iterated and salted {}, hashing {} octets.",
+                s2k.hash_algorithm().to_str(),
+                StringToKey::decode_count(StringToKey::DEFAULT_COUNT),
+            );
+
+            println!(
+                "Recording {} data with file name `{}` and date {}.",
+                literal_header.format.to_str(),
+                literal_header.file_name,
+                literal_header.date,
+            );
+
+            if self.compression != CompressionAlgorithm::Uncompressed {
+                println!("Compressing the data using {}.", self.compression.to_str());
+            }
+        }
+
+        progress.start();
 
-            let armor = ArmorWriterBuilder::new()
-                .data_type(ArmorDataType::PgpMessage)
+        let output = fs::File::create(&self.output_file)
+            .expect(&format!("Could not write to `{}`.", &self.output_file))
+        ;
+        let output = io::BufWriter::new(output);
+
+        if self.with_armor {
+            let armor = ArmorEncoder::new(output, ArmorDataType::PgpMessage)
                 .add_data_header(
                     ArmorDataHeader::Version,
                     &format!("{} v{}", APP_NAME, APP_VERSION)
                 )
-                .data(&ciphertext)
-                .build()
             ;
 
-            armor.write_unchecked(&mut buffer).unwrap();
-        } else {
-            fs::write(&self.output_file, &ciphertext)
-                .expect(&format!("Could not write to `{}`.", &self.output_file))
+            EncryptedMessage::encrypt_stream(
+                &mut input,
+                armor,
+                &literal_header,
+                self.compression,
+                self.algo,
+                s2k,
+                &self.passphrase,
+            )
+                .and_then(|armor| Ok(armor.finish()?))
+                .expect("Failed to encrypt.")
             ;
+        } else {
+            EncryptedMessage::encrypt_stream(
+                &mut input,
+                output,
+                &literal_header,
+                self.compression,
+                self.algo,
+                s2k,
+                &self.passphrase,
+            ).expect("Failed to encrypt.");
         }
 
-        println!(
-            "Successfully encrypted `{}`. {} bytes of ciphertext written to {}.",
-            &self.input_file,
-            ciphertext.len(),
-            &self.output_file,
-        );
+        progress.finish();
+
+        if self.verbosity >= Verbosity::Normal {
+            println!(
+                "Successfully encrypted `{}`. {} bytes written to {}.",
+                &self.input_file,
+                file_size(&self.output_file),
+                &self.output_file,
+            );
+        }
     }
 }
 
 pub struct DecryptionCommand {
-    pub algo: SymmetricCipher,
     pub input_file: String,
+
+    /// Where to write the plaintext when the message carries no usable file
+    /// name, or a file by that name already exists
     pub output_file: String,
-    pub cipher_key: Vec<u8>,
+    pub passphrase: Vec<u8>,
     pub ignore_crc_error: bool,
+    pub quick_check: bool,
+
+    /// Set the modification time of the output to the date in the message
+    pub restore_time: bool,
+    pub verbosity: Verbosity,
 }
 
 impl DecryptionCommand {
     pub fn run(self) {
-        match &self.algo {
-            SymmetricCipher::Aes128 => {},
-            SymmetricCipher::Aes192 => unimplemented!(),
-            SymmetricCipher::Aes256 => unimplemented!(),
+        if self.verbosity >= Verbosity::Normal {
+            println!("Decrypting `{}`...", self.input_file);
         }
 
-        println!("Decrypting `{}` using {}...", self.input_file, self.algo.to_str());
+        let read_error = format!("Could not read `{}`.", &self.input_file);
+
+        let input = fs::File::open(&self.input_file).expect(&read_error);
+
+        let progress = ProgressIndicator::new("Decrypting", self.verbosity);
+        let input = ProgressReader::new(
+            input,
+            Some(file_size(&self.input_file)),
+            |processed, total| progress.update(processed, total),
+        );
+        let mut input = io::BufReader::new(input);
+
+        let input_kind = InputKind::detect(input.fill_buf().expect(&read_error));
+        let mut armor = None;
+
+        let input: Box<dyn Read + '_> = match input_kind {
+            InputKind::Binary => Box::new(input),
+            InputKind::Armored(ArmorDataType::PgpMessage) => {
+                let decoder = ArmorDecoder::new(input)
+                    .expect("Failed to read ASCII Armor.")
+                    .verify_checksum(!self.ignore_crc_error)
+                ;
+
+                Box::new(armor.insert(decoder))
+            },
+            InputKind::Armored(data_type) => {
+                panic!("`{}` holds a {} instead of a PGP MESSAGE.", &self.input_file, data_type.to_string())
+            },
+            InputKind::CleartextSigned => {
+                panic!("`{}` is a cleartext signed message, which is not encrypted.", &self.input_file)
+            },
+            InputKind::Unknown => {
+                panic!("`{}` is neither an OpenPGP message nor valid ASCII Armor.", &self.input_file)
+            },
+        };
 
-        let input = fs::read(&self.input_file)
-            .expect(&format!("Could not read `{}`.", &self.input_file))
+        let reader = EncryptedMessageReader::new(input)
+            .expect("Failed to read encrypted message.")
+            .quick_check(self.quick_check)
         ;
 
-        let armor_reader = ArmorReader::read_file(&self.input_file);
+        if self.verbosity >= Verbosity::Normal {
+            println!("Message is encrypted using {}.", reader.cipher().to_str());
+        }
+
+        if self.verbosity >= Verbosity::Verbose {
+            println!(
+                "Deriving the key from the passphrase using {}.",
+                reader.session_key.s2k.hash_algorithm().to_str(),
+            );
+        }
 
-        let ciphertext = match armor_reader {
-            Ok(ref armor) => {
-                match &armor.decoded_data {
-                    Ok(decoded_data) => decoded_data,
-                    Err(_) => &input,
+        if !reader.is_integrity_protected() {
+            eprintln!("✗ Message is not integrity protected. Modifications cannot be detected.");
+        }
+
+        let mut output_path = None;
+
+        let result = reader.decrypt_to(&self.passphrase, |literal_data| {
+            let (path, output) = self.create_output(literal_data)?;
+
+            output_path = Some(path);
+            progress.start();
+
+            Ok(NativeTextWriter::new(io::BufWriter::new(output), literal_data.format))
+        });
+
+        let result = result.and_then(|(literal_data, output)| {
+            output.finish()?;
+
+            // The checksum follows the message, so read the armor to its end
+            if let Some(armor) = armor.as_mut() {
+                io::copy(armor, &mut io::sink())?;
+            }
+
+            Ok(literal_data)
+        });
+
+        progress.finish();
+
+        let literal_data = match result {
+            Ok(literal_data) => literal_data,
+            Err(error) => {
+                // Never leave plaintext behind that failed verification
+                if let Some(path) = &output_path {
+                    let _ = fs::remove_file(path);
                 }
+
+                if error.is::<WrongKeyError>() {
+                    eprintln!("✗ Wrong passphrase for `{}`. Nothing was written.", &self.input_file);
+                    process::exit(1);
+                }
+
+                panic!("Failed to decrypt: {}", error);
             },
-            Err(_) => &input,
         };
 
-        match armor_reader {
-            Ok(ref armor) => {
-                let checksum = armor.checksum.as_ref().expect("Could not read checksum.");
-
-                match checksum.verify(&ciphertext) {
-                    true => {
-                        println!("✓ Checksum verification passed.");
-                    },
-                    false => {
-                        match self.ignore_crc_error {
-                            true => {
-                                println!("✗ Checksum verification of `{}` failed. Ignoring...", checksum.get());
-                                std::thread::sleep(std::time::Duration::from_millis(2500));
-                            },
-                            false => {
-                                panic!("✗ Checksum verification of `{}` failed. Aborting.", checksum.get());
-                            },
-                        }
-                    },
-                }
+        let output_path = output_path.expect("Output is opened when decryption succeeds.");
+
+        if let Some(armor) = &armor {
+            match armor.checksum_status() {
+                Some(ArmorChecksumStatus::Matches) if self.verbosity >= Verbosity::Normal => {
+                    println!("✓ Checksum verification passed.");
+                },
+                Some(ArmorChecksumStatus::Absent) if self.verbosity >= Verbosity::Normal => {
+                    println!("Armor has no checksum. Integrity depends on the message itself.");
+                },
+                Some(ArmorChecksumStatus::Mismatch(checksum)) => {
+                    eprintln!("✗ Checksum verification of `{}` failed. Ignored due to --ignore-crc-error.", checksum.get());
+                },
+                _ => {},
+            }
+        }
+
+        if self.verbosity >= Verbosity::Verbose {
+            println!(
+                "Literal data is {} data, with file name `{}` and date {}.",
+                literal_data.format.to_str(),
+                literal_data.file_name,
+                literal_data.date,
+            );
+        }
+
+        if self.restore_time {
+            if let Some(time) = literal_data.modification_time() {
+                fs::File::options()
+                    .write(true)
+                    .open(&output_path)
+                    .and_then(|file| file.set_modified(time))
+                    .expect(&format!("Could not set the modification time of `{}`.", output_path.display()))
+                ;
+            }
+        }
+
+        if self.verbosity >= Verbosity::Normal {
+            println!(
+                "Decrypted `{}`. {} bytes of plaintext written to {}.",
+                &self.input_file,
+                file_size(&output_path),
+                output_path.display(),
+            );
+        }
+    }
+
+    /// Create the file to write the plaintext to: the file name in the
+    /// message, next to the input file, unless it is unusable or already
+    /// exists
+    ///
+    /// The file is only ever created anew, so that no symbolic link planted
+    /// under the name in the message is followed.
+    fn create_output(&self, literal_data: &LiteralData) -> Result<(PathBuf, fs::File), Error> {
+        if let Some(file_name) = literal_data.safe_file_name() {
+            let path = Path::new(&self.input_file).with_file_name(file_name);
+
+            match create_new_file(&path) {
+                Ok(output) => return Ok((path, output)),
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                    eprintln!(
+                        "✗ `{}` already exists. Writing to `{}` instead.",
+                        path.display(),
+                        &self.output_file,
+                    );
+                },
+                Err(error) => return Err(format!("Could not write to `{}`: {}", path.display(), error).into()),
+            }
+        }
+
+        self.create_fallback_output()
+    }
+
+    /// Create the output file given on the command line, which replaces an
+    /// existing file of that name
+    ///
+    /// The existing file is removed rather than truncated, so that a
+    /// symbolic link is replaced instead of followed.
+    fn create_fallback_output(&self) -> Result<(PathBuf, fs::File), Error> {
+        let path = PathBuf::from(&self.output_file);
+        let write_error = |error: io::Error| format!("Could not write to `{}`: {}", path.display(), error);
+
+        let output = match create_new_file(&path) {
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                eprintln!("✗ `{}` already exists and is replaced.", path.display());
+
+                fs::remove_file(&path).map_err(write_error)?;
+                create_new_file(&path).map_err(write_error)?
             },
-            Err(_) => {},
+            result => result.map_err(write_error)?,
+        };
+
+        Ok((path, output))
+    }
+}
+
+pub struct SplitCommand {
+    pub input_file: String,
+
+    /// The most octets of the message in each part
+    pub part_size: usize,
+    pub verbosity: Verbosity,
+}
+
+impl SplitCommand {
+    pub fn run(self) {
+        let message = read_message(&self.input_file)
+            .expect(&format!("Could not read message from `{}`.", self.input_file))
+        ;
+
+        let parts = multipart::split(&message, self.part_size)
+            .unwrap_or_else(|error| panic!("Failed to split message: {}", error))
+        ;
+
+        for (i, part) in parts.iter().enumerate() {
+            let output_file = format!("{}.part{}", self.input_file, i + 1);
+
+            fs::write(&output_file, part)
+                .expect(&format!("Could not write to `{}`.", output_file))
+            ;
+
+            if self.verbosity >= Verbosity::Normal {
+                println!("Wrote part {} of {} to {}.", i + 1, parts.len(), output_file);
+            }
         }
+    }
+}
+
+pub struct JoinCommand {
+    /// Files holding the parts, in any order
+    pub input_files: Vec<String>,
+    pub output_file: String,
+    pub verbosity: Verbosity,
+}
 
-        let plaintext = OpenPgpCfbAes128::decrypt(&ciphertext, &self.cipher_key)
-            .expect("Failed to encrypt.")
+impl JoinCommand {
+    pub fn run(self) {
+        let texts: Vec<String> = self.input_files.iter()
+            .map(|file| fs::read_to_string(file).expect(&format!("Could not read `{}`.", file)))
+            .collect()
+        ;
+        let texts: Vec<&str> = texts.iter().map(|text| &text[..]).collect();
+
+        let message = multipart::join(&texts)
+            .unwrap_or_else(|error| panic!("Failed to join parts: {}", error))
         ;
 
-        fs::write(&self.output_file, &plaintext)
+        let output = fs::File::create(&self.output_file)
             .expect(&format!("Could not write to `{}`.", &self.output_file))
         ;
 
-        println!(
-            "Decrypted `{}`. {} bytes of plaintext written to {}.",
-            &self.input_file,
-            plaintext.len(),
-            &self.output_file,
-        );
+        let mut armor = ArmorEncoder::new(io::BufWriter::new(output), ArmorDataType::PgpMessage)
+            .add_data_header(
+                ArmorDataHeader::Version,
+                &format!("{} v{}", APP_NAME, APP_VERSION)
+            )
+        ;
+
+        armor.write_all(&message)
+            .and_then(|_| armor.finish()?.flush())
+            .expect(&format!("Could not write to `{}`.", &self.output_file))
+        ;
+
+        if self.verbosity >= Verbosity::Normal {
+            println!(
+                "Joined {} files into {}.",
+                self.input_files.len(),
+                &self.output_file,
+            );
+        }
     }
 }
+
+/// Read a message from a file, removing its ASCII Armor if it has one
+fn read_message(path: &str) -> Result<Vec<u8>, Error> {
+    let mut input = io::BufReader::new(fs::File::open(path)?);
+    let mut message = vec![];
+
+    match InputKind::detect(input.fill_buf()?) {
+        InputKind::Binary => input.read_to_end(&mut message)?,
+        InputKind::Armored(_) => ArmorDecoder::new(input)?.read_to_end(&mut message)?,
+        _ => return Err(format!("`{}` is neither an OpenPGP message nor valid ASCII Armor.", path).into()),
+    };
+
+    Ok(message)
+}
+
+/// Open a file for writing that must not exist yet
+fn create_new_file<P: AsRef<Path>>(path: P) -> io::Result<fs::File> {
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+}
+
+fn file_size<P: AsRef<Path>>(path: P) -> u64 {
+    fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+/// The name of a file, as recorded in a Literal Data packet
+///
+/// Left empty when it does not fit.
+fn literal_file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .filter(|file_name| file_name.len() <= 0xFF)
+        .unwrap_or_default()
+}
+
+/// The modification time of a file, as recorded in a Literal Data packet
+///
+/// Zero when unknown, or not representable.
+fn literal_date(path: &str) -> u32 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .and_then(|duration| u32::try_from(duration.as_secs()).ok())
+        .unwrap_or(0)
+}
@@ -1,7 +1,12 @@
 use std::env;
+use std::fs;
+use std::io;
+use std::io::{BufRead, IsTerminal, Write};
+use std::process::Command;
 
 use rpg::{APP_NAME, APP_VERSION, DecryptionCommand};
-use rpg::{EncryptionCommand, SymmetricCipher};
+use rpg::{EncryptionCommand, JoinCommand, SplitCommand, SymmetricCipher, Verbosity};
+use rpg::packet::{CompressionAlgorithm, LiteralDataFormat};
 
 fn main() {
     let mut args: Vec<String> = env::args().collect();
@@ -17,7 +22,7 @@ fn main() {
     if let Some(arg) = args.get(0) {
         if arg == "--help" || arg == "-h" {
             println!("{}", get_help_text());
-        } else if arg == "--encrypt" || arg == "--decrypt" {
+        } else if arg == "--encrypt" {
             let algo = args.get(1)
                 .expect(&format!("Expected algorithm. None provided."))
             ;
@@ -29,35 +34,161 @@ fn main() {
                 .expect(&format!("Expected input file. None provided."))
             ;
 
-            if arg == "--encrypt" {
-                let output_file = format!("{}.rpg", input_file);
-                let with_armor = args.contains(&String::from("--armor"));
-
-                EncryptionCommand {
-                    algo,
-                    input_file: String::from(input_file),
-                    output_file,
-                    cipher_key: (0x112233445566778899AABBCCDDEEFF as u128).to_be_bytes().to_vec(),
-                    with_armor,
-                }.run();
-            } else {
-                let output_file = format!("{}.decrypted", input_file);
-                let ignore_crc_error = args.contains(&String::from("--ignore-crc-error"));
-
-                DecryptionCommand {
-                    algo,
-                    input_file: String::from(input_file),
-                    output_file,
-                    cipher_key: (0x112233445566778899AABBCCDDEEFF as u128).to_be_bytes().to_vec(),
-                    ignore_crc_error,
-                }.run();
+            let output_file = format!("{}.rpg", input_file);
+            let with_armor = args.contains(&String::from("--armor"));
+            let format = match get_option_value(&args, "--format") {
+                Some(format) => LiteralDataFormat::from_str(format)
+                    .expect(&format!("Unknown format `{}`.", format)),
+                None => LiteralDataFormat::Binary,
+            };
+            let compression = match get_option_value(&args, "--compress-algo") {
+                Some(algorithm) => CompressionAlgorithm::from_str(algorithm)
+                    .expect(&format!("Unknown compression algorithm `{}`.", algorithm)),
+                None => CompressionAlgorithm::Uncompressed,
+            };
+
+            if compression == CompressionAlgorithm::Bzip2 {
+                panic!("Compressing with BZip2 is not supported. Use zip or zlib.");
             }
+
+            let passphrase = read_passphrase(&args, true);
+
+            EncryptionCommand {
+                algo,
+                input_file: String::from(input_file),
+                output_file,
+                passphrase,
+                with_armor,
+                format,
+                compression,
+                verbosity: get_verbosity(&args),
+            }.run();
+        } else if arg == "--decrypt" {
+            let input_file = args.get(1)
+                .expect(&format!("Expected input file. None provided."))
+            ;
+
+            let output_file = format!("{}.decrypted", input_file);
+            let ignore_crc_error = args.contains(&String::from("--ignore-crc-error"));
+            let quick_check = !args.contains(&String::from("--no-quick-check"));
+            let restore_time = args.contains(&String::from("--restore-time"));
+            let passphrase = read_passphrase(&args, false);
+
+            DecryptionCommand {
+                input_file: String::from(input_file),
+                output_file,
+                passphrase,
+                ignore_crc_error,
+                quick_check,
+                restore_time,
+                verbosity: get_verbosity(&args),
+            }.run();
+        } else if arg == "--split" {
+            let input_file = args.get(1)
+                .expect(&format!("Expected input file. None provided."))
+            ;
+            let part_size = args.get(2)
+                .expect(&format!("Expected part size. None provided."))
+            ;
+            let part_size = part_size.parse()
+                .expect(&format!("Invalid part size `{}`.", part_size))
+            ;
+
+            SplitCommand {
+                input_file: String::from(input_file),
+                part_size,
+                verbosity: get_verbosity(&args),
+            }.run();
+        } else if arg == "--join" {
+            let input_files: Vec<String> = args[1..].iter()
+                .take_while(|arg| !arg.starts_with('-'))
+                .cloned()
+                .collect()
+            ;
+
+            let first_file = input_files.first()
+                .expect(&format!("Expected input files. None provided."))
+            ;
+            let output_file = format!("{}.joined", first_file);
+
+            JoinCommand {
+                input_files,
+                output_file,
+                verbosity: get_verbosity(&args),
+            }.run();
         } else {
             panic!("Unknown argument `{}` provided. Run --help for available commands.", arg);
         }
     }
 }
 
+fn get_verbosity(args: &[String]) -> Verbosity {
+    let quiet = args.contains(&String::from("--quiet")) || args.contains(&String::from("-q"));
+    let verbose = args.contains(&String::from("--verbose")) || args.contains(&String::from("-v"));
+
+    match (quiet, verbose) {
+        (true, true) => panic!("Options `--quiet` and `--verbose` cannot be combined."),
+        (true, false) => Verbosity::Quiet,
+        (false, true) => Verbosity::Verbose,
+        (false, false) => Verbosity::Normal,
+    }
+}
+
+fn get_option_value<'a>(args: &'a [String], option: &str) -> Option<&'a String> {
+    let index = args.iter().position(|arg| arg == option)?;
+
+    Some(args.get(index + 1).expect(&format!("Expected value for `{}`. None provided.", option)))
+}
+
+/// Read the passphrase from `--passphrase-file`, or prompt for it
+///
+/// When prompting on a terminal, the input is not echoed. The passphrase is
+/// asked twice when `confirm` is set.
+fn read_passphrase(args: &[String], confirm: bool) -> Vec<u8> {
+    if let Some(file) = get_option_value(args, "--passphrase-file") {
+        let contents = fs::read(file)
+            .expect(&format!("Could not read `{}`.", file))
+        ;
+
+        return contents
+            .split(|&b| b == b'\n')
+            .next()
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line).to_vec())
+            .unwrap_or_default()
+    }
+
+    let passphrase = prompt_passphrase("Passphrase: ");
+
+    if confirm && io::stdin().is_terminal() && prompt_passphrase("Repeat passphrase: ") != passphrase {
+        panic!("Passphrases do not match.");
+    }
+
+    passphrase
+}
+
+fn prompt_passphrase(prompt: &str) -> Vec<u8> {
+    let is_terminal = io::stdin().is_terminal();
+
+    eprint!("{}", prompt);
+    io::stderr().flush().unwrap();
+
+    if is_terminal {
+        let _ = Command::new("stty").arg("-echo").status();
+    }
+
+    let mut passphrase = String::new();
+    let result = io::stdin().lock().read_line(&mut passphrase);
+
+    if is_terminal {
+        let _ = Command::new("stty").arg("echo").status();
+        eprintln!();
+    }
+
+    result.expect("Could not read passphrase.");
+
+    passphrase.trim_end_matches(&['\r', '\n'][..]).as_bytes().to_vec()
+}
+
 fn get_help_text() -> String {
     format!(
         "\
@@ -71,11 +202,28 @@ fn get_help_text() -> String {
                             Example usage:
                             {app_bin} --encrypt aes128 input.txt [--armor]
 
-    --decrypt               Decrypt a file. Available ciphers:
-                            aes128, aes192, aes256
+    --decrypt               Decrypt a file. The cipher is read from the file.
+                            The plaintext is written next to it, under the
+                            file name stored in the message, or else with
+                            the .decrypted extension, replacing an existing
+                            file of that name.
+
+                            Example usage:
+                            {app_bin} --decrypt input.txt.rpg
+
+    --split                 Split a message into armored parts of at most
+                            SIZE octets of the message each, written next to
+                            it with the .part1, .part2, ... extensions.
+
+                            Example usage:
+                            {app_bin} --split input.txt.rpg 100000
+
+    --join                  Join the parts of a message, given in any order,
+                            into an armored message with the .joined
+                            extension, next to the first part.
 
                             Example usage:
-                            {app_bin} --decrypt aes128 input.txt.rpg
+                            {app_bin} --join input.txt.rpg.part2 input.txt.rpg.part1
 
     Options:
     --armor                 Apply Radix-64 encoding in ASCII Armor. Useful for
@@ -83,9 +231,41 @@ fn get_help_text() -> String {
 
                             To be used with the --encrypt command.
 
-    --ignore-crc-error      Do not fail on CRC mismatch.
+    --format FORMAT         Store the file as binary (default), text or utf8.
+                            Line endings of text are converted to CRLF, and
+                            back to native ones when decrypting.
+
+                            To be used with the --encrypt command.
+
+    --compress-algo ALGO    Compress the data before encrypting with zip,
+                            zlib or none (default). Messages compressed with
+                            zip, zlib or bzip2 can always be decrypted.
+
+                            To be used with the --encrypt command.
+
+    --ignore-crc-error      Do not fail on CRC mismatch, but warn about it.
+                            Armor without a CRC is always accepted.
 
                             To be used with the --decrypt command.
+
+    --no-quick-check        Do not check for a wrong passphrase before
+                            decrypting. Integrity protected messages still
+                            fail on modification.
+
+                            To be used with the --decrypt command.
+
+    --restore-time          Set the modification time of the decrypted file
+                            to the one stored in the message.
+
+                            To be used with the --decrypt command.
+
+    --passphrase-file FILE  Read the passphrase from the first line of FILE
+                            instead of prompting for it.
+
+    -q, --quiet             Only print warnings and errors.
+
+    -v, --verbose           Also print details about the key derivation and
+                            the decrypted data.
 \
         ",
         app_bin=APP_NAME,
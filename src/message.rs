@@ -0,0 +1,852 @@
+use std::io;
+use std::io::{Read, Write};
+
+use aes::aes128 as aes128_backend;
+use aes::aes192 as aes192_backend;
+use aes::aes256 as aes256_backend;
+
+use crate::crypto::openpgp_cfb::{OpenPgpCfb, OpenPgpCfbDecryptor, OpenPgpCfbEncryptor, OpenPgpCfbResyncDecryptor};
+use crate::crypto::symmetric_cipher::BlockCipherBackend;
+use crate::crypto::s2k::StringToKey;
+use crate::packet;
+use crate::packet::{CompressedData, CompressionAlgorithm, LiteralData, Packet, PacketBody, PacketHeader, PacketTag};
+use crate::packet::{SymmetricKeyEncryptedSessionKey, SymmetricallyEncryptedData};
+use crate::packet::SymmetricallyEncryptedIntegrityProtectedData;
+use crate::packet::compressed_data::{CompressedDataReader, CompressedDataWriter};
+use crate::packet::modification_detection_code::{ModificationDetectionCodeReader, ModificationDetectionCodeWriter};
+use crate::packet::stream::{PacketReader, PacketWriter};
+use crate::Error;
+use crate::SymmetricCipher;
+
+/// The packet holding the encrypted part of a message
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncryptedData {
+    /// Symmetrically Encrypted Data packet (tag 9), without integrity
+    /// protection. Only read, for compatibility with older messages.
+    Unprotected(SymmetricallyEncryptedData),
+
+    /// Sym. Encrypted Integrity Protected Data packet (tag 18)
+    IntegrityProtected(SymmetricallyEncryptedIntegrityProtectedData),
+}
+
+impl EncryptedData {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Unprotected(packet) => packet.to_bytes(),
+            Self::IntegrityProtected(packet) => packet.to_bytes(),
+        }
+    }
+
+    pub fn is_integrity_protected(&self) -> bool {
+        matches!(self, Self::IntegrityProtected(_))
+    }
+}
+
+/// A symmetrically encrypted OpenPGP message
+///
+/// Consists of a Symmetric-Key Encrypted Session Key packet, followed by an
+/// encrypted data packet. Once decrypted, the latter holds a Literal Data
+/// packet, which may be wrapped in a Compressed Data packet.
+///
+/// New messages are always integrity protected: a Modification Detection
+/// Code packet follows the Literal Data packet, and decryption fails when
+/// it does not match.
+///
+/// # Links
+/// - [RFC 4880, Section 11.3](https://tools.ietf.org/html/rfc4880#section-11.3)
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncryptedMessage {
+    pub session_key: SymmetricKeyEncryptedSessionKey,
+    pub encrypted_data: EncryptedData,
+}
+
+impl EncryptedMessage {
+    /// Encrypt a Literal Data packet with a key derived from a passphrase
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rpg::SymmetricCipher;
+    /// use rpg::crypto::hash::HashAlgorithm;
+    /// use rpg::crypto::s2k::StringToKey;
+    /// use rpg::message::EncryptedMessage;
+    /// use rpg::packet::LiteralData;
+    ///
+    /// # fn main() -> Result<(), rpg::Error> {
+    /// let s2k = StringToKey::new_iterated_and_salted(HashAlgorithm::Sha256, 0x60);
+    /// let literal_data = LiteralData::binary(b"Hello world!");
+    ///
+    /// let message = EncryptedMessage::encrypt(&literal_data, SymmetricCipher::Aes128, s2k, b"secret")?;
+    /// let message = EncryptedMessage::parse(&message.to_bytes()?)?;
+    ///
+    /// assert_eq!(message.decrypt(b"secret")?, literal_data);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt(
+        literal_data: &LiteralData,
+        cipher: SymmetricCipher,
+        s2k: StringToKey,
+        passphrase: &[u8],
+    ) -> Result<Self, Error> {
+        let output = Self::encrypt_stream(
+            &mut &literal_data.data[..],
+            Vec::new(),
+            literal_data,
+            CompressionAlgorithm::Uncompressed,
+            cipher,
+            s2k,
+            passphrase,
+        )?;
+
+        Self::parse(&output)
+    }
+
+    /// Encrypt data read from `input` into a Literal Data packet, and write
+    /// the message to `output`
+    ///
+    /// The data is processed in chunks, using partial body lengths, so
+    /// memory use does not depend on its size. The format, file name and
+    /// date are taken from `literal_header`; its data is ignored.
+    ///
+    /// Unless `compression` is `Uncompressed`, the Literal Data packet is
+    /// wrapped in a Compressed Data packet.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rpg::SymmetricCipher;
+    /// use rpg::crypto::hash::HashAlgorithm;
+    /// use rpg::crypto::s2k::StringToKey;
+    /// use rpg::message::EncryptedMessageReader;
+    /// use rpg::message::EncryptedMessage;
+    /// use rpg::packet::{CompressionAlgorithm, LiteralData};
+    ///
+    /// # fn main() -> Result<(), rpg::Error> {
+    /// let s2k = StringToKey::new_iterated_and_salted(HashAlgorithm::Sha256, 0x60);
+    /// let plaintext = vec![0x42; 100_000];
+    ///
+    /// let ciphertext = EncryptedMessage::encrypt_stream(
+    ///     &mut &plaintext[..],
+    ///     Vec::new(),
+    ///     &LiteralData::binary(b""),
+    ///     CompressionAlgorithm::Zlib,
+    ///     SymmetricCipher::Aes256,
+    ///     s2k,
+    ///     b"secret",
+    /// )?;
+    ///
+    /// assert!(ciphertext.len() < 1000);
+    ///
+    /// let mut decrypted = vec![];
+    /// EncryptedMessageReader::new(&ciphertext[..])?.decrypt(b"secret", &mut decrypted)?;
+    ///
+    /// assert_eq!(decrypted, plaintext);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_stream<R: Read, W: Write>(
+        input: &mut R,
+        mut output: W,
+        literal_header: &LiteralData,
+        compression: CompressionAlgorithm,
+        cipher: SymmetricCipher,
+        s2k: StringToKey,
+        passphrase: &[u8],
+    ) -> Result<W, Error> {
+        let key = s2k.derive_key(passphrase, cipher.key_size())?;
+        let key = &key[..];
+
+        let session_key = SymmetricKeyEncryptedSessionKey {
+            cipher,
+            s2k,
+            encrypted_session_key: None,
+        };
+
+        output.write_all(&session_key.to_bytes()?)?;
+
+        let mut packet = PacketWriter::new(output, SymmetricallyEncryptedIntegrityProtectedData::TAG);
+        packet.write_all(&[SymmetricallyEncryptedIntegrityProtectedData::VERSION])?;
+
+        let literal_header = literal_header.header_bytes()?;
+        let literal_header = &literal_header[..];
+
+        let packet = match cipher {
+            SymmetricCipher::Aes128 => Self::encrypt_integrity_protected::<_, _, aes128_backend::Aes128>(input, packet, literal_header, compression, key)?,
+            SymmetricCipher::Aes192 => Self::encrypt_integrity_protected::<_, _, aes192_backend::Aes192>(input, packet, literal_header, compression, key)?,
+            SymmetricCipher::Aes256 => Self::encrypt_integrity_protected::<_, _, aes256_backend::Aes256>(input, packet, literal_header, compression, key)?,
+        };
+
+        packet.finish()
+    }
+
+    /// Parse an encrypted message from binary packets
+    pub fn parse(input: &[u8]) -> Result<Self, Error> {
+        let mut session_key = None;
+        let mut encrypted_data = None;
+
+        for packet in packet::parse(input)? {
+            match packet {
+                Packet::SymmetricKeyEncryptedSessionKey(x) if session_key.is_none() => {
+                    session_key = Some(x);
+                },
+                Packet::SymmetricallyEncryptedData(x) => {
+                    encrypted_data = Some(EncryptedData::Unprotected(x));
+                    break
+                },
+                Packet::SymmetricallyEncryptedIntegrityProtectedData(x) => {
+                    encrypted_data = Some(EncryptedData::IntegrityProtected(x));
+                    break
+                },
+                _ => {},
+            }
+        }
+
+        Ok(Self {
+            session_key: session_key.ok_or("No symmetric-key encrypted session key packet found.")?,
+            encrypted_data: encrypted_data.ok_or("No encrypted data packet found.")?,
+        })
+    }
+
+    /// Encode the message as binary packets
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        Ok([self.session_key.to_bytes()?, self.encrypted_data.to_bytes()?].concat())
+    }
+
+    /// The cipher the message is encrypted with
+    pub fn cipher(&self) -> SymmetricCipher {
+        self.session_key.cipher
+    }
+
+    /// Decrypt the message with a passphrase and return the Literal Data
+    /// packet it holds
+    pub fn decrypt(&self, passphrase: &[u8]) -> Result<LiteralData, Error> {
+        let input = self.to_bytes()?;
+
+        let mut data = vec![];
+        let mut literal_data = EncryptedMessageReader::new(&input[..])?.decrypt(passphrase, &mut data)?;
+        literal_data.data = data;
+
+        Ok(literal_data)
+    }
+
+    /// Prefix the packets, append a Modification Detection Code packet and
+    /// encrypt the result without the OpenPGP CFB resync step
+    fn encrypt_integrity_protected<R: Read, W: Write, C: BlockCipherBackend>(
+        input: &mut R,
+        output: W,
+        literal_header: &[u8],
+        compression: CompressionAlgorithm,
+        key: &[u8],
+    ) -> Result<W, Error> {
+        let encryptor = OpenPgpCfbEncryptor::<_, C>::new(output, key)?;
+
+        let mut mdc = ModificationDetectionCodeWriter::new(encryptor);
+        mdc.write_all(&OpenPgpCfb::<C>::random_prefix())?;
+
+        write_literal_data(input, mdc, literal_header, compression)?.finish()?.finish()
+    }
+}
+
+/// Write a Literal Data packet with the data read from `input`, compressed
+/// in a Compressed Data packet unless `compression` is `Uncompressed`
+fn write_literal_data<R: Read, W: Write>(
+    input: &mut R,
+    output: W,
+    literal_header: &[u8],
+    compression: CompressionAlgorithm,
+) -> Result<W, Error> {
+    if compression == CompressionAlgorithm::Uncompressed {
+        let mut literal_data = PacketWriter::new(output, LiteralData::TAG);
+        literal_data.write_all(literal_header)?;
+        io::copy(input, &mut literal_data)?;
+
+        return literal_data.finish()
+    }
+
+    let compressed_data = PacketWriter::new(output, CompressedData::TAG);
+    let compressed_data = CompressedDataWriter::new(compressed_data, compression)?;
+
+    let mut literal_data = PacketWriter::new(compressed_data, LiteralData::TAG);
+    literal_data.write_all(literal_header)?;
+    io::copy(input, &mut literal_data)?;
+
+    literal_data.finish()?.finish()?.finish()
+}
+
+/// Reads an encrypted message from a stream
+///
+/// Reads up to the start of the encrypted data when created, so the cipher
+/// is known before decrypting.
+///
+/// # Examples
+/// ```rust
+/// use rpg::SymmetricCipher;
+/// use rpg::crypto::hash::HashAlgorithm;
+/// use rpg::crypto::s2k::StringToKey;
+/// use rpg::message::{EncryptedMessage, EncryptedMessageReader};
+/// use rpg::packet::LiteralData;
+///
+/// # fn main() -> Result<(), rpg::Error> {
+/// let s2k = StringToKey::new_iterated_and_salted(HashAlgorithm::Sha256, 0x60);
+/// let literal_data = LiteralData::binary(b"Hello world!");
+///
+/// let message = EncryptedMessage::encrypt(&literal_data, SymmetricCipher::Aes192, s2k, b"secret")?;
+/// let input = message.to_bytes()?;
+///
+/// let reader = EncryptedMessageReader::new(&input[..])?;
+/// assert_eq!(reader.cipher(), SymmetricCipher::Aes192);
+///
+/// let mut output = vec![];
+/// reader.decrypt(b"secret", &mut output)?;
+///
+/// assert_eq!(output, b"Hello world!");
+/// # Ok(())
+/// # }
+/// ```
+pub struct EncryptedMessageReader<R: Read> {
+    pub session_key: SymmetricKeyEncryptedSessionKey,
+    encrypted_data: PacketReader<R>,
+    integrity_protected: bool,
+    quick_check: bool,
+}
+
+impl<R: Read> EncryptedMessageReader<R> {
+    /// Read packets up to the start of the encrypted data
+    pub fn new(mut input: R) -> Result<Self, Error> {
+        let mut session_key = None;
+
+        let (header, integrity_protected) = loop {
+            let header = PacketHeader::read(&mut input)?
+                .ok_or("No encrypted data packet found.")?
+            ;
+
+            match header.tag {
+                PacketTag::SymmetricallyEncryptedData => break (header, false),
+                PacketTag::SymmetricallyEncryptedIntegrityProtectedData => break (header, true),
+                tag => {
+                    let mut body = vec![];
+                    PacketReader::new(header, &mut input).read_to_end(&mut body)?;
+
+                    if tag == PacketTag::SymmetricKeyEncryptedSessionKey && session_key.is_none() {
+                        session_key = Some(SymmetricKeyEncryptedSessionKey::from_body(&body)?);
+                    }
+                },
+            }
+        };
+
+        let mut encrypted_data = PacketReader::new(header, input);
+
+        if integrity_protected {
+            let mut version = [0];
+            encrypted_data.read_exact(&mut version)?;
+
+            SymmetricallyEncryptedIntegrityProtectedData::check_version(version[0])?;
+        }
+
+        Ok(Self {
+            session_key: session_key.ok_or("No symmetric-key encrypted session key packet found.")?,
+            encrypted_data,
+            integrity_protected,
+            quick_check: true,
+        })
+    }
+
+    /// The cipher the message is encrypted with
+    pub fn cipher(&self) -> SymmetricCipher {
+        self.session_key.cipher
+    }
+
+    pub fn is_integrity_protected(&self) -> bool {
+        self.integrity_protected
+    }
+
+    /// Whether to check the quick check octets of the random prefix, which
+    /// fails early with `WrongKeyError` for a wrong key or passphrase
+    ///
+    /// Enabled by default. Disable it where decryption errors are reported
+    /// back to an attacker for chosen ciphertexts, since the check then
+    /// acts as an oracle that leaks plaintext. Integrity protected data
+    /// still fails on the modification detection code.
+    ///
+    /// # Links
+    /// - [RFC 4880, Section 14](https://tools.ietf.org/html/rfc4880#section-14)
+    pub fn quick_check(mut self, enabled: bool) -> Self {
+        self.quick_check = enabled;
+        self
+    }
+
+    /// Decrypt the message with a passphrase, and write the data of the
+    /// Literal Data packet it holds to `output`
+    ///
+    /// Returns the Literal Data packet, without its data. Data is written
+    /// before the Modification Detection Code is checked at the end, so
+    /// the output must be discarded if this fails. A wrong key or
+    /// passphrase fails with `WrongKeyError` before anything is written,
+    /// unless the quick check is disabled.
+    pub fn decrypt<W: Write>(self, passphrase: &[u8], output: &mut W) -> Result<LiteralData, Error> {
+        let (literal_data, _) = self.decrypt_to(passphrase, |_| Ok(output))?;
+
+        Ok(literal_data)
+    }
+
+    /// Like `decrypt`, but only opens the output once the Literal Data
+    /// packet's header has been read
+    ///
+    /// `open_output` is given the packet without its data, so the output
+    /// can depend on its file name, date or format. It is not called when
+    /// the key or passphrase is found to be wrong. Returns the packet and
+    /// the output.
+    pub fn decrypt_to<W, F>(self, passphrase: &[u8], open_output: F) -> Result<(LiteralData, W), Error>
+    where
+        W: Write,
+        F: FnOnce(&LiteralData) -> Result<W, Error>,
+    {
+        if self.session_key.encrypted_session_key.is_some() {
+            return Err("Encrypted session keys are not supported.".into())
+        }
+
+        let key = self.session_key.s2k.derive_key(passphrase, self.cipher().key_size())?;
+        let key = &key[..];
+
+        match self.cipher() {
+            SymmetricCipher::Aes128 => self.decrypt_packets::<_, _, aes128_backend::Aes128>(key, open_output),
+            SymmetricCipher::Aes192 => self.decrypt_packets::<_, _, aes192_backend::Aes192>(key, open_output),
+            SymmetricCipher::Aes256 => self.decrypt_packets::<_, _, aes256_backend::Aes256>(key, open_output),
+        }
+    }
+
+    /// Decrypt the encrypted data packet and read the packets it holds
+    fn decrypt_packets<W, F, C>(self, key: &[u8], open_output: F) -> Result<(LiteralData, W), Error>
+    where
+        W: Write,
+        F: FnOnce(&LiteralData) -> Result<W, Error>,
+        C: BlockCipherBackend,
+    {
+        if !self.integrity_protected {
+            let mut plaintext = OpenPgpCfbResyncDecryptor::<_, C>::new(self.encrypted_data, key)?;
+
+            let mut prefix = vec![0; OpenPgpCfb::<C>::PREFIX_LENGTH];
+            plaintext.read_exact(&mut prefix)?;
+
+            if self.quick_check {
+                OpenPgpCfb::<C>::check_prefix(&prefix)?;
+            }
+
+            return read_literal_data(&mut plaintext, open_output)
+        }
+
+        let decryptor = OpenPgpCfbDecryptor::<_, C>::new(self.encrypted_data, key)?;
+        let mut plaintext = ModificationDetectionCodeReader::new(decryptor);
+
+        let mut prefix = vec![0; OpenPgpCfb::<C>::PREFIX_LENGTH];
+        let literal_data = match plaintext.read_exact(&mut prefix) {
+            Ok(()) => {
+                // Fail before writing anything when the key is wrong
+                if self.quick_check {
+                    OpenPgpCfb::<C>::check_prefix(&prefix)?;
+                }
+
+                read_literal_data(&mut plaintext, open_output)
+            },
+            Err(error) => Err(error.into()),
+        };
+
+        // Read to the end to check the modification detection code, which
+        // takes precedence over any error found in the data it protects
+        io::copy(&mut plaintext, &mut io::sink())?;
+
+        literal_data
+    }
+}
+
+/// How many Compressed Data packets may be nested, which stops messages
+/// that decompress into themselves
+const MAX_COMPRESSION_DEPTH: usize = 8;
+
+/// Read packets up to and including the first Literal Data packet, and
+/// write its data to the output opened for it
+///
+/// Reads into Compressed Data packets on the way.
+fn read_literal_data<W, F>(input: &mut dyn Read, open_output: F) -> Result<(LiteralData, W), Error>
+where
+    W: Write,
+    F: FnOnce(&LiteralData) -> Result<W, Error>,
+{
+    read_literal_data_at_depth(input, open_output, 0)
+}
+
+fn read_literal_data_at_depth<W, F>(mut input: &mut dyn Read, open_output: F, depth: usize) -> Result<(LiteralData, W), Error>
+where
+    W: Write,
+    F: FnOnce(&LiteralData) -> Result<W, Error>,
+{
+    while let Some(header) = PacketHeader::read(&mut input)? {
+        let mut body = PacketReader::new(header, &mut *input);
+
+        if header.tag == CompressedData::TAG {
+            if depth == MAX_COMPRESSION_DEPTH {
+                return Err("Too many nested compressed data packets.".into())
+            }
+
+            let mut packets = CompressedDataReader::new(body)?;
+
+            return read_literal_data_at_depth(&mut packets, open_output, depth + 1)
+        }
+
+        if header.tag == LiteralData::TAG {
+            let literal_data = LiteralData::read_header(&mut body)?;
+
+            let mut output = open_output(&literal_data)?;
+            io::copy(&mut body, &mut output)?;
+
+            return Ok((literal_data, output))
+        }
+
+        io::copy(&mut body, &mut io::sink())?;
+    }
+
+    Err("No literal data packet found in decrypted data.".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::openpgp_cfb::{OpenPgpCfbAes128, WrongKeyError};
+    use crate::crypto::hash::HashAlgorithm;
+    use crate::packet::ModificationDetectionCode;
+
+    const S2K: StringToKey = StringToKey::IteratedAndSalted {
+        hash: HashAlgorithm::Sha256,
+        salt: [1, 2, 3, 4, 5, 6, 7, 8],
+        count: 0x00,
+    };
+
+    /// A random prefix that is not random, since with a random one a wrong
+    /// key passes the quick check by chance once in 65536 times
+    const FIXED_PREFIX: [u8; 18] = [
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09,
+        0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F, 0x10, 0x0F, 0x10,
+    ];
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let literal_data = LiteralData::binary(b"This secret message uses less than 3 blocks.");
+
+        let message = EncryptedMessage::encrypt(&literal_data, SymmetricCipher::Aes128, S2K, b"secret").unwrap();
+        let message = EncryptedMessage::parse(&message.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(message.cipher(), SymmetricCipher::Aes128);
+        assert_eq!(message.session_key.s2k, S2K);
+        assert_eq!(message.decrypt(b"secret").unwrap(), literal_data);
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_with_all_ciphers() {
+        let literal_data = LiteralData::binary(b"This secret message uses less than 3 blocks.");
+
+        for cipher in [SymmetricCipher::Aes128, SymmetricCipher::Aes192, SymmetricCipher::Aes256].iter() {
+            let message = EncryptedMessage::encrypt(&literal_data, *cipher, S2K, b"secret").unwrap();
+            let message = EncryptedMessage::parse(&message.to_bytes().unwrap()).unwrap();
+
+            assert_eq!(message.cipher(), *cipher);
+            assert_eq!(message.decrypt(b"secret").unwrap(), literal_data, "{}", cipher.to_str());
+        }
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_stream_with_partial_lengths() {
+        let plaintext: Vec<u8> = (0..300_000).map(|i| (i % 251) as u8).collect();
+        let literal_header = LiteralData {
+            format: packet::LiteralDataFormat::Binary,
+            file_name: String::from("backup.tar"),
+            date: 1_600_000_000,
+            data: vec![],
+        };
+
+        let ciphertext = EncryptedMessage::encrypt_stream(
+            &mut &plaintext[..],
+            Vec::new(),
+            &literal_header,
+            CompressionAlgorithm::Uncompressed,
+            SymmetricCipher::Aes256,
+            S2K,
+            b"secret",
+        ).unwrap();
+
+        let reader = EncryptedMessageReader::new(&ciphertext[..]).unwrap();
+        assert_eq!(reader.cipher(), SymmetricCipher::Aes256);
+        assert!(reader.is_integrity_protected());
+
+        let mut decrypted = vec![];
+        assert_eq!(reader.decrypt(b"secret", &mut decrypted).unwrap(), literal_header);
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_stream_with_compression() {
+        let plaintext = b"Compressible, compressible, compressible. ".repeat(5000);
+
+        for compression in [CompressionAlgorithm::Zip, CompressionAlgorithm::Zlib].iter() {
+            let ciphertext = EncryptedMessage::encrypt_stream(
+                &mut &plaintext[..],
+                Vec::new(),
+                &LiteralData::binary(b""),
+                *compression,
+                SymmetricCipher::Aes128,
+                S2K,
+                b"secret",
+            ).unwrap();
+
+            assert!(ciphertext.len() < plaintext.len() / 50, "{}", compression.to_str());
+
+            let mut decrypted = vec![];
+            EncryptedMessageReader::new(&ciphertext[..]).unwrap().decrypt(b"secret", &mut decrypted).unwrap();
+
+            assert_eq!(decrypted, plaintext, "{}", compression.to_str());
+        }
+    }
+
+    #[test]
+    fn decrypt_literal_data_in_indeterminate_length_compressed_data() {
+        let key = S2K.derive_key(b"secret", 16).unwrap();
+        let literal_data = LiteralData::binary(b"Hello world!");
+        let compressed_data = CompressedData::compress(CompressionAlgorithm::Zip, &literal_data.to_bytes().unwrap()).unwrap();
+
+        // Like gpg: an old format header with indeterminate length
+        let mut plaintext = FIXED_PREFIX.to_vec();
+        plaintext.push(0xA3);
+        plaintext.extend(compressed_data.to_body().unwrap());
+        plaintext.extend(ModificationDetectionCode::compute(&plaintext).unwrap().to_bytes().unwrap());
+
+        let message = EncryptedMessage {
+            session_key: SymmetricKeyEncryptedSessionKey {
+                cipher: SymmetricCipher::Aes128,
+                s2k: S2K,
+                encrypted_session_key: None,
+            },
+            encrypted_data: EncryptedData::IntegrityProtected(SymmetricallyEncryptedIntegrityProtectedData {
+                ciphertext: OpenPgpCfbAes128::with_key(&key).unwrap().encrypt_without_resync(&plaintext).unwrap(),
+            }),
+        };
+
+        assert_eq!(message.decrypt(b"secret").unwrap(), literal_data);
+    }
+
+    #[test]
+    fn read_literal_data_fails_for_deeply_nested_compressed_data() {
+        let mut packets = LiteralData::binary(b"Hello world!").to_bytes().unwrap();
+
+        for _ in 0..=MAX_COMPRESSION_DEPTH {
+            packets = CompressedData::compress(CompressionAlgorithm::Uncompressed, &packets).unwrap().to_bytes().unwrap();
+        }
+
+        assert!(read_literal_data(&mut &packets[..], |_| Ok(io::sink())).is_err());
+    }
+
+    #[test]
+    fn decrypt_to_opens_output_for_literal_data_header() {
+        let literal_data = LiteralData {
+            format: packet::LiteralDataFormat::Text,
+            file_name: String::from("notes.txt"),
+            date: 1_600_000_000,
+            data: b"Hello\r\n".to_vec(),
+        };
+
+        let message = EncryptedMessage::encrypt(&literal_data, SymmetricCipher::Aes128, S2K, b"secret").unwrap();
+        let input = message.to_bytes().unwrap();
+
+        let (header, output) = EncryptedMessageReader::new(&input[..])
+            .unwrap()
+            .decrypt_to(b"secret", |header| {
+                assert_eq!(header.file_name, "notes.txt");
+                assert!(header.data.is_empty());
+
+                Ok(vec![])
+            })
+            .unwrap()
+        ;
+
+        assert_eq!(header.date, 1_600_000_000);
+        assert_eq!(header.format, packet::LiteralDataFormat::Text);
+        assert_eq!(output, b"Hello\r\n");
+    }
+
+    #[test]
+    fn decrypt_stream_fails_for_modified_ciphertext() {
+        let plaintext = vec![0x42; 100_000];
+        let mut ciphertext = EncryptedMessage::encrypt_stream(
+            &mut &plaintext[..],
+            Vec::new(),
+            &LiteralData::binary(b""),
+            CompressionAlgorithm::Uncompressed,
+            SymmetricCipher::Aes128,
+            S2K,
+            b"secret",
+        ).unwrap();
+
+        let last = ciphertext.len() - 30;
+        ciphertext[last] ^= 0x01;
+
+        let error = EncryptedMessageReader::new(&ciphertext[..])
+            .unwrap()
+            .decrypt(b"secret", &mut io::sink())
+            .unwrap_err()
+        ;
+
+        assert!(error.to_string().contains("tampered"), "{}", error);
+    }
+
+    #[test]
+    fn decrypt_fails_for_wrong_passphrase() {
+        let literal_data = LiteralData::binary(b"This secret message uses less than 3 blocks.");
+        let message = EncryptedMessage::encrypt(&literal_data, SymmetricCipher::Aes128, S2K, b"secret").unwrap();
+
+        assert!(message.decrypt(b"wrong").is_err());
+    }
+
+    #[test]
+    fn decrypt_fails_with_wrong_key_error_before_writing() {
+        let key = S2K.derive_key(b"secret", 16).unwrap();
+
+        let mut plaintext = FIXED_PREFIX.to_vec();
+        plaintext.extend(LiteralData::binary(b"Hello world!").to_bytes().unwrap());
+        plaintext.extend(ModificationDetectionCode::compute(&plaintext).unwrap().to_bytes().unwrap());
+
+        let message = EncryptedMessage {
+            session_key: SymmetricKeyEncryptedSessionKey {
+                cipher: SymmetricCipher::Aes128,
+                s2k: S2K,
+                encrypted_session_key: None,
+            },
+            encrypted_data: EncryptedData::IntegrityProtected(SymmetricallyEncryptedIntegrityProtectedData {
+                ciphertext: OpenPgpCfbAes128::with_key(&key).unwrap().encrypt_without_resync(&plaintext).unwrap(),
+            }),
+        };
+
+        let input = message.to_bytes().unwrap();
+        assert_eq!(message.decrypt(b"secret").unwrap().data, b"Hello world!");
+
+        let mut output = vec![];
+        let error = EncryptedMessageReader::new(&input[..]).unwrap().decrypt(b"wrong", &mut output).unwrap_err();
+
+        assert!(error.is::<WrongKeyError>());
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn decrypt_without_quick_check_fails_on_modification_detection_code() {
+        let literal_data = LiteralData::binary(b"This secret message uses less than 3 blocks.");
+        let message = EncryptedMessage::encrypt(&literal_data, SymmetricCipher::Aes128, S2K, b"secret").unwrap();
+        let input = message.to_bytes().unwrap();
+
+        let error = EncryptedMessageReader::new(&input[..])
+            .unwrap()
+            .quick_check(false)
+            .decrypt(b"wrong", &mut io::sink())
+            .unwrap_err()
+        ;
+
+        assert!(!error.is::<WrongKeyError>());
+    }
+
+    #[test]
+    fn decrypt_unprotected_data_fails_with_wrong_key_error() {
+        let key = S2K.derive_key(b"secret", 16).unwrap();
+
+        // Before the resync step, both variants encrypt alike
+        let message = EncryptedMessage {
+            session_key: SymmetricKeyEncryptedSessionKey {
+                cipher: SymmetricCipher::Aes128,
+                s2k: S2K,
+                encrypted_session_key: None,
+            },
+            encrypted_data: EncryptedData::Unprotected(SymmetricallyEncryptedData {
+                ciphertext: OpenPgpCfbAes128::with_key(&key).unwrap().encrypt_without_resync(&FIXED_PREFIX).unwrap(),
+            }),
+        };
+
+        assert!(message.decrypt(b"wrong").unwrap_err().is::<WrongKeyError>());
+    }
+
+    #[test]
+    fn decrypt_fails_for_modified_ciphertext() {
+        let literal_data = LiteralData::binary(b"This secret message uses less than 3 blocks.");
+        let mut message = EncryptedMessage::encrypt(&literal_data, SymmetricCipher::Aes128, S2K, b"secret").unwrap();
+
+        if let EncryptedData::IntegrityProtected(packet) = &mut message.encrypted_data {
+            // Flip a bit in the literal data
+            packet.ciphertext[30] ^= 0x01;
+        }
+
+        assert!(message.decrypt(b"secret").is_err());
+    }
+
+    #[test]
+    fn decrypt_fails_for_missing_modification_detection_code() {
+        let literal_data = LiteralData::binary(b"This secret message uses less than 3 blocks.");
+        let mut message = EncryptedMessage::encrypt(&literal_data, SymmetricCipher::Aes128, S2K, b"secret").unwrap();
+
+        if let EncryptedData::IntegrityProtected(packet) = &mut message.encrypted_data {
+            let length = packet.ciphertext.len() - ModificationDetectionCode::PACKET_LENGTH;
+            packet.ciphertext.truncate(length);
+        }
+
+        assert!(message.decrypt(b"secret").is_err());
+    }
+
+    #[test]
+    fn decrypt_unprotected_data() {
+        let literal_data = LiteralData::binary(b"This secret message uses less than 3 blocks.");
+        let key = S2K.derive_key(b"secret", 16).unwrap();
+
+        let message = EncryptedMessage {
+            session_key: SymmetricKeyEncryptedSessionKey {
+                cipher: SymmetricCipher::Aes128,
+                s2k: S2K,
+                encrypted_session_key: None,
+            },
+            encrypted_data: EncryptedData::Unprotected(SymmetricallyEncryptedData {
+                ciphertext: OpenPgpCfbAes128::with_key(&key)
+                    .unwrap()
+                    .encrypt(&literal_data.to_bytes().unwrap())
+                    .unwrap(),
+            }),
+        };
+
+        let message = EncryptedMessage::parse(&message.to_bytes().unwrap()).unwrap();
+
+        assert!(!message.encrypted_data.is_integrity_protected());
+        assert_eq!(message.decrypt(b"secret").unwrap(), literal_data);
+    }
+
+    #[test]
+    fn decrypt_fails_for_encrypted_session_key() {
+        let literal_data = LiteralData::binary(b"");
+        let mut message = EncryptedMessage::encrypt(&literal_data, SymmetricCipher::Aes128, S2K, b"").unwrap();
+        message.session_key.encrypted_session_key = Some(vec![0; 17]);
+
+        assert!(message.decrypt(b"").is_err());
+    }
+
+    #[test]
+    fn message_starts_with_skesk_followed_by_integrity_protected_data() {
+        let message = EncryptedMessage::encrypt(&LiteralData::binary(b""), SymmetricCipher::Aes128, S2K, b"")
+            .unwrap()
+            .to_bytes()
+            .unwrap()
+        ;
+
+        let tags: Vec<_> = packet::parse(&message).unwrap().iter().map(|p| p.tag()).collect();
+
+        assert_eq!(tags, vec![
+            packet::PacketTag::SymmetricKeyEncryptedSessionKey,
+            packet::PacketTag::SymmetricallyEncryptedIntegrityProtectedData,
+        ]);
+    }
+
+    #[test]
+    fn parse_fails_without_session_key() {
+        let encrypted_data = SymmetricallyEncryptedData { ciphertext: vec![0; 32] };
+
+        assert!(EncryptedMessage::parse(&encrypted_data.to_bytes().unwrap()).is_err());
+    }
+}
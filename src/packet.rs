@@ -0,0 +1,212 @@
+// OpenPGP packets
+//
+// https://tools.ietf.org/html/rfc4880#section-4
+pub mod header;
+pub mod tag;
+pub mod stream;
+
+pub mod compressed_data;
+pub mod literal_data;
+pub mod symmetric_key_encrypted_session_key;
+pub mod symmetrically_encrypted_data;
+pub mod symmetrically_encrypted_integrity_protected_data;
+pub mod modification_detection_code;
+
+pub use header::{PacketFormat, PacketHeader, PacketLength};
+pub use tag::PacketTag;
+
+pub use compressed_data::{CompressedData, CompressionAlgorithm};
+pub use literal_data::{LiteralData, LiteralDataFormat};
+pub use symmetric_key_encrypted_session_key::SymmetricKeyEncryptedSessionKey;
+pub use symmetrically_encrypted_data::SymmetricallyEncryptedData;
+pub use symmetrically_encrypted_integrity_protected_data::SymmetricallyEncryptedIntegrityProtectedData;
+pub use modification_detection_code::ModificationDetectionCode;
+
+use crate::Error;
+
+/// A packet body that can be parsed and encoded
+pub trait PacketBody: Sized {
+    const TAG: PacketTag;
+
+    /// Parse the packet body, i.e. the packet without its header
+    fn from_body(body: &[u8]) -> Result<Self, Error>;
+
+    /// Encode the packet body, i.e. the packet without its header
+    fn to_body(&self) -> Result<Vec<u8>, Error>;
+
+    /// Encode the packet, including a new format header
+    fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let body = self.to_body()?;
+        let header = PacketHeader::new(Self::TAG, body.len()).to_bytes()?;
+
+        Ok([header, body].concat())
+    }
+}
+
+/// A parsed OpenPGP packet
+#[derive(Debug, Clone, PartialEq)]
+pub enum Packet {
+    SymmetricKeyEncryptedSessionKey(SymmetricKeyEncryptedSessionKey),
+    CompressedData(CompressedData),
+    SymmetricallyEncryptedData(SymmetricallyEncryptedData),
+    LiteralData(LiteralData),
+    SymmetricallyEncryptedIntegrityProtectedData(SymmetricallyEncryptedIntegrityProtectedData),
+    ModificationDetectionCode(ModificationDetectionCode),
+
+    /// A packet that is not (yet) supported. Holds the raw body.
+    Unknown(PacketTag, Vec<u8>),
+}
+
+impl Packet {
+    pub fn tag(&self) -> PacketTag {
+        match self {
+            Self::SymmetricKeyEncryptedSessionKey(_) => SymmetricKeyEncryptedSessionKey::TAG,
+            Self::CompressedData(_) => CompressedData::TAG,
+            Self::SymmetricallyEncryptedData(_) => SymmetricallyEncryptedData::TAG,
+            Self::LiteralData(_) => LiteralData::TAG,
+            Self::SymmetricallyEncryptedIntegrityProtectedData(_) => SymmetricallyEncryptedIntegrityProtectedData::TAG,
+            Self::ModificationDetectionCode(_) => ModificationDetectionCode::TAG,
+            Self::Unknown(tag, _) => *tag,
+        }
+    }
+
+    fn from_raw(tag: PacketTag, body: &[u8]) -> Result<Self, Error> {
+        match tag {
+            PacketTag::SymmetricKeyEncryptedSessionKey => {
+                Ok(Self::SymmetricKeyEncryptedSessionKey(SymmetricKeyEncryptedSessionKey::from_body(body)?))
+            },
+            PacketTag::CompressedData => Ok(Self::CompressedData(CompressedData::from_body(body)?)),
+            PacketTag::SymmetricallyEncryptedData => {
+                Ok(Self::SymmetricallyEncryptedData(SymmetricallyEncryptedData::from_body(body)?))
+            },
+            PacketTag::LiteralData => Ok(Self::LiteralData(LiteralData::from_body(body)?)),
+            PacketTag::SymmetricallyEncryptedIntegrityProtectedData => {
+                Ok(Self::SymmetricallyEncryptedIntegrityProtectedData(
+                    SymmetricallyEncryptedIntegrityProtectedData::from_body(body)?
+                ))
+            },
+            PacketTag::ModificationDetectionCode => {
+                Ok(Self::ModificationDetectionCode(ModificationDetectionCode::from_body(body)?))
+            },
+            tag => Ok(Self::Unknown(tag, body.to_vec())),
+        }
+    }
+}
+
+/// Parse a sequence of packets
+///
+/// # Examples
+/// ```rust
+/// use rpg::packet::{LiteralData, Packet, PacketBody};
+///
+/// # fn main() -> Result<(), rpg::Error> {
+/// let literal_data = LiteralData::binary(b"Hello");
+/// let packets = rpg::packet::parse(&literal_data.to_bytes()?)?;
+///
+/// assert_eq!(packets, vec![Packet::LiteralData(literal_data)]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse(input: &[u8]) -> Result<Vec<Packet>, Error> {
+    let mut packets = vec![];
+    let mut offset = 0;
+
+    while offset < input.len() {
+        let (tag, body, size) = read_raw_packet(&input[offset..])?;
+
+        packets.push(Packet::from_raw(tag, &body)?);
+        offset += size;
+    }
+
+    Ok(packets)
+}
+
+/// Read the tag and the (joined) body of the first packet in the input
+///
+/// Returns the tag, the body and the total amount of octets read.
+fn read_raw_packet(input: &[u8]) -> Result<(PacketTag, Vec<u8>, usize), Error> {
+    let eof = "Unexpected end of input while reading packet body.";
+
+    let (header, mut offset) = PacketHeader::parse(input)?;
+    let mut length = header.length;
+    let mut body = vec![];
+
+    loop {
+        match length {
+            PacketLength::Definite(n) => {
+                body.extend(input.get(offset..offset + n).ok_or(eof)?);
+                offset += n;
+
+                return Ok((header.tag, body, offset))
+            },
+            PacketLength::Partial(n) => {
+                body.extend(input.get(offset..offset + n).ok_or(eof)?);
+                offset += n;
+
+                let (next_length, size) = header::parse_new_format_length(&input[offset..])?;
+                length = next_length;
+                offset += size;
+            },
+            PacketLength::Indeterminate => {
+                body.extend(&input[offset..]);
+
+                return Ok((header.tag, body, input.len()))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_multiple_packets() {
+        let first = LiteralData::binary(b"first");
+        let second = SymmetricallyEncryptedData { ciphertext: vec![1, 2, 3] };
+
+        let input = [first.to_bytes().unwrap(), second.to_bytes().unwrap()].concat();
+
+        assert_eq!(parse(&input).unwrap(), vec![
+            Packet::LiteralData(first),
+            Packet::SymmetricallyEncryptedData(second),
+        ]);
+    }
+
+    #[test]
+    fn parse_partial_body_lengths() {
+        let body = LiteralData::binary(&[0x42; 600]).to_body().unwrap();
+
+        let mut input = vec![0xCB, 0xE9]; // Partial, 512 octets
+        input.extend(&body[..512]);
+        input.extend(&header::encode_length(body.len() - 512).unwrap());
+        input.extend(&body[512..]);
+
+        assert_eq!(parse(&input).unwrap(), vec![
+            Packet::LiteralData(LiteralData::binary(&[0x42; 600])),
+        ]);
+    }
+
+    #[test]
+    fn parse_old_format_indeterminate_length() {
+        let mut input = vec![0xAF]; // Old format, tag 11, indeterminate
+        input.extend(LiteralData::binary(b"Hello").to_body().unwrap());
+
+        assert_eq!(parse(&input).unwrap(), vec![
+            Packet::LiteralData(LiteralData::binary(b"Hello")),
+        ]);
+    }
+
+    #[test]
+    fn parse_unknown_packet() {
+        assert_eq!(
+            parse(&[0xCA, 0x03, b'P', b'G', b'P']).unwrap(),
+            vec![Packet::Unknown(PacketTag::Marker, b"PGP".to_vec())]
+        );
+    }
+
+    #[test]
+    fn parse_fails_for_truncated_body() {
+        assert!(parse(&[0xCB, 0x10, b'b', 0x00]).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use std::io;
+use std::io::{Read, Write};
+
+use bzip2::Bzip2Decoder;
+use deflate::{DeflateDecoder, DeflateEncoder, ZlibDecoder, ZlibEncoder};
+
+use super::{PacketBody, PacketTag};
+
+use crate::Error;
+
+/// OpenPGP compression algorithms
+///
+/// # Links
+/// - [RFC 4880, Section 9.3](https://tools.ietf.org/html/rfc4880#section-9.3)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompressionAlgorithm {
+    Uncompressed,
+
+    /// Raw deflate data
+    Zip,
+
+    /// Deflate data with a zlib header and checksum
+    Zlib,
+
+    /// Only supported for decompression
+    Bzip2,
+}
+
+impl CompressionAlgorithm {
+    /// Get the compression algorithm from its OpenPGP algorithm ID
+    pub fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            0 => Ok(Self::Uncompressed),
+            1 => Ok(Self::Zip),
+            2 => Ok(Self::Zlib),
+            3 => Ok(Self::Bzip2),
+            x => Err(format!("Unknown compression algorithm ID `{}`.", x).into()),
+        }
+    }
+
+    /// Get the OpenPGP algorithm ID
+    pub fn to_id(&self) -> u8 {
+        match self {
+            Self::Uncompressed => 0,
+            Self::Zip => 1,
+            Self::Zlib => 2,
+            Self::Bzip2 => 3,
+        }
+    }
+
+    pub fn from_str(input: &str) -> Result<Self, Error> {
+        match &input.to_lowercase()[..] {
+            "none" | "uncompressed" => Ok(Self::Uncompressed),
+            "zip" => Ok(Self::Zip),
+            "zlib" => Ok(Self::Zlib),
+            "bzip2" => Ok(Self::Bzip2),
+            x => Err(format!("Unknown compression algorithm `{}`.", x).into()),
+        }
+    }
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            Self::Uncompressed => "uncompressed",
+            Self::Zip => "ZIP",
+            Self::Zlib => "ZLIB",
+            Self::Bzip2 => "BZip2",
+        }
+    }
+}
+
+/// Compressed Data packet (tag 8)
+///
+/// Holds other packets, compressed as a whole.
+///
+/// # Examples
+/// ```rust
+/// use rpg::packet::{CompressedData, CompressionAlgorithm, LiteralData, PacketBody};
+///
+/// # fn main() -> Result<(), rpg::Error> {
+/// let literal_data = LiteralData::binary(&[0x42; 1000]).to_bytes()?;
+/// let compressed_data = CompressedData::compress(CompressionAlgorithm::Zlib, &literal_data)?;
+///
+/// assert!(compressed_data.data.len() < 100);
+/// assert_eq!(compressed_data.decompress()?, literal_data);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Links
+/// - [RFC 4880, Section 5.6](https://tools.ietf.org/html/rfc4880#section-5.6)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressedData {
+    pub algorithm: CompressionAlgorithm,
+
+    /// The compressed packets
+    pub data: Vec<u8>,
+}
+
+impl CompressedData {
+    /// Compress encoded packets
+    pub fn compress(algorithm: CompressionAlgorithm, packets: &[u8]) -> Result<Self, Error> {
+        let mut writer = CompressedDataWriter::new(Vec::new(), algorithm)?;
+        writer.write_all(packets)?;
+
+        Ok(Self {
+            algorithm,
+            data: writer.finish()?[1..].to_vec(),
+        })
+    }
+
+    /// Decompress the packets
+    pub fn decompress(&self) -> Result<Vec<u8>, Error> {
+        let body = self.to_body()?;
+
+        let mut packets = vec![];
+        CompressedDataReader::new(&body[..])?.read_to_end(&mut packets)?;
+
+        Ok(packets)
+    }
+}
+
+impl PacketBody for CompressedData {
+    const TAG: PacketTag = PacketTag::CompressedData;
+
+    fn from_body(body: &[u8]) -> Result<Self, Error> {
+        let algorithm = *body.first().ok_or("Unexpected end of compressed data packet.")?;
+
+        Ok(Self {
+            algorithm: CompressionAlgorithm::from_id(algorithm)?,
+            data: body[1..].to_vec(),
+        })
+    }
+
+    fn to_body(&self) -> Result<Vec<u8>, Error> {
+        Ok([&[self.algorithm.to_id()], &self.data[..]].concat())
+    }
+}
+
+enum Compressor<W: Write> {
+    Uncompressed(W),
+    Zip(DeflateEncoder<W>),
+    Zlib(ZlibEncoder<W>),
+}
+
+/// Writes the body of a Compressed Data packet, compressing the packets
+/// written to it
+///
+/// The output is usually a `PacketWriter` for the Compressed Data packet.
+pub struct CompressedDataWriter<W: Write> {
+    compressor: Compressor<W>,
+}
+
+impl<W: Write> CompressedDataWriter<W> {
+    /// Write the algorithm octet, failing for algorithms that can only be
+    /// decompressed
+    pub fn new(mut output: W, algorithm: CompressionAlgorithm) -> Result<Self, Error> {
+        if algorithm == CompressionAlgorithm::Bzip2 {
+            return Err("Compressing with BZip2 is not supported.".into())
+        }
+
+        output.write_all(&[algorithm.to_id()])?;
+
+        let compressor = match algorithm {
+            CompressionAlgorithm::Zip => Compressor::Zip(DeflateEncoder::new(output)),
+            CompressionAlgorithm::Zlib => Compressor::Zlib(ZlibEncoder::new(output)),
+            _ => Compressor::Uncompressed(output),
+        };
+
+        Ok(Self { compressor })
+    }
+
+    /// Write the remaining compressed data and return the underlying writer
+    pub fn finish(self) -> Result<W, Error> {
+        let output = match self.compressor {
+            Compressor::Uncompressed(output) => output,
+            Compressor::Zip(encoder) => encoder.finish()?,
+            Compressor::Zlib(encoder) => encoder.finish()?,
+        };
+
+        Ok(output)
+    }
+}
+
+impl<W: Write> Write for CompressedDataWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match &mut self.compressor {
+            Compressor::Uncompressed(output) => output.write(data),
+            Compressor::Zip(encoder) => encoder.write(data),
+            Compressor::Zlib(encoder) => encoder.write(data),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.compressor {
+            Compressor::Uncompressed(output) => output.flush(),
+            Compressor::Zip(encoder) => encoder.flush(),
+            Compressor::Zlib(encoder) => encoder.flush(),
+        }
+    }
+}
+
+enum Decompressor<R: Read> {
+    Uncompressed(R),
+    Zip(DeflateDecoder<R>),
+    Zlib(ZlibDecoder<R>),
+    Bzip2(Bzip2Decoder<R>),
+}
+
+/// Reads the body of a Compressed Data packet, decompressing the packets it
+/// holds
+pub struct CompressedDataReader<R: Read> {
+    pub algorithm: CompressionAlgorithm,
+    decompressor: Decompressor<R>,
+}
+
+impl<R: Read> CompressedDataReader<R> {
+    /// Read the algorithm octet at the start of the body
+    pub fn new(mut input: R) -> Result<Self, Error> {
+        let mut algorithm = [0];
+        input.read_exact(&mut algorithm)?;
+
+        let algorithm = CompressionAlgorithm::from_id(algorithm[0])?;
+
+        let decompressor = match algorithm {
+            CompressionAlgorithm::Uncompressed => Decompressor::Uncompressed(input),
+            CompressionAlgorithm::Zip => Decompressor::Zip(DeflateDecoder::new(input)),
+            CompressionAlgorithm::Zlib => Decompressor::Zlib(ZlibDecoder::new(input)),
+            CompressionAlgorithm::Bzip2 => Decompressor::Bzip2(Bzip2Decoder::new(input)),
+        };
+
+        Ok(Self { algorithm, decompressor })
+    }
+}
+
+impl<R: Read> Read for CompressedDataReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match &mut self.decompressor {
+            Decompressor::Uncompressed(input) => input.read(buffer),
+            Decompressor::Zip(decoder) => decoder.read(buffer),
+            Decompressor::Zlib(decoder) => decoder.read(buffer),
+            Decompressor::Bzip2(decoder) => decoder.read(buffer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+    use crate::packet::LiteralData;
+
+    #[test]
+    fn compress_and_decompress_with_all_algorithms() {
+        let packets = LiteralData::binary(&b"Hello, Hello, Hello!".repeat(100)).to_bytes().unwrap();
+
+        for algorithm in [CompressionAlgorithm::Uncompressed, CompressionAlgorithm::Zip, CompressionAlgorithm::Zlib].iter() {
+            let compressed_data = CompressedData::compress(*algorithm, &packets).unwrap();
+            let compressed_data = CompressedData::from_body(&compressed_data.to_body().unwrap()).unwrap();
+
+            assert_eq!(compressed_data.algorithm, *algorithm);
+            assert_eq!(compressed_data.decompress().unwrap(), packets, "{}", algorithm.to_str());
+        }
+    }
+
+    #[test]
+    fn decompress_bzip2() {
+        let compressed_data = CompressedData {
+            algorithm: CompressionAlgorithm::Bzip2,
+
+            // Python: bz2.compress(b"hello")
+            data: hex!("
+                425A6839 31415926 53591931 653D0000 00810002 44A00021 9A68334D 07338BB9
+                229C2848 0C98B29E 80
+            ").to_vec(),
+        };
+
+        assert_eq!(compressed_data.decompress().unwrap(), b"hello");
+    }
+
+    #[test]
+    fn compress_fails_for_bzip2() {
+        assert!(CompressedData::compress(CompressionAlgorithm::Bzip2, b"").is_err());
+    }
+
+    #[test]
+    fn parse_fails_for_unknown_algorithm() {
+        assert!(CompressedData::from_body(&[0x04]).is_err());
+        assert!(CompressedData::from_body(&[]).is_err());
+    }
+
+    #[test]
+    fn algorithm_from_str() {
+        assert_eq!(CompressionAlgorithm::from_str("ZLIB").unwrap(), CompressionAlgorithm::Zlib);
+        assert_eq!(CompressionAlgorithm::from_str("none").unwrap(), CompressionAlgorithm::Uncompressed);
+        assert!(CompressionAlgorithm::from_str("lzma").is_err());
+    }
+}
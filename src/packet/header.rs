@@ -0,0 +1,457 @@
+use std::convert::TryInto;
+use std::io;
+use std::io::Read;
+
+use super::PacketTag;
+
+use crate::Error;
+
+/// The packet header format
+///
+/// # Links
+/// - [RFC 4880, Section 4.2](https://tools.ietf.org/html/rfc4880#section-4.2)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PacketFormat {
+    /// Old format packets can only express tags up to 15.
+    Old,
+
+    /// New format packets, which are always written by rpg.
+    New,
+}
+
+/// The length of a packet body, as encoded in the packet header
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PacketLength {
+    /// The body consists of exactly this many octets.
+    Definite(usize),
+
+    /// Only the first part of the body, of this many octets, follows. It is
+    /// followed by another length header for the next part.
+    Partial(usize),
+
+    /// The body extends until the end of the input. Old format only.
+    Indeterminate,
+}
+
+/// OpenPGP packet header
+///
+/// # Links
+/// - [RFC 4880, Section 4.2](https://tools.ietf.org/html/rfc4880#section-4.2)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PacketHeader {
+    pub format: PacketFormat,
+    pub tag: PacketTag,
+    pub length: PacketLength,
+}
+
+impl PacketHeader {
+    /// Create a new format header for a body of a known length
+    pub fn new(tag: PacketTag, body_length: usize) -> Self {
+        Self {
+            format: PacketFormat::New,
+            tag,
+            length: PacketLength::Definite(body_length),
+        }
+    }
+
+    /// Parse a packet header
+    ///
+    /// Returns the header and the amount of octets it occupies.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rpg::packet::{PacketHeader, PacketLength, PacketTag};
+    ///
+    /// # fn main() -> Result<(), rpg::Error> {
+    /// let (header, size) = PacketHeader::parse(&[0xCB, 0x0F])?;
+    ///
+    /// assert_eq!(header.tag, PacketTag::LiteralData);
+    /// assert_eq!(header.length, PacketLength::Definite(15));
+    /// assert_eq!(size, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(input: &[u8]) -> Result<(Self, usize), Error> {
+        let ptag = *input.first().ok_or("Unexpected end of input while reading packet tag.")?;
+
+        if ptag & 0b1000_0000 == 0 {
+            return Err(format!("Invalid packet tag octet `{:#04X}`.", ptag).into())
+        }
+
+        if ptag & 0b0100_0000 == 0 {
+            let tag = PacketTag::from_id((ptag & 0b0011_1100) >> 2);
+            let (length, size) = parse_old_format_length(ptag & 0b0000_0011, &input[1..])?;
+
+            return Ok((Self { format: PacketFormat::Old, tag, length }, 1 + size))
+        }
+
+        let tag = PacketTag::from_id(ptag & 0b0011_1111);
+        let (length, size) = parse_new_format_length(&input[1..])?;
+
+        Ok((Self { format: PacketFormat::New, tag, length }, 1 + size))
+    }
+
+    /// Read a packet header from a stream
+    ///
+    /// Returns `None` when the stream ends before the header starts.
+    pub fn read<R: Read>(input: &mut R) -> Result<Option<Self>, Error> {
+        let mut ptag = [0];
+
+        match input.read_exact(&mut ptag) {
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            result => result?,
+        }
+
+        if ptag[0] & 0b1000_0000 == 0 {
+            return Err(format!("Invalid packet tag octet `{:#04X}`.", ptag[0]).into())
+        }
+
+        let mut header = ptag.to_vec();
+
+        if ptag[0] & 0b0100_0000 == 0 {
+            let octet_count = match ptag[0] & 0b0000_0011 {
+                0 => 1,
+                1 => 2,
+                2 => 4,
+                _ => 0,
+            };
+
+            header.extend(read_octets(input, octet_count)?);
+        } else {
+            header.extend(read_new_format_length_octets(input)?);
+        }
+
+        Ok(Some(Self::parse(&header)?.0))
+    }
+
+    /// Encode the packet header
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rpg::packet::{PacketHeader, PacketTag};
+    ///
+    /// # fn main() -> Result<(), rpg::Error> {
+    /// let header = PacketHeader::new(PacketTag::LiteralData, 1000);
+    ///
+    /// assert_eq!(header.to_bytes()?, [0xCB, 0xC3, 0x28]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let tag = self.tag.to_id();
+
+        match self.format {
+            PacketFormat::New => {
+                if tag > 0b0011_1111 {
+                    return Err(format!("Packet tag `{}` does not fit in a packet header.", tag).into())
+                }
+
+                let mut output = vec![0b1100_0000 | tag];
+
+                match self.length {
+                    PacketLength::Definite(length) => output.extend(encode_length(length)?),
+                    PacketLength::Partial(length) => output.push(encode_partial_length(length)?),
+                    PacketLength::Indeterminate => {
+                        return Err("New format packets cannot have an indeterminate length.".into())
+                    },
+                }
+
+                Ok(output)
+            },
+            PacketFormat::Old => {
+                if tag > 0b0000_1111 {
+                    return Err(format!("Packet tag `{}` does not fit in an old format header.", tag).into())
+                }
+
+                let ptag = 0b1000_0000 | tag << 2;
+
+                match self.length {
+                    PacketLength::Definite(length) if length <= 0xFF => {
+                        Ok(vec![ptag, length as u8])
+                    },
+                    PacketLength::Definite(length) if length <= 0xFFFF => {
+                        Ok([&[ptag | 1], &(length as u16).to_be_bytes()[..]].concat())
+                    },
+                    PacketLength::Definite(length) => {
+                        let length: u32 = length.try_into()
+                            .map_err(|_| "Packet body is too long.")?
+                        ;
+
+                        Ok([&[ptag | 2], &length.to_be_bytes()[..]].concat())
+                    },
+                    PacketLength::Indeterminate => Ok(vec![ptag | 3]),
+                    PacketLength::Partial(_) => {
+                        Err("Old format packets cannot have a partial length.".into())
+                    },
+                }
+            },
+        }
+    }
+}
+
+fn parse_old_format_length(length_type: u8, input: &[u8]) -> Result<(PacketLength, usize), Error> {
+    let octet_count = match length_type {
+        0 => 1,
+        1 => 2,
+        2 => 4,
+        _ => return Ok((PacketLength::Indeterminate, 0)),
+    };
+
+    let octets = input.get(0..octet_count)
+        .ok_or("Unexpected end of input while reading packet length.")?
+    ;
+
+    let length = octets
+        .iter()
+        .fold(0usize, |length, &octet| length << 8 | octet as usize)
+    ;
+
+    Ok((PacketLength::Definite(length), octet_count))
+}
+
+/// Parse a new format body length
+///
+/// Also used for the body lengths following a partial body length.
+///
+/// # Links
+/// - [RFC 4880, Section 4.2.2](https://tools.ietf.org/html/rfc4880#section-4.2.2)
+pub fn parse_new_format_length(input: &[u8]) -> Result<(PacketLength, usize), Error> {
+    let eof = "Unexpected end of input while reading packet length.";
+
+    let first = *input.first().ok_or(eof)? as usize;
+
+    match first {
+        0..=191 => Ok((PacketLength::Definite(first), 1)),
+        192..=223 => {
+            let second = *input.get(1).ok_or(eof)? as usize;
+
+            Ok((PacketLength::Definite(((first - 192) << 8) + second + 192), 2))
+        },
+        224..=254 => Ok((PacketLength::Partial(1 << (first & 0x1F)), 1)),
+        _ => {
+            let octets: [u8; 4] = input.get(1..5).ok_or(eof)?.try_into()?;
+
+            Ok((PacketLength::Definite(u32::from_be_bytes(octets) as usize), 5))
+        },
+    }
+}
+
+/// Read a new format body length from a stream
+///
+/// Used for the body lengths following a partial body length.
+pub fn read_new_format_length<R: Read>(input: &mut R) -> Result<PacketLength, Error> {
+    Ok(parse_new_format_length(&read_new_format_length_octets(input)?)?.0)
+}
+
+fn read_new_format_length_octets<R: Read>(input: &mut R) -> Result<Vec<u8>, Error> {
+    let mut octets = read_octets(input, 1)?;
+
+    let remaining = match octets[0] {
+        192..=223 => 1,
+        255 => 4,
+        _ => 0,
+    };
+
+    octets.extend(read_octets(input, remaining)?);
+
+    Ok(octets)
+}
+
+fn read_octets<R: Read>(input: &mut R, count: usize) -> Result<Vec<u8>, Error> {
+    let mut octets = vec![0; count];
+
+    input.read_exact(&mut octets)
+        .map_err(|_| "Unexpected end of input while reading packet length.")?
+    ;
+
+    Ok(octets)
+}
+
+/// Encode a new format body length using the shortest possible encoding
+pub fn encode_length(length: usize) -> Result<Vec<u8>, Error> {
+    match length {
+        0..=191 => Ok(vec![length as u8]),
+        192..=8383 => {
+            let length = length - 192;
+
+            Ok(vec![(length >> 8) as u8 + 192, length as u8])
+        },
+        _ => {
+            let length: u32 = length.try_into()
+                .map_err(|_| "Packet body is too long.")?
+            ;
+
+            Ok([&[0xFF], &length.to_be_bytes()[..]].concat())
+        },
+    }
+}
+
+/// Encode a partial body length
+///
+/// Partial body lengths must be a power of two between 1 and 2^30.
+pub fn encode_partial_length(length: usize) -> Result<u8, Error> {
+    if !length.is_power_of_two() || length > 1 << 30 {
+        return Err(format!("Invalid partial body length `{}`.", length).into())
+    }
+
+    Ok(224 + length.trailing_zeros() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_new_format_one_octet_length() {
+        let (header, size) = PacketHeader::parse(&[0xC3, 0x0D]).unwrap();
+
+        assert_eq!(header.format, PacketFormat::New);
+        assert_eq!(header.tag, PacketTag::SymmetricKeyEncryptedSessionKey);
+        assert_eq!(header.length, PacketLength::Definite(13));
+        assert_eq!(size, 2);
+    }
+
+    #[test]
+    fn parse_new_format_two_octet_length() {
+        let (header, size) = PacketHeader::parse(&[0xC9, 0xC5, 0xFB]).unwrap();
+
+        assert_eq!(header.length, PacketLength::Definite(1723));
+        assert_eq!(size, 3);
+    }
+
+    #[test]
+    fn parse_new_format_five_octet_length() {
+        let (header, size) = PacketHeader::parse(&[0xC9, 0xFF, 0x00, 0x01, 0x86, 0xA0]).unwrap();
+
+        assert_eq!(header.length, PacketLength::Definite(100_000));
+        assert_eq!(size, 6);
+    }
+
+    #[test]
+    fn parse_new_format_partial_length() {
+        let (header, _) = PacketHeader::parse(&[0xCB, 0xEF]).unwrap();
+
+        assert_eq!(header.length, PacketLength::Partial(32768));
+    }
+
+    #[test]
+    fn parse_old_format_lengths() {
+        let (header, size) = PacketHeader::parse(&[0xA4, 0x10]).unwrap();
+        assert_eq!(header.format, PacketFormat::Old);
+        assert_eq!(header.tag, PacketTag::SymmetricallyEncryptedData);
+        assert_eq!(header.length, PacketLength::Definite(16));
+        assert_eq!(size, 2);
+
+        let (header, size) = PacketHeader::parse(&[0xA5, 0x01, 0x00]).unwrap();
+        assert_eq!(header.length, PacketLength::Definite(256));
+        assert_eq!(size, 3);
+
+        let (header, size) = PacketHeader::parse(&[0xA6, 0x00, 0x01, 0x00, 0x00]).unwrap();
+        assert_eq!(header.length, PacketLength::Definite(65536));
+        assert_eq!(size, 5);
+
+        let (header, size) = PacketHeader::parse(&[0xA7]).unwrap();
+        assert_eq!(header.length, PacketLength::Indeterminate);
+        assert_eq!(size, 1);
+    }
+
+    #[test]
+    fn parse_fails_for_invalid_tag_octet() {
+        assert!(PacketHeader::parse(&[0x0B, 0x00]).is_err());
+    }
+
+    #[test]
+    fn parse_fails_for_truncated_input() {
+        assert!(PacketHeader::parse(&[]).is_err());
+        assert!(PacketHeader::parse(&[0xCB]).is_err());
+        assert!(PacketHeader::parse(&[0xCB, 0xC5]).is_err());
+        assert!(PacketHeader::parse(&[0xCB, 0xFF, 0x00]).is_err());
+        assert!(PacketHeader::parse(&[0xA5, 0x01]).is_err());
+    }
+
+    #[test]
+    fn read_headers_from_stream() {
+        let input = [0xCB, 0xC5, 0xFB, 0xA6, 0x00, 0x01, 0x00, 0x00, 0xD2, 0xEF];
+        let mut input = &input[..];
+
+        let header = PacketHeader::read(&mut input).unwrap().unwrap();
+        assert_eq!(header.length, PacketLength::Definite(1723));
+
+        let header = PacketHeader::read(&mut input).unwrap().unwrap();
+        assert_eq!(header.format, PacketFormat::Old);
+        assert_eq!(header.length, PacketLength::Definite(65536));
+
+        let header = PacketHeader::read(&mut input).unwrap().unwrap();
+        assert_eq!(header.tag, PacketTag::SymmetricallyEncryptedIntegrityProtectedData);
+        assert_eq!(header.length, PacketLength::Partial(32768));
+
+        assert_eq!(PacketHeader::read(&mut input).unwrap(), None);
+    }
+
+    #[test]
+    fn read_fails_for_truncated_stream() {
+        assert!(PacketHeader::read(&mut &[0xCB, 0xFF, 0x00][..]).is_err());
+        assert!(PacketHeader::read(&mut &[0xA5, 0x01][..]).is_err());
+    }
+
+    #[test]
+    fn encode_length_boundaries() {
+        assert_eq!(encode_length(0).unwrap(), [0x00]);
+        assert_eq!(encode_length(191).unwrap(), [0xBF]);
+        assert_eq!(encode_length(192).unwrap(), [0xC0, 0x00]);
+        assert_eq!(encode_length(8383).unwrap(), [0xDF, 0xFF]);
+        assert_eq!(encode_length(8384).unwrap(), [0xFF, 0x00, 0x00, 0x20, 0xC0]);
+    }
+
+    #[test]
+    fn encode_partial_length_only_accepts_powers_of_two() {
+        assert_eq!(encode_partial_length(1).unwrap(), 0xE0);
+        assert_eq!(encode_partial_length(512).unwrap(), 0xE9);
+        assert!(encode_partial_length(0).is_err());
+        assert!(encode_partial_length(500).is_err());
+    }
+
+    #[test]
+    fn headers_round_trip() {
+        let headers = [
+            PacketHeader::new(PacketTag::LiteralData, 0),
+            PacketHeader::new(PacketTag::LiteralData, 191),
+            PacketHeader::new(PacketTag::SymmetricallyEncryptedData, 8383),
+            PacketHeader::new(PacketTag::SymmetricallyEncryptedData, 1 << 20),
+            PacketHeader {
+                format: PacketFormat::New,
+                tag: PacketTag::LiteralData,
+                length: PacketLength::Partial(4096),
+            },
+            PacketHeader {
+                format: PacketFormat::Old,
+                tag: PacketTag::LiteralData,
+                length: PacketLength::Definite(300),
+            },
+            PacketHeader {
+                format: PacketFormat::Old,
+                tag: PacketTag::CompressedData,
+                length: PacketLength::Indeterminate,
+            },
+        ];
+
+        for header in headers.iter() {
+            let encoded = header.to_bytes().unwrap();
+            let (decoded, size) = PacketHeader::parse(&encoded).unwrap();
+
+            assert_eq!(&decoded, header);
+            assert_eq!(size, encoded.len());
+        }
+    }
+
+    #[test]
+    fn old_format_cannot_encode_large_tags() {
+        let header = PacketHeader {
+            format: PacketFormat::Old,
+            tag: PacketTag::SymmetricallyEncryptedIntegrityProtectedData,
+            length: PacketLength::Definite(0),
+        };
+
+        assert!(header.to_bytes().is_err());
+    }
+}
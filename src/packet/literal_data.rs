@@ -0,0 +1,503 @@
+use std::convert::TryInto;
+use std::io;
+use std::io::{Read, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{PacketBody, PacketTag};
+
+use crate::Error;
+
+/// The format of the data in a Literal Data packet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LiteralDataFormat {
+    /// `b`, binary data
+    Binary,
+
+    /// `t`, text data with <CR><LF> line endings
+    Text,
+
+    /// `u`, UTF-8 text data with <CR><LF> line endings
+    Utf8,
+}
+
+impl LiteralDataFormat {
+    pub fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            b'b' => Ok(Self::Binary),
+            b't' => Ok(Self::Text),
+            b'u' => Ok(Self::Utf8),
+            x => Err(format!("Unknown literal data format `{}`.", x as char).into()),
+        }
+    }
+
+    pub fn to_id(&self) -> u8 {
+        match self {
+            Self::Binary => b'b',
+            Self::Text => b't',
+            Self::Utf8 => b'u',
+        }
+    }
+
+    pub fn from_str(input: &str) -> Result<Self, Error> {
+        match &input.to_lowercase()[..] {
+            "binary" | "b" => Ok(Self::Binary),
+            "text" | "t" => Ok(Self::Text),
+            "utf8" | "utf-8" | "u" => Ok(Self::Utf8),
+            x => Err(format!("Unknown literal data format `{}`.", x).into()),
+        }
+    }
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            Self::Binary => "binary",
+            Self::Text => "text",
+            Self::Utf8 => "UTF-8",
+        }
+    }
+
+    /// Whether the data is text, with <CR><LF> line endings
+    pub fn is_text(&self) -> bool {
+        !matches!(self, Self::Binary)
+    }
+}
+
+/// Literal Data packet (tag 11)
+///
+/// Contains the body of a message; data that is not to be further
+/// interpreted.
+///
+/// # Links
+/// - [RFC 4880, Section 5.9](https://tools.ietf.org/html/rfc4880#section-5.9)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiteralData {
+    pub format: LiteralDataFormat,
+    pub file_name: String,
+
+    /// Seconds since the UNIX epoch, or 0 when unspecified.
+    pub date: u32,
+    pub data: Vec<u8>,
+}
+
+impl LiteralData {
+    /// Create a Literal Data packet holding binary data without file name
+    /// and date
+    pub fn binary(data: &[u8]) -> Self {
+        Self {
+            format: LiteralDataFormat::Binary,
+            file_name: String::new(),
+            date: 0,
+            data: data.to_vec(),
+        }
+    }
+
+    /// The file name, reduced to a name that is safe to create in the
+    /// current directory
+    ///
+    /// Only the last path component is kept, leading dots are removed so
+    /// the file is not hidden, and characters that are not allowed in file
+    /// names on common platforms are replaced. Names that Windows reserves
+    /// for devices, such as `NUL` or `com1.txt`, get a `_` prefix. Returns
+    /// `None` when nothing is left, or for the special name `_CONSOLE`,
+    /// which asks for the data not to be stored.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rpg::packet::LiteralData;
+    ///
+    /// let mut literal_data = LiteralData::binary(b"");
+    ///
+    /// literal_data.file_name = String::from("../../.ssh/authorized_keys");
+    /// assert_eq!(literal_data.safe_file_name().as_deref(), Some("authorized_keys"));
+    ///
+    /// literal_data.file_name = String::from("..");
+    /// assert_eq!(literal_data.safe_file_name(), None);
+    /// ```
+    pub fn safe_file_name(&self) -> Option<String> {
+        if self.file_name == "_CONSOLE" {
+            return None
+        }
+
+        let name: String = self.file_name
+            .rsplit(&['/', '\\'][..])
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .map(|c| match c {
+                '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect()
+        ;
+
+        let name = name
+            .trim_start_matches(|c: char| c == '.' || c.is_whitespace())
+            .trim_end_matches(|c: char| c == '.' || c.is_whitespace())
+        ;
+
+        match name.is_empty() {
+            true => None,
+            false if is_windows_device_name(name) => Some(format!("_{}", name)),
+            false => Some(String::from(name)),
+        }
+    }
+
+    /// The date as a point in time, or `None` when unspecified
+    pub fn modification_time(&self) -> Option<SystemTime> {
+        match self.date {
+            0 => None,
+            date => Some(UNIX_EPOCH + Duration::from_secs(date.into())),
+        }
+    }
+
+    /// Encode the body up to where the data starts
+    ///
+    /// Lets the data itself be streamed after it.
+    pub fn header_bytes(&self) -> Result<Vec<u8>, Error> {
+        let file_name = self.file_name.as_bytes();
+
+        if file_name.len() > 0xFF {
+            return Err(format!("File name `{}` is too long.", self.file_name).into())
+        }
+
+        let mut output = Vec::with_capacity(6 + file_name.len());
+        output.push(self.format.to_id());
+        output.push(file_name.len() as u8);
+        output.extend(file_name);
+        output.extend(&self.date.to_be_bytes());
+
+        Ok(output)
+    }
+
+    /// Read the body up to where the data starts
+    ///
+    /// The returned packet holds no data; the rest of the body is left in
+    /// the reader.
+    pub fn read_header<R: Read>(input: &mut R) -> Result<Self, Error> {
+        let mut octets = [0; 2];
+        input.read_exact(&mut octets)?;
+
+        let format = LiteralDataFormat::from_id(octets[0])?;
+
+        let mut file_name = vec![0; octets[1] as usize];
+        input.read_exact(&mut file_name)?;
+
+        let mut date = [0; 4];
+        input.read_exact(&mut date)?;
+
+        Ok(Self {
+            format,
+            file_name: String::from_utf8_lossy(&file_name).into_owned(),
+            date: u32::from_be_bytes(date),
+            data: vec![],
+        })
+    }
+}
+
+/// Whether Windows opens a device instead of a file for the name, which it
+/// does whatever the extension
+fn is_windows_device_name(name: &str) -> bool {
+    let stem = name
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .trim_end()
+        .to_ascii_uppercase()
+    ;
+
+    match stem.as_str() {
+        "CON" | "PRN" | "AUX" | "NUL" | "CONIN$" | "CONOUT$" => true,
+        _ => match stem.get(..3) {
+            Some("COM") | Some("LPT") => matches!(
+                &stem[3..],
+                "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" | "¹" | "²" | "³"
+            ),
+            _ => false,
+        },
+    }
+}
+
+impl PacketBody for LiteralData {
+    const TAG: PacketTag = PacketTag::LiteralData;
+
+    fn from_body(body: &[u8]) -> Result<Self, Error> {
+        let eof = "Unexpected end of literal data packet.";
+
+        let format = LiteralDataFormat::from_id(*body.first().ok_or(eof)?)?;
+        let file_name_length = *body.get(1).ok_or(eof)? as usize;
+        let file_name = body.get(2..2 + file_name_length).ok_or(eof)?;
+
+        let offset = 2 + file_name_length;
+        let date: [u8; 4] = body.get(offset..offset + 4).ok_or(eof)?.try_into()?;
+
+        Ok(Self {
+            format,
+            file_name: String::from_utf8_lossy(file_name).into_owned(),
+            date: u32::from_be_bytes(date),
+            data: body[offset + 4..].to_vec(),
+        })
+    }
+
+    fn to_body(&self) -> Result<Vec<u8>, Error> {
+        Ok([self.header_bytes()?, self.data.clone()].concat())
+    }
+}
+
+/// Converts line endings to <CR><LF> while reading, as required for the
+/// data of text formats
+///
+/// Existing <CR><LF> line endings are kept as they are.
+pub struct CanonicalTextReader<R: Read> {
+    input: R,
+    buffer: Vec<u8>,
+    last_was_cr: bool,
+}
+
+impl<R: Read> CanonicalTextReader<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            input,
+            buffer: vec![],
+            last_was_cr: false,
+        }
+    }
+}
+
+impl<R: Read> Read for CanonicalTextReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if buffer.is_empty() {
+            return Ok(0)
+        }
+
+        while self.buffer.is_empty() {
+            let mut chunk = vec![0; buffer.len()];
+            let n = self.input.read(&mut chunk)?;
+
+            if n == 0 {
+                return Ok(0)
+            }
+
+            for &octet in &chunk[..n] {
+                if octet == b'\n' && !self.last_was_cr {
+                    self.buffer.push(b'\r');
+                }
+
+                self.buffer.push(octet);
+                self.last_was_cr = octet == b'\r';
+            }
+        }
+
+        let n = self.buffer.len().min(buffer.len());
+        buffer[..n].copy_from_slice(&self.buffer[..n]);
+        self.buffer.drain(..n);
+
+        Ok(n)
+    }
+}
+
+/// Writes the data of a Literal Data packet with native line endings
+///
+/// For text formats, <CR><LF> is converted to <LF>, except on Windows.
+/// Binary data is written as it is.
+pub struct NativeTextWriter<W: Write> {
+    output: W,
+    convert: bool,
+    pending_cr: bool,
+}
+
+impl<W: Write> NativeTextWriter<W> {
+    pub fn new(output: W, format: LiteralDataFormat) -> Self {
+        Self {
+            output,
+            convert: format.is_text() && !cfg!(windows),
+            pending_cr: false,
+        }
+    }
+
+    /// Write a <CR> held back at the end of the data, flush, and return
+    /// the underlying writer
+    pub fn finish(mut self) -> Result<W, Error> {
+        if self.pending_cr {
+            self.output.write_all(b"\r")?;
+        }
+
+        self.output.flush()?;
+
+        Ok(self.output)
+    }
+}
+
+impl<W: Write> Write for NativeTextWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if !self.convert {
+            return self.output.write(data)
+        }
+
+        let mut converted = Vec::with_capacity(data.len() + 1);
+
+        for &octet in data {
+            if self.pending_cr && octet != b'\n' {
+                converted.push(b'\r');
+            }
+
+            self.pending_cr = octet == b'\r';
+
+            if !self.pending_cr {
+                converted.push(octet);
+            }
+        }
+
+        self.output.write_all(&converted)?;
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_body() {
+        let body = b"b\x08file.txt\x5F\x00\x00\x00Hello";
+        let packet = LiteralData::from_body(body).unwrap();
+
+        assert_eq!(packet.format, LiteralDataFormat::Binary);
+        assert_eq!(packet.file_name, "file.txt");
+        assert_eq!(packet.date, 0x5F000000);
+        assert_eq!(packet.data, b"Hello");
+    }
+
+    #[test]
+    fn parse_fails_for_truncated_body() {
+        assert!(LiteralData::from_body(b"b\x08file").is_err());
+        assert!(LiteralData::from_body(b"b\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn parse_fails_for_unknown_format() {
+        assert!(LiteralData::from_body(b"x\x00\x00\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn read_header_leaves_data() {
+        let body = b"b\x08file.txt\x5F\x00\x00\x00Hello";
+        let mut input = &body[..];
+
+        let packet = LiteralData::read_header(&mut input).unwrap();
+
+        assert_eq!(packet.file_name, "file.txt");
+        assert_eq!(packet.date, 0x5F000000);
+        assert!(packet.data.is_empty());
+        assert_eq!(input, b"Hello");
+    }
+
+    #[test]
+    fn format_from_str() {
+        assert_eq!(LiteralDataFormat::from_str("text").unwrap(), LiteralDataFormat::Text);
+        assert_eq!(LiteralDataFormat::from_str("UTF8").unwrap(), LiteralDataFormat::Utf8);
+        assert!(LiteralDataFormat::from_str("mime").is_err());
+    }
+
+    #[test]
+    fn safe_file_name() {
+        let name = |file_name: &str| LiteralData {
+            file_name: String::from(file_name),
+            ..LiteralData::binary(b"")
+        }.safe_file_name();
+
+        assert_eq!(name("notes.txt").as_deref(), Some("notes.txt"));
+        assert_eq!(name("/etc/passwd").as_deref(), Some("passwd"));
+        assert_eq!(name("..\\..\\boot.ini").as_deref(), Some("boot.ini"));
+        assert_eq!(name(".bashrc").as_deref(), Some("bashrc"));
+        assert_eq!(name("C:evil\u{7}.txt").as_deref(), Some("C_evil_.txt"));
+        assert_eq!(name("dir/"), None);
+        assert_eq!(name(" .. "), None);
+        assert_eq!(name("_CONSOLE"), None);
+        assert_eq!(name("CON").as_deref(), Some("_CON"));
+        assert_eq!(name("nul.txt").as_deref(), Some("_nul.txt"));
+        assert_eq!(name("Aux .tar.gz").as_deref(), Some("_Aux .tar.gz"));
+        assert_eq!(name("COM1").as_deref(), Some("_COM1"));
+        assert_eq!(name("lpt9.log").as_deref(), Some("_lpt9.log"));
+        assert_eq!(name("COM¹").as_deref(), Some("_COM¹"));
+        assert_eq!(name("conout$").as_deref(), Some("_conout$"));
+        assert_eq!(name("COM10").as_deref(), Some("COM10"));
+        assert_eq!(name("console.txt").as_deref(), Some("console.txt"));
+        assert_eq!(name("nul_file").as_deref(), Some("nul_file"));
+        assert_eq!(name(""), None);
+    }
+
+    #[test]
+    fn modification_time() {
+        let mut packet = LiteralData::binary(b"");
+        assert_eq!(packet.modification_time(), None);
+
+        packet.date = 1_600_000_000;
+        assert_eq!(packet.modification_time(), Some(UNIX_EPOCH + Duration::from_secs(1_600_000_000)));
+    }
+
+    #[test]
+    fn canonical_text_reader_adds_carriage_returns() {
+        let input = b"one\ntwo\r\nthree\n\n";
+        let mut output = vec![];
+
+        // Reads of one octet, so a <CR><LF> is split between reads
+        let mut reader = CanonicalTextReader::new(&input[..]);
+        let mut octet = [0];
+
+        while reader.read(&mut octet).unwrap() > 0 {
+            output.push(octet[0]);
+        }
+
+        assert_eq!(output, b"one\r\ntwo\r\nthree\r\n\r\n");
+    }
+
+    #[test]
+    fn native_text_writer_converts_text_only() {
+        let input = b"one\r\ntwo\rthree\r";
+
+        let mut writer = NativeTextWriter::new(vec![], LiteralDataFormat::Utf8);
+
+        for octet in input.iter() {
+            writer.write_all(&[*octet]).unwrap();
+        }
+
+        let expected: &[u8] = match cfg!(windows) {
+            true => input,
+            false => b"one\ntwo\rthree\r",
+        };
+
+        assert_eq!(writer.finish().unwrap(), expected);
+
+        let mut writer = NativeTextWriter::new(vec![], LiteralDataFormat::Binary);
+        writer.write_all(input).unwrap();
+
+        assert_eq!(writer.finish().unwrap(), input);
+    }
+
+    #[test]
+    fn binary_without_data() {
+        let packet = LiteralData::binary(b"");
+
+        assert_eq!(packet.to_body().unwrap(), b"b\x00\x00\x00\x00\x00");
+    }
+
+    #[test]
+    fn packet_round_trip() {
+        let packet = LiteralData {
+            format: LiteralDataFormat::Utf8,
+            file_name: String::from("notes.md"),
+            date: 1_600_000_000,
+            data: b"# Notes\r\n".to_vec(),
+        };
+
+        let encoded = packet.to_bytes().unwrap();
+        assert_eq!(encoded[0], 0xCB);
+
+        assert_eq!(LiteralData::from_body(&encoded[2..]).unwrap(), packet);
+    }
+}
@@ -0,0 +1,295 @@
+use std::convert::TryInto;
+use std::io;
+use std::io::{Read, Write};
+
+use sha::sha1::Sha1;
+
+use super::{PacketBody, PacketHeader, PacketTag};
+
+use crate::into_io_error;
+use crate::Error;
+
+/// Modification Detection Code packet (tag 19)
+///
+/// The SHA-1 hash of the plaintext of a Sym. Encrypted Integrity Protected
+/// Data packet. It is always the last packet of that plaintext, and the
+/// hash covers the random prefix, all preceding packets and the first two
+/// octets of this packet's own header.
+///
+/// # Links
+/// - [RFC 4880, Section 5.14](https://tools.ietf.org/html/rfc4880#section-5.14)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModificationDetectionCode {
+    pub hash: [u8; Sha1::OUTPUT_SIZE],
+}
+
+impl ModificationDetectionCode {
+    /// The length of the encoded packet, header included
+    pub const PACKET_LENGTH: usize = 2 + Sha1::OUTPUT_SIZE;
+
+    /// Compute the code for the prefix and packets that precede it
+    pub fn compute(data: &[u8]) -> Result<Self, Error> {
+        let mut hasher = Sha1::new();
+        hasher.update(data);
+
+        Self::finalize(hasher)
+    }
+
+    /// Check the code against the prefix and packets that precede it
+    pub fn verify(&self, data: &[u8]) -> Result<bool, Error> {
+        Ok(self.matches(&Self::compute(data)?))
+    }
+
+    /// Hash this packet's own header and finish the code
+    fn finalize(mut hasher: Sha1) -> Result<Self, Error> {
+        hasher.update(&PacketHeader::new(Self::TAG, Sha1::OUTPUT_SIZE).to_bytes()?);
+
+        Ok(Self {
+            hash: hasher.finalize(),
+        })
+    }
+
+    fn matches(&self, expected: &Self) -> bool {
+        // Compare all octets, rather than stopping at the first difference
+        let difference = self.hash.iter()
+            .zip(expected.hash.iter())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+        ;
+
+        difference == 0
+    }
+}
+
+impl PacketBody for ModificationDetectionCode {
+    const TAG: PacketTag = PacketTag::ModificationDetectionCode;
+
+    fn from_body(body: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            hash: body.try_into().map_err(|_| format!(
+                "Modification detection code must be {} octets, found {}.",
+                Sha1::OUTPUT_SIZE,
+                body.len()
+            ))?,
+        })
+    }
+
+    fn to_body(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.hash.to_vec())
+    }
+}
+
+/// Hashes everything written to it, and appends a Modification Detection
+/// Code packet on `finish`
+///
+/// The random prefix and all packets must be written through it.
+pub struct ModificationDetectionCodeWriter<W: Write> {
+    output: W,
+    hasher: Sha1,
+}
+
+impl<W: Write> ModificationDetectionCodeWriter<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            output,
+            hasher: Sha1::new(),
+        }
+    }
+
+    /// Write the Modification Detection Code packet and return the
+    /// underlying writer
+    pub fn finish(mut self) -> Result<W, Error> {
+        let mdc = ModificationDetectionCode::finalize(self.hasher)?;
+        self.output.write_all(&mdc.to_bytes()?)?;
+
+        Ok(self.output)
+    }
+}
+
+impl<W: Write> Write for ModificationDetectionCodeWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.output.write_all(data)?;
+        self.hasher.update(data);
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// Reads decrypted integrity protected data, and checks the Modification
+/// Detection Code packet at its end
+///
+/// The last `PACKET_LENGTH` octets are held back, so only the prefix and
+/// the packets it protects are returned. Reaching the end of the data fails
+/// if the code is missing or does not match; any data read before that
+/// must not be trusted until then.
+pub struct ModificationDetectionCodeReader<R: Read> {
+    input: R,
+    hasher: Option<Sha1>,
+    buffer: Vec<u8>,
+    error: Option<String>,
+}
+
+impl<R: Read> ModificationDetectionCodeReader<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            input,
+            hasher: Some(Sha1::new()),
+            buffer: Vec::with_capacity(ModificationDetectionCode::PACKET_LENGTH),
+            error: None,
+        }
+    }
+
+    fn verify(&mut self) -> Result<(), Error> {
+        let hasher = match self.hasher.take() {
+            Some(hasher) => hasher,
+            None => return Ok(()),
+        };
+
+        if self.buffer.len() < ModificationDetectionCode::PACKET_LENGTH {
+            return Err("Integrity protected data is too short to hold a modification detection code.".into())
+        }
+
+        let header = PacketHeader::new(ModificationDetectionCode::TAG, Sha1::OUTPUT_SIZE).to_bytes()?;
+
+        if self.buffer[..2] != header[..] {
+            return Err("No modification detection code found. The message may have been tampered with.".into())
+        }
+
+        let mdc = ModificationDetectionCode::from_body(&self.buffer[2..])?;
+
+        if !mdc.matches(&ModificationDetectionCode::finalize(hasher)?) {
+            return Err("Modification detection code mismatch. The message has been tampered with or is corrupt.".into())
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for ModificationDetectionCodeReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if let Some(error) = &self.error {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, error.clone()))
+        }
+
+        if buffer.is_empty() {
+            return Ok(0)
+        }
+
+        loop {
+            let available = self.buffer.len().saturating_sub(ModificationDetectionCode::PACKET_LENGTH);
+
+            if available > 0 {
+                let n = available.min(buffer.len());
+                buffer[..n].copy_from_slice(&self.buffer[..n]);
+
+                if let Some(hasher) = &mut self.hasher {
+                    hasher.update(&buffer[..n]);
+                }
+
+                self.buffer.drain(..n);
+
+                return Ok(n)
+            }
+
+            let mut chunk = vec![0; buffer.len()];
+            let n = self.input.read(&mut chunk)?;
+
+            if n == 0 {
+                return match self.verify() {
+                    Ok(()) => Ok(0),
+                    Err(error) => {
+                        self.error = Some(error.to_string());
+                        Err(into_io_error(error))
+                    },
+                }
+            }
+
+            self.buffer.extend(&chunk[..n]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+
+    #[test]
+    fn compute_hashes_data_and_header() {
+        let mdc = ModificationDetectionCode::compute(b"abc").unwrap();
+
+        assert_eq!(mdc.hash, Sha1::digest(b"abc\xD3\x14"));
+    }
+
+    #[test]
+    fn verify() {
+        let mdc = ModificationDetectionCode::compute(b"abc").unwrap();
+
+        assert!(mdc.verify(b"abc").unwrap());
+        assert!(!mdc.verify(b"abd").unwrap());
+    }
+
+    #[test]
+    fn writer_appends_code() {
+        let mut writer = ModificationDetectionCodeWriter::new(Vec::new());
+        writer.write_all(b"ab").unwrap();
+        writer.write_all(b"c").unwrap();
+
+        let output = writer.finish().unwrap();
+        let mdc = ModificationDetectionCode::compute(b"abc").unwrap();
+
+        assert_eq!(output, [&b"abc"[..], &mdc.to_bytes().unwrap()].concat());
+    }
+
+    #[test]
+    fn reader_holds_back_and_verifies_code() {
+        let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let input = [data.clone(), ModificationDetectionCode::compute(&data).unwrap().to_bytes().unwrap()].concat();
+
+        let mut output = vec![];
+        ModificationDetectionCodeReader::new(&input[..]).read_to_end(&mut output).unwrap();
+
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn reader_fails_for_modified_data() {
+        let mut input = [&b"abc"[..], &ModificationDetectionCode::compute(b"abc").unwrap().to_bytes().unwrap()].concat();
+        input[1] ^= 0x01;
+
+        let mut reader = ModificationDetectionCodeReader::new(&input[..]);
+
+        assert!(reader.read_to_end(&mut vec![]).is_err());
+
+        // The error is not forgotten by later reads
+        assert!(reader.read(&mut [0; 8]).is_err());
+    }
+
+    #[test]
+    fn reader_fails_for_missing_code() {
+        assert!(ModificationDetectionCodeReader::new(&[0x42; 10][..]).read_to_end(&mut vec![]).is_err());
+        assert!(ModificationDetectionCodeReader::new(&[0x42; 40][..]).read_to_end(&mut vec![]).is_err());
+    }
+
+    #[test]
+    fn packet_round_trip() {
+        let mdc = ModificationDetectionCode {
+            hash: hex!("A9993E36 4706816A BA3E2571 7850C26C 9CD0D89D"),
+        };
+
+        let encoded = mdc.to_bytes().unwrap();
+        assert_eq!(encoded.len(), ModificationDetectionCode::PACKET_LENGTH);
+        assert_eq!(&encoded[..2], &[0xD3, 0x14]);
+
+        assert_eq!(ModificationDetectionCode::from_body(&encoded[2..]).unwrap(), mdc);
+    }
+
+    #[test]
+    fn parse_fails_for_wrong_length() {
+        assert!(ModificationDetectionCode::from_body(&[0; 19]).is_err());
+    }
+}
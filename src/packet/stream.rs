@@ -0,0 +1,260 @@
+use std::io;
+use std::io::{Read, Write};
+
+use super::header;
+use super::{PacketFormat, PacketHeader, PacketLength, PacketTag};
+
+use crate::into_io_error;
+use crate::Error;
+
+/// Writes a packet whose body length is not known in advance
+///
+/// The body is buffered up to `CHUNK_SIZE` octets at a time and written
+/// using partial body lengths. Bodies shorter than that get a regular
+/// header with a definite length.
+///
+/// # Examples
+/// ```rust
+/// use std::io::Write;
+///
+/// use rpg::packet::{LiteralData, PacketBody, PacketTag};
+/// use rpg::packet::stream::PacketWriter;
+///
+/// # fn main() -> Result<(), rpg::Error> {
+/// let literal_data = LiteralData::binary(b"Hello");
+///
+/// let mut writer = PacketWriter::new(Vec::new(), PacketTag::LiteralData);
+/// writer.write_all(&literal_data.to_body()?)?;
+///
+/// assert_eq!(writer.finish()?, literal_data.to_bytes()?);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Links
+/// - [RFC 4880, Section 4.2.2.4](https://tools.ietf.org/html/rfc4880#section-4.2.2.4)
+pub struct PacketWriter<W: Write> {
+    output: W,
+    tag: PacketTag,
+    buffer: Vec<u8>,
+    started: bool,
+}
+
+impl<W: Write> PacketWriter<W> {
+    /// The size of each partial body chunk
+    ///
+    /// Must be a power of two, and at least 512 octets for the first chunk.
+    pub const CHUNK_SIZE: usize = 1 << 16;
+
+    pub fn new(output: W, tag: PacketTag) -> Self {
+        Self {
+            output,
+            tag,
+            buffer: Vec::with_capacity(Self::CHUNK_SIZE),
+            started: false,
+        }
+    }
+
+    /// Write the remaining body with a definite length and return the
+    /// underlying writer
+    pub fn finish(mut self) -> Result<W, Error> {
+        if self.started {
+            self.output.write_all(&header::encode_length(self.buffer.len())?)?;
+        } else {
+            self.output.write_all(&PacketHeader::new(self.tag, self.buffer.len()).to_bytes()?)?;
+        }
+
+        self.output.write_all(&self.buffer)?;
+        self.output.flush()?;
+
+        Ok(self.output)
+    }
+
+    fn write_chunk(&mut self) -> Result<(), Error> {
+        if self.started {
+            self.output.write_all(&[header::encode_partial_length(Self::CHUNK_SIZE)?])?;
+        } else {
+            let header = PacketHeader {
+                format: PacketFormat::New,
+                tag: self.tag,
+                length: PacketLength::Partial(Self::CHUNK_SIZE),
+            };
+
+            self.output.write_all(&header.to_bytes()?)?;
+            self.started = true;
+        }
+
+        self.output.write_all(&self.buffer[..Self::CHUNK_SIZE])?;
+        self.buffer.drain(..Self::CHUNK_SIZE);
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for PacketWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend(data);
+
+        while self.buffer.len() >= Self::CHUNK_SIZE {
+            self.write_chunk().map_err(into_io_error)?;
+        }
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// Reads the body of a packet from a stream
+///
+/// Joins partial body lengths transparently. Ends at the end of the body,
+/// leaving the stream positioned at the next packet.
+pub struct PacketReader<R: Read> {
+    input: R,
+    length: PacketLength,
+    remaining: usize,
+}
+
+impl<R: Read> PacketReader<R> {
+    /// Read the body following the header, which has already been read
+    pub fn new(header: PacketHeader, input: R) -> Self {
+        let remaining = match header.length {
+            PacketLength::Definite(n) | PacketLength::Partial(n) => n,
+            PacketLength::Indeterminate => 0,
+        };
+
+        Self {
+            input,
+            length: header.length,
+            remaining,
+        }
+    }
+
+    /// Return the underlying stream
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+}
+
+impl<R: Read> Read for PacketReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if buffer.is_empty() {
+            return Ok(0)
+        }
+
+        loop {
+            if self.remaining > 0 {
+                let length = buffer.len().min(self.remaining);
+                let n = self.input.read(&mut buffer[..length])?;
+
+                if n == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "Unexpected end of input while reading packet body.",
+                    ))
+                }
+
+                self.remaining -= n;
+
+                return Ok(n)
+            }
+
+            match self.length {
+                PacketLength::Definite(_) => return Ok(0),
+                PacketLength::Indeterminate => return self.input.read(buffer),
+                PacketLength::Partial(_) => {
+                    self.length = header::read_new_format_length(&mut self.input).map_err(into_io_error)?;
+
+                    self.remaining = match self.length {
+                        PacketLength::Definite(n) | PacketLength::Partial(n) => n,
+                        PacketLength::Indeterminate => 0,
+                    };
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_packet(body: &[u8]) -> Vec<u8> {
+        let mut writer = PacketWriter::new(Vec::new(), PacketTag::LiteralData);
+
+        for chunk in body.chunks(1000) {
+            writer.write_all(chunk).unwrap();
+        }
+
+        writer.finish().unwrap()
+    }
+
+    fn read_packet(input: &[u8]) -> Vec<u8> {
+        let mut input = input;
+        let header = PacketHeader::read(&mut input).unwrap().unwrap();
+
+        let mut body = vec![];
+        let mut reader = PacketReader::new(header, &mut input);
+        reader.read_to_end(&mut body).unwrap();
+
+        assert!(input.is_empty());
+
+        body
+    }
+
+    #[test]
+    fn short_body_uses_definite_length() {
+        let packet = write_packet(&[0x42; 300]);
+
+        assert_eq!(&packet[..3], &[0xCB, 0xC0, 0x6C]);
+        assert_eq!(packet.len(), 303);
+    }
+
+    #[test]
+    fn long_body_uses_partial_lengths() {
+        let body: Vec<u8> = (0..200_000).map(|i| i as u8).collect();
+        let packet = write_packet(&body);
+
+        // Three full chunks of 2^16 octets, followed by the remainder
+        assert_eq!(&packet[..2], &[0xCB, 0xF0]);
+        assert_eq!(packet[2 + (1 << 16)], 0xF0);
+        assert_eq!(packet[3 + (2 << 16)], 0xF0);
+        assert_eq!(&packet[4 + (3 << 16)..][..2], &[0xCC, 0x80]);
+
+        assert_eq!(read_packet(&packet), body);
+    }
+
+    #[test]
+    fn body_of_exactly_one_chunk_ends_with_empty_part() {
+        let body = vec![0x42; 1 << 16];
+        let packet = write_packet(&body);
+
+        assert_eq!(packet[packet.len() - 1], 0x00);
+        assert_eq!(read_packet(&packet), body);
+    }
+
+    #[test]
+    fn reader_stops_at_end_of_body() {
+        let input = [0xCB, 0x02, b'a', b'b', 0xCB, 0x01, b'c'];
+        let mut input = &input[..];
+
+        let header = PacketHeader::read(&mut input).unwrap().unwrap();
+        let mut body = vec![];
+        PacketReader::new(header, &mut input).read_to_end(&mut body).unwrap();
+
+        assert_eq!(body, b"ab");
+        assert_eq!(input, &[0xCB, 0x01, b'c']);
+    }
+
+    #[test]
+    fn reader_fails_for_truncated_body() {
+        let input = [0xCB, 0x05, b'a', b'b'];
+        let mut input = &input[..];
+
+        let header = PacketHeader::read(&mut input).unwrap().unwrap();
+
+        assert!(PacketReader::new(header, &mut input).read_to_end(&mut vec![]).is_err());
+    }
+}
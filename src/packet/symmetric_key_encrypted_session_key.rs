@@ -0,0 +1,120 @@
+use super::{PacketBody, PacketTag};
+
+use crate::crypto::s2k::StringToKey;
+use crate::Error;
+use crate::SymmetricCipher;
+
+/// Symmetric-Key Encrypted Session Key packet (tag 3)
+///
+/// Describes how the key for the encrypted data that follows is derived
+/// from a passphrase. When no encrypted session key is present, the S2K
+/// output is used as the session key directly.
+///
+/// # Links
+/// - [RFC 4880, Section 5.3](https://tools.ietf.org/html/rfc4880#section-5.3)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymmetricKeyEncryptedSessionKey {
+    pub cipher: SymmetricCipher,
+    pub s2k: StringToKey,
+    pub encrypted_session_key: Option<Vec<u8>>,
+}
+
+impl SymmetricKeyEncryptedSessionKey {
+    /// The only version described by RFC 4880
+    const VERSION: u8 = 4;
+}
+
+impl PacketBody for SymmetricKeyEncryptedSessionKey {
+    const TAG: PacketTag = PacketTag::SymmetricKeyEncryptedSessionKey;
+
+    fn from_body(body: &[u8]) -> Result<Self, Error> {
+        let eof = "Unexpected end of symmetric-key encrypted session key packet.";
+
+        let version = *body.first().ok_or(eof)?;
+
+        if version != Self::VERSION {
+            return Err(format!(
+                "Unsupported symmetric-key encrypted session key packet version `{}`.",
+                version
+            ).into())
+        }
+
+        let cipher = SymmetricCipher::from_id(*body.get(1).ok_or(eof)?)?;
+        let (s2k, s2k_length) = StringToKey::parse(&body[2..])?;
+
+        let encrypted_session_key = match &body[2 + s2k_length..] {
+            [] => None,
+            x => Some(x.to_vec()),
+        };
+
+        Ok(Self {
+            cipher,
+            s2k,
+            encrypted_session_key,
+        })
+    }
+
+    fn to_body(&self) -> Result<Vec<u8>, Error> {
+        let mut output = vec![Self::VERSION, self.cipher.to_id()];
+        output.extend(self.s2k.to_bytes());
+
+        if let Some(encrypted_session_key) = &self.encrypted_session_key {
+            output.extend(encrypted_session_key);
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hash::HashAlgorithm;
+
+    #[test]
+    fn parse_body_without_session_key() {
+        let body = [0x04, 0x07, 0x03, 0x08, 1, 2, 3, 4, 5, 6, 7, 8, 0xFF];
+        let packet = SymmetricKeyEncryptedSessionKey::from_body(&body).unwrap();
+
+        assert_eq!(packet.cipher, SymmetricCipher::Aes128);
+        assert_eq!(packet.s2k, StringToKey::IteratedAndSalted {
+            hash: HashAlgorithm::Sha256,
+            salt: [1, 2, 3, 4, 5, 6, 7, 8],
+            count: 0xFF,
+        });
+        assert_eq!(packet.encrypted_session_key, None);
+    }
+
+    #[test]
+    fn parse_body_with_session_key() {
+        let body = [0x04, 0x09, 0x00, 0x02, 0xAA, 0xBB];
+        let packet = SymmetricKeyEncryptedSessionKey::from_body(&body).unwrap();
+
+        assert_eq!(packet.cipher, SymmetricCipher::Aes256);
+        assert_eq!(packet.encrypted_session_key, Some(vec![0xAA, 0xBB]));
+    }
+
+    #[test]
+    fn parse_fails_for_unknown_version() {
+        assert!(SymmetricKeyEncryptedSessionKey::from_body(&[0x05, 0x07, 0x00, 0x02]).is_err());
+    }
+
+    #[test]
+    fn parse_fails_for_unknown_cipher() {
+        assert!(SymmetricKeyEncryptedSessionKey::from_body(&[0x04, 0x01, 0x00, 0x02]).is_err());
+    }
+
+    #[test]
+    fn packet_round_trip() {
+        let packet = SymmetricKeyEncryptedSessionKey {
+            cipher: SymmetricCipher::Aes192,
+            s2k: StringToKey::Salted { hash: HashAlgorithm::Sha1, salt: [7; 8] },
+            encrypted_session_key: None,
+        };
+
+        let encoded = packet.to_bytes().unwrap();
+        assert_eq!(&encoded[..2], &[0xC3, 12]);
+
+        assert_eq!(SymmetricKeyEncryptedSessionKey::from_body(&encoded[2..]).unwrap(), packet);
+    }
+}
@@ -0,0 +1,47 @@
+use super::{PacketBody, PacketTag};
+
+use crate::Error;
+
+/// Symmetrically Encrypted Data packet (tag 9)
+///
+/// Holds the OpenPGP CFB encrypted packets that make up the message. The
+/// key is derived from the preceding Symmetric-Key Encrypted Session Key
+/// packet.
+///
+/// # Links
+/// - [RFC 4880, Section 5.7](https://tools.ietf.org/html/rfc4880#section-5.7)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymmetricallyEncryptedData {
+    pub ciphertext: Vec<u8>,
+}
+
+impl PacketBody for SymmetricallyEncryptedData {
+    const TAG: PacketTag = PacketTag::SymmetricallyEncryptedData;
+
+    fn from_body(body: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            ciphertext: body.to_vec(),
+        })
+    }
+
+    fn to_body(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.ciphertext.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packet_round_trip() {
+        let packet = SymmetricallyEncryptedData {
+            ciphertext: vec![0x42; 300],
+        };
+
+        let encoded = packet.to_bytes().unwrap();
+        assert_eq!(&encoded[..3], &[0xC9, 0xC0, 0x6C]);
+
+        assert_eq!(SymmetricallyEncryptedData::from_body(&encoded[3..]).unwrap(), packet);
+    }
+}
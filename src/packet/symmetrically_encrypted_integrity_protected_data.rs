@@ -0,0 +1,80 @@
+use super::{PacketBody, PacketTag};
+
+use crate::Error;
+
+/// Sym. Encrypted Integrity Protected Data packet (tag 18)
+///
+/// Like the Symmetrically Encrypted Data packet, but encrypted without the
+/// OpenPGP CFB resync step, and with a Modification Detection Code packet
+/// as the last of the encrypted packets.
+///
+/// # Links
+/// - [RFC 4880, Section 5.13](https://tools.ietf.org/html/rfc4880#section-5.13)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymmetricallyEncryptedIntegrityProtectedData {
+    pub ciphertext: Vec<u8>,
+}
+
+impl SymmetricallyEncryptedIntegrityProtectedData {
+    /// The only version described by RFC 4880
+    pub const VERSION: u8 = 1;
+
+    /// Check the version octet at the start of the packet body
+    pub fn check_version(version: u8) -> Result<(), Error> {
+        if version != Self::VERSION {
+            return Err(format!(
+                "Unsupported integrity protected data packet version `{}`.",
+                version
+            ).into())
+        }
+
+        Ok(())
+    }
+}
+
+impl PacketBody for SymmetricallyEncryptedIntegrityProtectedData {
+    const TAG: PacketTag = PacketTag::SymmetricallyEncryptedIntegrityProtectedData;
+
+    fn from_body(body: &[u8]) -> Result<Self, Error> {
+        let version = *body.first()
+            .ok_or("Unexpected end of integrity protected data packet.")?
+        ;
+
+        Self::check_version(version)?;
+
+        Ok(Self {
+            ciphertext: body[1..].to_vec(),
+        })
+    }
+
+    fn to_body(&self) -> Result<Vec<u8>, Error> {
+        Ok([&[Self::VERSION], &self.ciphertext[..]].concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packet_round_trip() {
+        let packet = SymmetricallyEncryptedIntegrityProtectedData {
+            ciphertext: vec![0x42; 300],
+        };
+
+        let encoded = packet.to_bytes().unwrap();
+        assert_eq!(&encoded[..4], &[0xD2, 0xC0, 0x6D, 0x01]);
+
+        assert_eq!(SymmetricallyEncryptedIntegrityProtectedData::from_body(&encoded[3..]).unwrap(), packet);
+    }
+
+    #[test]
+    fn parse_fails_for_unknown_version() {
+        assert!(SymmetricallyEncryptedIntegrityProtectedData::from_body(&[0x02, 0x42]).is_err());
+    }
+
+    #[test]
+    fn parse_fails_for_empty_body() {
+        assert!(SymmetricallyEncryptedIntegrityProtectedData::from_body(&[]).is_err());
+    }
+}
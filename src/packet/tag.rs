@@ -0,0 +1,149 @@
+/// OpenPGP packet tags
+///
+/// The tag identifies the type of the packet body that follows the header.
+///
+/// # Links
+/// - [RFC 4880, Section 4.3](https://tools.ietf.org/html/rfc4880#section-4.3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketTag {
+    /// Public-Key Encrypted Session Key Packet
+    PublicKeyEncryptedSessionKey,
+
+    /// Signature Packet
+    Signature,
+
+    /// Symmetric-Key Encrypted Session Key Packet
+    SymmetricKeyEncryptedSessionKey,
+
+    /// One-Pass Signature Packet
+    OnePassSignature,
+
+    /// Secret-Key Packet
+    SecretKey,
+
+    /// Public-Key Packet
+    PublicKey,
+
+    /// Secret-Subkey Packet
+    SecretSubkey,
+
+    /// Compressed Data Packet
+    CompressedData,
+
+    /// Symmetrically Encrypted Data Packet
+    SymmetricallyEncryptedData,
+
+    /// Marker Packet (Obsolete Literal Packet)
+    Marker,
+
+    /// Literal Data Packet
+    LiteralData,
+
+    /// Trust Packet
+    Trust,
+
+    /// User ID Packet
+    UserId,
+
+    /// Public-Subkey Packet
+    PublicSubkey,
+
+    /// User Attribute Packet
+    UserAttribute,
+
+    /// Sym. Encrypted and Integrity Protected Data Packet
+    SymmetricallyEncryptedIntegrityProtectedData,
+
+    /// Modification Detection Code Packet
+    ModificationDetectionCode,
+
+    /// Reserved, private or experimental tags
+    Unknown(u8),
+}
+
+impl PacketTag {
+    /// Get the packet tag from its numeric value
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rpg::packet::PacketTag;
+    ///
+    /// assert_eq!(PacketTag::from_id(11), PacketTag::LiteralData);
+    /// assert_eq!(PacketTag::from_id(61), PacketTag::Unknown(61));
+    /// ```
+    pub fn from_id(id: u8) -> Self {
+        match id {
+            1 => Self::PublicKeyEncryptedSessionKey,
+            2 => Self::Signature,
+            3 => Self::SymmetricKeyEncryptedSessionKey,
+            4 => Self::OnePassSignature,
+            5 => Self::SecretKey,
+            6 => Self::PublicKey,
+            7 => Self::SecretSubkey,
+            8 => Self::CompressedData,
+            9 => Self::SymmetricallyEncryptedData,
+            10 => Self::Marker,
+            11 => Self::LiteralData,
+            12 => Self::Trust,
+            13 => Self::UserId,
+            14 => Self::PublicSubkey,
+            17 => Self::UserAttribute,
+            18 => Self::SymmetricallyEncryptedIntegrityProtectedData,
+            19 => Self::ModificationDetectionCode,
+            x => Self::Unknown(x),
+        }
+    }
+
+    /// Get the numeric value of the packet tag
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rpg::packet::PacketTag;
+    ///
+    /// assert_eq!(PacketTag::LiteralData.to_id(), 11);
+    /// ```
+    pub fn to_id(&self) -> u8 {
+        match self {
+            Self::PublicKeyEncryptedSessionKey => 1,
+            Self::Signature => 2,
+            Self::SymmetricKeyEncryptedSessionKey => 3,
+            Self::OnePassSignature => 4,
+            Self::SecretKey => 5,
+            Self::PublicKey => 6,
+            Self::SecretSubkey => 7,
+            Self::CompressedData => 8,
+            Self::SymmetricallyEncryptedData => 9,
+            Self::Marker => 10,
+            Self::LiteralData => 11,
+            Self::Trust => 12,
+            Self::UserId => 13,
+            Self::PublicSubkey => 14,
+            Self::UserAttribute => 17,
+            Self::SymmetricallyEncryptedIntegrityProtectedData => 18,
+            Self::ModificationDetectionCode => 19,
+            Self::Unknown(x) => *x,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PacketTag;
+
+    #[test]
+    fn id_to_enum_for_skesk() {
+        assert_eq!(PacketTag::from_id(3), PacketTag::SymmetricKeyEncryptedSessionKey);
+    }
+
+    #[test]
+    fn id_to_enum_for_reserved_tag() {
+        assert_eq!(PacketTag::from_id(0), PacketTag::Unknown(0));
+    }
+
+    #[test]
+    fn ids_round_trip() {
+        for id in 0..64 {
+            assert_eq!(PacketTag::from_id(id).to_id(), id);
+        }
+    }
+}
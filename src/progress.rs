@@ -0,0 +1,96 @@
+// Progress reporting
+//
+// The library never prints. Callers that want to show progress wrap their
+// input in a `ProgressReader` with an observer of their choice.
+use std::io;
+use std::io::Read;
+
+/// Observes how much of an input has been processed
+///
+/// Implemented for closures taking the same arguments as `update`.
+pub trait Progress {
+    /// Called after each chunk read, with the total number of octets read
+    /// so far, and the size of the input when known
+    fn update(&mut self, processed: u64, total: Option<u64>);
+}
+
+impl<F: FnMut(u64, Option<u64>)> Progress for F {
+    fn update(&mut self, processed: u64, total: Option<u64>) {
+        self(processed, total)
+    }
+}
+
+/// Reports the progress of reading from the underlying reader
+///
+/// # Examples
+/// ```rust
+/// use std::io::Read;
+///
+/// use rpg::progress::ProgressReader;
+///
+/// # fn main() -> Result<(), rpg::Error> {
+/// let input = [0x42; 1000];
+/// let mut last = 0;
+///
+/// let mut reader = ProgressReader::new(&input[..], Some(1000), |processed, _| last = processed);
+/// reader.read_to_end(&mut vec![])?;
+///
+/// assert_eq!(last, 1000);
+/// # Ok(())
+/// # }
+/// ```
+pub struct ProgressReader<R: Read, P: Progress> {
+    input: R,
+    observer: P,
+    processed: u64,
+    total: Option<u64>,
+}
+
+impl<R: Read, P: Progress> ProgressReader<R, P> {
+    pub fn new(input: R, total: Option<u64>, observer: P) -> Self {
+        Self {
+            input,
+            observer,
+            processed: 0,
+            total,
+        }
+    }
+
+    /// Return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+}
+
+impl<R: Read, P: Progress> Read for ProgressReader<R, P> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let n = self.input.read(buffer)?;
+
+        if n > 0 {
+            self.processed += n as u64;
+            self.observer.update(self.processed, self.total);
+        }
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_octets_read_so_far() {
+        let input = [0x42; 100];
+        let mut updates = vec![];
+
+        let mut reader = ProgressReader::new(&input[..], Some(100), |processed, total| {
+            updates.push((processed, total))
+        });
+
+        let mut chunk = [0; 40];
+        while reader.read(&mut chunk).unwrap() > 0 {}
+
+        assert_eq!(updates, vec![(40, Some(100)), (80, Some(100)), (100, Some(100))]);
+    }
+}
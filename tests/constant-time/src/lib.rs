@@ -207,3 +207,68 @@ pub fn measure_aes_add_round_key(bench_count: usize, plot_image_path: &Path) {
         println!(" ✓");
     println!("Finished benchmark for aes_add_round_key.");
 }
+
+pub fn measure_base64_decode_constant_time(bench_count: usize, plot_image_path: &Path) {
+    println!("Started benchmark for base64_decode_constant_time.");
+        println!("\tStarted M1: fixed measurements.");
+            print!("\t\tSetting up Bencher...");
+            let mut bencher = Bencher::new(bench_count);
+            println!(" ✓");
+
+            let symbols = base64::Alphabet::STANDARD.symbols();
+            let encoded = base64::encode(&random::<[u8; 24]>());
+            let mut buffer = vec![0u8; encoded.len()];
+
+            print!("\t\tRunning `{}` iterations...", bench_count); std::io::stdout().flush().unwrap();
+            for _ in 0..bench_count {
+                buffer.copy_from_slice(&encoded);
+
+                let start = Start::now();
+                    let _converted = base64::convert_sextets_to_octets_constant_time(&mut buffer, symbols);
+                let stop = Stop::now();
+
+                let duration: Duration = stop - start;
+                &bencher.measurements.push(duration.cycles().into());
+            }
+            println!(" ✓");
+
+            print!("\t\tCalculating CDF values..."); std::io::stdout().flush().unwrap();
+            let fixed_cdf = bencher.calc_cdf();
+            println!(" ✓");
+        println!("\tFinished M1: fixed measurements.");
+
+        println!("\tStarted M2: random measurements.");
+            print!("\t\tSetting up Bencher...");
+            let mut bencher = Bencher::new(bench_count);
+            println!(" ✓");
+
+            print!("\t\tRunning `{}` iterations...", bench_count); std::io::stdout().flush().unwrap();
+            for _ in 0..bench_count {
+                let encoded = base64::encode(&random::<[u8; 24]>());
+                buffer.copy_from_slice(&encoded);
+
+                let start = Start::now();
+                    let _converted = base64::convert_sextets_to_octets_constant_time(&mut buffer, symbols);
+                let stop = Stop::now();
+
+                let duration: Duration = stop - start;
+                &bencher.measurements.push(duration.cycles().into());
+            }
+            println!(" ✓");
+
+            print!("\t\tCalculating CDF values..."); std::io::stdout().flush().unwrap();
+            let random_cdf = bencher.calc_cdf();
+            println!(" ✓");
+        println!("\tFinished M2: random measurements.");
+
+        print!("\tPlotting..."); std::io::stdout().flush().unwrap();
+            Plotter {
+                chart_title: format!("base64_decode_constant_time, {} iterations", bench_count),
+                chart_x_spec: 700f32..2000f32,
+                data_fixed: fixed_cdf,
+                data_random: random_cdf,
+                image_path: plot_image_path.into(),
+            }.plot().unwrap();
+        println!(" ✓");
+    println!("Finished benchmark for base64_decode_constant_time.");
+}